@@ -0,0 +1,25 @@
+CREATE TABLE task_dependencies (
+    -- the task that has to be completed first
+    parent_id TEXT NOT NULL,
+    -- the task waiting on the parent
+    child_id TEXT NOT NULL,
+    PRIMARY KEY (parent_id, child_id),
+    FOREIGN KEY (parent_id) REFERENCES tasks (id) ON DELETE CASCADE,
+    FOREIGN KEY (child_id) REFERENCES tasks (id) ON DELETE CASCADE
+);
+
+-- carry over the single-child links that are already stored on tasks
+INSERT INTO
+    task_dependencies (parent_id, child_id)
+SELECT
+    id,
+    child_task_id
+FROM
+    tasks
+WHERE
+    child_task_id IN (
+        SELECT
+            id
+        FROM
+            tasks
+    );
@@ -0,0 +1,7 @@
+CREATE TABLE task_conditions (
+    task_id TEXT NOT NULL,
+    condition_name TEXT NOT NULL,
+    PRIMARY KEY (task_id, condition_name),
+    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE,
+    FOREIGN KEY (condition_name) REFERENCES conditions (name) ON UPDATE CASCADE
+);
@@ -0,0 +1,5 @@
+DROP TABLE journal_entries;
+
+DROP TABLE journal_changes;
+
+DROP TABLE journal_state;
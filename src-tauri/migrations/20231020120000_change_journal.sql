@@ -0,0 +1,28 @@
+-- a change groups the journal entries written by one command, so it can be undone
+-- and redone as a whole
+CREATE TABLE journal_changes (
+    id INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    undone INTEGER NOT NULL,
+    PRIMARY KEY (id)
+);
+
+-- written by triggers on every journaled table, which are set up on connect; entries
+-- without a change belong to a command that hasn't finished yet
+CREATE TABLE journal_entries (
+    id INTEGER NOT NULL,
+    change_id INTEGER NULL,
+    undo_sql TEXT NOT NULL,
+    redo_sql TEXT NOT NULL,
+    PRIMARY KEY (id),
+    FOREIGN KEY (change_id) REFERENCES journal_changes (id) ON DELETE CASCADE
+);
+
+-- the triggers stay quiet while a change is being undone or redone
+CREATE TABLE journal_state (replaying INTEGER NOT NULL);
+
+INSERT INTO
+    journal_state (replaying)
+VALUES
+    (0);
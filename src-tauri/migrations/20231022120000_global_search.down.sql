@@ -0,0 +1,33 @@
+DROP TRIGGER search_genes_insert;
+
+DROP TRIGGER search_genes_delete;
+
+DROP TRIGGER search_genes_update;
+
+DROP TRIGGER search_alleles_insert;
+
+DROP TRIGGER search_alleles_delete;
+
+DROP TRIGGER search_alleles_update;
+
+DROP TRIGGER search_strains_insert;
+
+DROP TRIGGER search_strains_delete;
+
+DROP TRIGGER search_strains_update;
+
+DROP TRIGGER search_phenotypes_insert;
+
+DROP TRIGGER search_phenotypes_delete;
+
+DROP TRIGGER search_phenotypes_update;
+
+DROP TRIGGER search_cross_designs_insert;
+
+DROP TRIGGER search_cross_designs_delete;
+
+DROP TRIGGER search_cross_designs_update;
+
+DROP TABLE search_index_keys;
+
+DROP TABLE search_index;
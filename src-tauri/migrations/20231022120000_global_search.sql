@@ -0,0 +1,471 @@
+-- full-text index over the names and descriptions of genes, alleles, strains, phenotypes
+-- and cross designs; kind and key say which row a hit is, wild only applies to phenotypes
+CREATE VIRTUAL TABLE search_index USING fts5(
+    kind UNINDEXED,
+    key UNINDEXED,
+    wild UNINDEXED,
+    name,
+    description
+);
+
+-- the rowid of each row's entry in search_index, since FTS5 can only find unindexed
+-- columns by scanning the whole index
+CREATE TABLE search_index_keys (
+    id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    key TEXT NOT NULL,
+    wild INTEGER NULL,
+    PRIMARY KEY (id)
+);
+
+CREATE INDEX search_index_keys_kind_key ON search_index_keys (kind, key);
+
+INSERT INTO
+    search_index_keys (kind, key)
+SELECT
+    'gene',
+    systematic_name
+FROM
+    genes;
+
+INSERT INTO
+    search_index_keys (kind, key)
+SELECT
+    'allele',
+    name
+FROM
+    alleles;
+
+INSERT INTO
+    search_index_keys (kind, key)
+SELECT
+    'strain',
+    name
+FROM
+    strains;
+
+INSERT INTO
+    search_index_keys (kind, key, wild)
+SELECT
+    'phenotype',
+    name,
+    wild
+FROM
+    phenotypes;
+
+INSERT INTO
+    search_index_keys (kind, key)
+SELECT
+    'cross_design',
+    id
+FROM
+    cross_designs;
+
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+SELECT
+    search_index_keys.id,
+    'gene',
+    genes.systematic_name,
+    genes.systematic_name || ' ' || coalesce(genes.descriptive_name, ''),
+    NULL
+FROM
+    genes
+    JOIN search_index_keys ON search_index_keys.kind = 'gene'
+    AND search_index_keys.key = genes.systematic_name;
+
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+SELECT
+    search_index_keys.id,
+    'allele',
+    alleles.name,
+    alleles.name,
+    alleles.contents
+FROM
+    alleles
+    JOIN search_index_keys ON search_index_keys.kind = 'allele'
+    AND search_index_keys.key = alleles.name;
+
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+SELECT
+    search_index_keys.id,
+    'strain',
+    strains.name,
+    strains.name || ' ' || strains.genotype,
+    strains.description
+FROM
+    strains
+    JOIN search_index_keys ON search_index_keys.kind = 'strain'
+    AND search_index_keys.key = strains.name;
+
+INSERT INTO
+    search_index (rowid, kind, key, wild, name, description)
+SELECT
+    search_index_keys.id,
+    'phenotype',
+    phenotypes.name,
+    phenotypes.wild,
+    phenotypes.name || ' ' || phenotypes.short_name,
+    phenotypes.description
+FROM
+    phenotypes
+    JOIN search_index_keys ON search_index_keys.kind = 'phenotype'
+    AND search_index_keys.key = phenotypes.name
+    AND search_index_keys.wild = phenotypes.wild;
+
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+SELECT
+    search_index_keys.id,
+    'cross_design',
+    cross_designs.id,
+    cross_designs.name,
+    NULL
+FROM
+    cross_designs
+    JOIN search_index_keys ON search_index_keys.kind = 'cross_design'
+    AND search_index_keys.key = cross_designs.id;
+
+-- the triggers keep the index in sync however the rows change, including undo and redo
+CREATE TRIGGER search_genes_insert AFTER INSERT ON genes BEGIN
+INSERT INTO
+    search_index_keys (kind, key)
+VALUES
+    ('gene', NEW.systematic_name);
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'gene',
+        NEW.systematic_name,
+        NEW.systematic_name || ' ' || coalesce(NEW.descriptive_name, ''),
+        NULL
+    );
+END;
+
+CREATE TRIGGER search_genes_delete AFTER DELETE ON genes BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'gene'
+            AND key = OLD.systematic_name
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'gene'
+    AND key = OLD.systematic_name;
+END;
+
+CREATE TRIGGER search_genes_update AFTER UPDATE ON genes BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'gene'
+            AND key = OLD.systematic_name
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'gene'
+    AND key = OLD.systematic_name;
+INSERT INTO
+    search_index_keys (kind, key)
+VALUES
+    ('gene', NEW.systematic_name);
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'gene',
+        NEW.systematic_name,
+        NEW.systematic_name || ' ' || coalesce(NEW.descriptive_name, ''),
+        NULL
+    );
+END;
+
+CREATE TRIGGER search_alleles_insert AFTER INSERT ON alleles BEGIN
+INSERT INTO
+    search_index_keys (kind, key)
+VALUES
+    ('allele', NEW.name);
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'allele',
+        NEW.name,
+        NEW.name,
+        NEW.contents
+    );
+END;
+
+CREATE TRIGGER search_alleles_delete AFTER DELETE ON alleles BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'allele'
+            AND key = OLD.name
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'allele'
+    AND key = OLD.name;
+END;
+
+CREATE TRIGGER search_alleles_update AFTER UPDATE ON alleles BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'allele'
+            AND key = OLD.name
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'allele'
+    AND key = OLD.name;
+INSERT INTO
+    search_index_keys (kind, key)
+VALUES
+    ('allele', NEW.name);
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'allele',
+        NEW.name,
+        NEW.name,
+        NEW.contents
+    );
+END;
+
+CREATE TRIGGER search_strains_insert AFTER INSERT ON strains BEGIN
+INSERT INTO
+    search_index_keys (kind, key)
+VALUES
+    ('strain', NEW.name);
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'strain',
+        NEW.name,
+        NEW.name || ' ' || NEW.genotype,
+        NEW.description
+    );
+END;
+
+CREATE TRIGGER search_strains_delete AFTER DELETE ON strains BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'strain'
+            AND key = OLD.name
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'strain'
+    AND key = OLD.name;
+END;
+
+CREATE TRIGGER search_strains_update AFTER UPDATE ON strains BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'strain'
+            AND key = OLD.name
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'strain'
+    AND key = OLD.name;
+INSERT INTO
+    search_index_keys (kind, key)
+VALUES
+    ('strain', NEW.name);
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'strain',
+        NEW.name,
+        NEW.name || ' ' || NEW.genotype,
+        NEW.description
+    );
+END;
+
+CREATE TRIGGER search_phenotypes_insert AFTER INSERT ON phenotypes BEGIN
+INSERT INTO
+    search_index_keys (kind, key, wild)
+VALUES
+    ('phenotype', NEW.name, NEW.wild);
+INSERT INTO
+    search_index (rowid, kind, key, wild, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'phenotype',
+        NEW.name,
+        NEW.wild,
+        NEW.name || ' ' || NEW.short_name,
+        NEW.description
+    );
+END;
+
+CREATE TRIGGER search_phenotypes_delete AFTER DELETE ON phenotypes BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'phenotype'
+            AND key = OLD.name
+            AND wild = OLD.wild
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'phenotype'
+    AND key = OLD.name
+    AND wild = OLD.wild;
+END;
+
+CREATE TRIGGER search_phenotypes_update AFTER UPDATE ON phenotypes BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'phenotype'
+            AND key = OLD.name
+            AND wild = OLD.wild
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'phenotype'
+    AND key = OLD.name
+    AND wild = OLD.wild;
+INSERT INTO
+    search_index_keys (kind, key, wild)
+VALUES
+    ('phenotype', NEW.name, NEW.wild);
+INSERT INTO
+    search_index (rowid, kind, key, wild, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'phenotype',
+        NEW.name,
+        NEW.wild,
+        NEW.name || ' ' || NEW.short_name,
+        NEW.description
+    );
+END;
+
+CREATE TRIGGER search_cross_designs_insert AFTER INSERT ON cross_designs BEGIN
+INSERT INTO
+    search_index_keys (kind, key)
+VALUES
+    ('cross_design', NEW.id);
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'cross_design',
+        NEW.id,
+        NEW.name,
+        NULL
+    );
+END;
+
+CREATE TRIGGER search_cross_designs_delete AFTER DELETE ON cross_designs BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'cross_design'
+            AND key = OLD.id
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'cross_design'
+    AND key = OLD.id;
+END;
+
+CREATE TRIGGER search_cross_designs_update AFTER UPDATE ON cross_designs BEGIN
+DELETE FROM search_index
+WHERE
+    rowid = (
+        SELECT
+            id
+        FROM
+            search_index_keys
+        WHERE
+            kind = 'cross_design'
+            AND key = OLD.id
+    );
+DELETE FROM search_index_keys
+WHERE
+    kind = 'cross_design'
+    AND key = OLD.id;
+INSERT INTO
+    search_index_keys (kind, key)
+VALUES
+    ('cross_design', NEW.id);
+INSERT INTO
+    search_index (rowid, kind, key, name, description)
+VALUES
+    (
+        last_insert_rowid(),
+        'cross_design',
+        NEW.id,
+        NEW.name,
+        NULL
+    );
+END;
@@ -0,0 +1,41 @@
+pub mod allele;
+pub mod allele_pair;
+pub mod chromosome_pair;
+pub mod genetic_map;
+pub mod genotype;
+pub mod offspring;
+pub mod strain;
+
+use crate::models::chromosome_name::ChromosomeName;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum GeneticsError {
+    #[error(
+        "Cannot add allele pair {0} because it conflicts with one of the same gene or variation"
+    )]
+    DuplicateLocus(String),
+    #[error("Cannot have more than two heterozygous alleles on one gene or variation: {0}")]
+    TooManyAlleles(String),
+    #[error("Cannot merge allele pairs {0} and {1}")]
+    InvalidMerge(String, String),
+    #[error("Invalid genotype {0}")]
+    InvalidGenotype(String),
+}
+
+/// Specific to C. elegans: I to V are the five somatic chromosomes, X is the sex
+/// chromosome and Ex is an extrachromosomal array (not technically a chromosome,
+/// but treated as one). Alleles without a known chromosome sort last.
+pub fn chromosome_order(chrom: &Option<ChromosomeName>) -> usize {
+    match chrom {
+        Some(ChromosomeName::I) => 0,
+        Some(ChromosomeName::Ii) => 1,
+        Some(ChromosomeName::Iii) => 2,
+        Some(ChromosomeName::Iv) => 3,
+        Some(ChromosomeName::V) => 4,
+        Some(ChromosomeName::X) => 5,
+        Some(ChromosomeName::MtDNA) => 6,
+        Some(ChromosomeName::Ex) => 7,
+        None => 8,
+    }
+}
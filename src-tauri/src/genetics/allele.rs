@@ -0,0 +1,85 @@
+use super::allele_pair::AllelePair;
+use crate::models::chromosome_name::ChromosomeName;
+
+pub const WILD_ALLELE_NAME: &str = "+";
+
+/// The gene or variation an allele belongs to
+#[derive(Clone, Debug, PartialEq)]
+pub struct Locus {
+    /// Systematic gene name, or variation name
+    pub name: String,
+    /// Descriptive gene name (e.g. "dpy-10"), None for variations
+    pub gene_name: Option<String>,
+    pub is_gene: bool,
+    pub chromosome: Option<ChromosomeName>,
+    pub gen_loc: Option<f64>,
+    /// Whether gen_loc was estimated from the physical location, for lack of a measured one
+    pub gen_loc_estimated: bool,
+}
+
+/// An allele of a gene or variation. Wild alleles are named "+" and keep the
+/// locus of the allele they were derived from.
+#[derive(Clone, Debug)]
+pub struct Allele {
+    pub name: String,
+    pub locus: Locus,
+}
+
+impl PartialEq for Allele {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Allele {
+    pub fn new(name: String, locus: Locus) -> Self {
+        Self { name, locus }
+    }
+
+    pub fn to_wild(&self) -> Allele {
+        Allele {
+            name: WILD_ALLELE_NAME.to_owned(),
+            locus: self.locus.clone(),
+        }
+    }
+
+    pub fn to_homo(&self) -> AllelePair {
+        AllelePair::new(self.clone(), self.clone())
+    }
+
+    pub fn to_top_het(&self) -> AllelePair {
+        AllelePair::new(self.clone(), self.to_wild())
+    }
+
+    pub fn to_bot_het(&self) -> AllelePair {
+        AllelePair::new(self.to_wild(), self.clone())
+    }
+
+    pub fn is_wild(&self) -> bool {
+        self.name == WILD_ALLELE_NAME
+    }
+
+    pub fn is_eca(&self) -> bool {
+        self.locus.chromosome == Some(ChromosomeName::Ex)
+    }
+
+    pub fn get_chrom_name(&self) -> Option<ChromosomeName> {
+        self.locus.chromosome
+    }
+
+    pub fn get_gen_position(&self) -> Option<f64> {
+        self.locus.gen_loc
+    }
+
+    /// For alleles of genes, returns 'genename(allelename)'; for other alleles, returns 'allelename'
+    pub fn get_qualified_name(&self) -> String {
+        if self.locus.is_gene {
+            let gene_name = self.locus.gene_name.as_ref().unwrap_or(&self.locus.name);
+            format!("{}({})", gene_name, self.name)
+        } else if self.is_wild() {
+            format!("{}({})", self.locus.name, self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+}
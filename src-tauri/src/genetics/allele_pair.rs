@@ -0,0 +1,95 @@
+use super::{allele::Allele, GeneticsError};
+use crate::models::chromosome_name::ChromosomeName;
+use std::{cmp::Ordering, fmt};
+
+/// Unmapped alleles are sorted after every position, including estimates extrapolated
+/// past the ends of a chromosome's genetic map
+const UNKNOWN_GEN_POSITION: f64 = f64::INFINITY;
+
+/// The two alleles found at one locus of a pair of homologous chromosomes
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllelePair {
+    pub top: Allele,
+    pub bot: Allele,
+}
+
+impl fmt::Display for AllelePair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.top.name, self.bot.name)
+    }
+}
+
+impl AllelePair {
+    pub fn new(top: Allele, bot: Allele) -> Self {
+        Self { top, bot }
+    }
+
+    /// Sorts by genetic position, or by name for extrachromosomal arrays.
+    /// Ties are broken by locus name so results are reproducible.
+    pub fn sort(allele_pairs: &mut [AllelePair]) {
+        if allele_pairs.first().map_or(false, |pair| pair.is_eca()) {
+            allele_pairs.sort_by(|a, b| a.top.name.cmp(&b.top.name));
+        } else {
+            allele_pairs.sort_by(|a, b| {
+                let a_pos = a.top.get_gen_position().unwrap_or(UNKNOWN_GEN_POSITION);
+                let b_pos = b.top.get_gen_position().unwrap_or(UNKNOWN_GEN_POSITION);
+                a_pos
+                    .partial_cmp(&b_pos)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| a.top.locus.name.cmp(&b.top.locus.name))
+            });
+        }
+    }
+
+    /// Checks if two allele pairs are on the same variation or the same gene
+    pub fn is_of_same_gene_or_variation(&self, other: &AllelePair) -> bool {
+        let (this_locus, other_locus) = (&self.top.locus, &other.top.locus);
+        this_locus.is_gene == other_locus.is_gene && this_locus.name == other_locus.name
+    }
+
+    pub fn get_chrom_name(&self) -> Option<ChromosomeName> {
+        self.top.get_chrom_name()
+    }
+
+    /// Checks if this pair is only made up of wild alleles
+    pub fn is_wild(&self) -> bool {
+        self.top.is_wild() && self.bot.is_wild()
+    }
+
+    /// Returns true if pair is homozygous (false for heterozygous)
+    pub fn is_homo(&self) -> bool {
+        self.top.name == self.bot.name
+    }
+
+    pub fn is_eca(&self) -> bool {
+        self.top.is_eca()
+    }
+
+    /// Returns true if the pair is heterozygous, with one allele being wild
+    pub fn is_wild_het(&self) -> bool {
+        !self.is_homo() && (self.top.is_wild() || self.bot.is_wild())
+    }
+
+    /// Combines two wild-heterozygous pairs of the same locus with their
+    /// non-wild alleles on opposite sides (e.g. a/+ and +/b into a/b)
+    pub fn merge(&self, other: &AllelePair) -> Result<AllelePair, GeneticsError> {
+        let invalid = || GeneticsError::InvalidMerge(self.to_string(), other.to_string());
+        if !self.is_wild_het() || !other.is_wild_het() {
+            return Err(invalid());
+        }
+        if (self.top.is_wild() && other.top.is_wild())
+            || (self.bot.is_wild() && other.bot.is_wild())
+        {
+            return Err(invalid());
+        }
+        Ok(match self.top.is_wild() {
+            true => AllelePair::new(other.top.clone(), self.bot.clone()),
+            false => AllelePair::new(self.top.clone(), other.bot.clone()),
+        })
+    }
+
+    /// Flips in place
+    pub fn flip(&mut self) {
+        std::mem::swap(&mut self.top, &mut self.bot);
+    }
+}
@@ -0,0 +1,361 @@
+use super::{allele::Allele, allele_pair::AllelePair};
+use crate::models::chromosome_name::ChromosomeName;
+
+/// One chromosome that can come out of meiosis, and how likely it is
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChromosomeOption {
+    pub chromosome: Vec<Allele>,
+    pub prob: f64,
+}
+
+/// Two homologous chromosomes of a specimen, represented as a list of allele pairs
+/// kept in sorted order by genetic position. Also used for extrachromosomal arrays.
+#[derive(Clone, Debug, Default)]
+pub struct ChromosomePair {
+    pub allele_pairs: Vec<AllelePair>,
+}
+
+impl ChromosomePair {
+    /// Pairs up two chromosomes of a gamete. A missing bottom allele is treated as wild.
+    pub fn build_from_chroms(top: &[Allele], bot: &[Allele]) -> Self {
+        if top.first().map_or(false, |allele| allele.is_eca()) {
+            return Self::eca_build_from_chroms(top, bot);
+        }
+        let mut allele_pairs: Vec<AllelePair> = top
+            .iter()
+            .enumerate()
+            .map(|(idx, top_allele)| {
+                AllelePair::new(
+                    top_allele.clone(),
+                    bot.get(idx)
+                        .cloned()
+                        .unwrap_or_else(|| top_allele.to_wild()),
+                )
+            })
+            .collect();
+        AllelePair::sort(&mut allele_pairs);
+        Self { allele_pairs }
+    }
+
+    /// Extrachromosomal arrays are not paired, the child simply carries every array
+    /// either parent passed on
+    fn eca_build_from_chroms(top: &[Allele], bot: &[Allele]) -> Self {
+        let mut unique_non_wild: Vec<&Allele> = vec![];
+        for allele in top.iter().chain(bot.iter()) {
+            if !allele.is_wild() && !unique_non_wild.contains(&allele) {
+                unique_non_wild.push(allele);
+            }
+        }
+        unique_non_wild.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            allele_pairs: unique_non_wild
+                .into_iter()
+                .map(|allele| allele.to_top_het())
+                .collect(),
+        }
+    }
+
+    /// Returns an equivalent chromosome pair without any wild allele pairs,
+    /// flipped so the leading allele pair has its non-wild allele on top
+    pub fn simplify(&self) -> Self {
+        let mut allele_pairs: Vec<AllelePair> = self
+            .allele_pairs
+            .iter()
+            .filter(|pair| !pair.is_wild())
+            .cloned()
+            .collect();
+        if allele_pairs
+            .first()
+            .map_or(false, |pair| pair.top.is_wild())
+        {
+            allele_pairs.iter_mut().for_each(|pair| pair.flip());
+        }
+        Self { allele_pairs }
+    }
+
+    pub fn get_chrom_name(&self) -> Option<ChromosomeName> {
+        self.allele_pairs
+            .first()
+            .and_then(|pair| pair.get_chrom_name())
+    }
+
+    pub fn contains_same_gene_or_variation_as(&self, other_pair: &AllelePair) -> bool {
+        self.allele_pairs
+            .iter()
+            .any(|pair| pair.is_of_same_gene_or_variation(other_pair))
+    }
+
+    /// Genotype notation of the non-wild alleles, e.g. "dpy-10(e128) II" or
+    /// "unc-24(e138) fbl-1(hd43)/unc-24(e138) + IV"
+    pub fn to_genotype_string(&self) -> String {
+        let chrom_name = self
+            .get_chrom_name()
+            .map_or("?".to_owned(), |chrom| chrom.to_string());
+        let chrom_pair = self.simplify();
+        if self.is_homo() {
+            let alleles = chrom_pair
+                .allele_pairs
+                .iter()
+                .map(|pair| pair.top.get_qualified_name())
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!("{} {}", alleles, chrom_name)
+        } else {
+            format!(
+                "{}/{} {}",
+                Self::get_chromosome_string(&chrom_pair.get_top()),
+                Self::get_chromosome_string(&chrom_pair.get_bot()),
+                chrom_name
+            )
+        }
+    }
+
+    fn get_chromosome_string(chrom: &[Allele]) -> String {
+        chrom
+            .iter()
+            .map(|allele| match allele.is_wild() {
+                true => allele.name.clone(),
+                false => allele.get_qualified_name(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn get_top(&self) -> Vec<Allele> {
+        self.allele_pairs
+            .iter()
+            .map(|pair| pair.top.clone())
+            .collect()
+    }
+
+    pub fn get_bot(&self) -> Vec<Allele> {
+        self.allele_pairs
+            .iter()
+            .map(|pair| pair.bot.clone())
+            .collect()
+    }
+
+    pub fn is_wild(&self) -> bool {
+        self.allele_pairs.iter().all(|pair| pair.is_wild())
+    }
+
+    pub fn is_homo(&self) -> bool {
+        self.allele_pairs.iter().all(|pair| pair.is_homo())
+    }
+
+    pub fn is_eca(&self) -> bool {
+        self.get_chrom_name() == Some(ChromosomeName::Ex)
+    }
+
+    /// Checks if both pairs carry the same non-wild alleles, in either orientation
+    pub fn equals(&self, other: &ChromosomePair) -> bool {
+        fn chroms_equal(chrom1: &[Allele], chrom2: &[Allele]) -> bool {
+            let chrom1_no_wilds: Vec<&Allele> =
+                chrom1.iter().filter(|allele| !allele.is_wild()).collect();
+            let chrom2_no_wilds: Vec<&Allele> =
+                chrom2.iter().filter(|allele| !allele.is_wild()).collect();
+            chrom1_no_wilds == chrom2_no_wilds
+        }
+
+        let (top, bot) = (self.get_top(), self.get_bot());
+        let (other_top, other_bot) = (other.get_top(), other.get_bot());
+        let same_sides_equal = chroms_equal(&top, &other_top) && chroms_equal(&bot, &other_bot);
+        let flipped_sides_equal = chroms_equal(&top, &other_bot) && chroms_equal(&bot, &other_top);
+        same_sides_equal || flipped_sides_equal
+    }
+
+    /// Half the genetic distance between two allele pairs as a fraction, or 0 if either
+    /// pair doesn't have a genetic location
+    pub fn get_recomb_prob(pair1: &AllelePair, pair2: &AllelePair) -> f64 {
+        match (pair1.top.get_gen_position(), pair2.top.get_gen_position()) {
+            (Some(pos1), Some(pos2)) => (pos1 - pos2).abs() / 2.0 / 100.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Computes the chromosomes (of a single chromosome pair) that can result from at most
+    /// one recombination event
+    /// * `starting_chrom` - alleles used before a recombination event
+    /// * `flipped_chrom` - alleles used after a recombination event
+    fn permute_recomb_options(
+        starting_chrom: &[Allele],
+        flipped_chrom: &[Allele],
+        allele_pairs: &[AllelePair],
+    ) -> Vec<ChromosomeOption> {
+        let mut chroms: Vec<ChromosomeOption> = vec![];
+
+        // the first iteration accounts for no recombination at all
+        for i in 0..allele_pairs.len() {
+            let mut chrom: Vec<Allele> = vec![starting_chrom[0].clone()];
+            let mut prob = 0.5;
+
+            for (j, window) in allele_pairs.windows(2).enumerate().map(|(j, w)| (j + 1, w)) {
+                let recomb_prob = Self::get_recomb_prob(&window[0], &window[1]);
+
+                // recombination event: take the remainder from the other chromosome
+                if j == i {
+                    chrom.extend(flipped_chrom[j..].iter().cloned());
+                    prob = recomb_prob;
+                    break;
+                }
+
+                // no recombination, continue along the starting chromosome
+                prob -= recomb_prob;
+                chrom.push(starting_chrom[j].clone());
+            }
+            chroms.push(ChromosomeOption {
+                chromosome: chrom,
+                prob,
+            });
+        }
+
+        chroms
+    }
+
+    /// All chromosomes this pair can pass on to a gamete, with their probabilities
+    pub fn meiosis(&self) -> Vec<ChromosomeOption> {
+        if self.allele_pairs.is_empty() {
+            return vec![ChromosomeOption {
+                chromosome: vec![],
+                prob: 1.0,
+            }];
+        }
+        let top = self.get_top();
+        let bot = self.get_bot();
+
+        let mut options = Self::permute_recomb_options(&top, &bot, &self.allele_pairs);
+        options.extend(Self::permute_recomb_options(&bot, &top, &self.allele_pairs));
+
+        Self::reduce_chrom_options(options)
+    }
+
+    /// Combines probabilities of duplicate chromosomes so the resulting list is unique
+    fn reduce_chrom_options(chrom_opts: Vec<ChromosomeOption>) -> Vec<ChromosomeOption> {
+        let mut reduced: Vec<ChromosomeOption> = vec![];
+        for chrom_opt in chrom_opts {
+            match reduced
+                .iter_mut()
+                .find(|existing| existing.chromosome == chrom_opt.chromosome)
+            {
+                Some(existing) => existing.prob += chrom_opt.prob,
+                None => reduced.push(chrom_opt),
+            }
+        }
+        reduced
+    }
+
+    /// Adds a wild pair for every locus of {other} that this pair doesn't have yet
+    pub fn fill_wilds_from(&mut self, other: &ChromosomePair) {
+        for other_pair in other.allele_pairs.iter() {
+            if !self.contains_same_gene_or_variation_as(other_pair) {
+                let wild = other_pair.top.to_wild();
+                self.insert_pair(AllelePair::new(wild.clone(), wild));
+            }
+        }
+    }
+
+    pub fn insert_pair(&mut self, pair: AllelePair) {
+        self.allele_pairs.push(pair);
+        AllelePair::sort(&mut self.allele_pairs);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::genetics::allele::{Allele, Locus};
+    use crate::genetics::allele_pair::AllelePair;
+    use crate::genetics::chromosome_pair::{ChromosomeOption, ChromosomePair};
+    use crate::models::chromosome_name::ChromosomeName;
+    use pretty_assertions::assert_eq;
+
+    fn gene_allele(name: &str, gene: &str, gen_loc: f64) -> Allele {
+        Allele::new(
+            name.to_owned(),
+            Locus {
+                name: gene.to_owned(),
+                gene_name: Some(gene.to_owned()),
+                is_gene: true,
+                chromosome: Some(ChromosomeName::Iv),
+                gen_loc: Some(gen_loc),
+                gen_loc_estimated: false,
+            },
+        )
+    }
+
+    fn build_pair(mut allele_pairs: Vec<AllelePair>) -> ChromosomePair {
+        AllelePair::sort(&mut allele_pairs);
+        ChromosomePair { allele_pairs }
+    }
+
+    #[test]
+    fn test_meiosis_homozygous() {
+        let a = gene_allele("e1", "unc-1", 1.0);
+        let pair = build_pair(vec![a.to_homo()]);
+
+        assert_eq!(
+            pair.meiosis(),
+            vec![ChromosomeOption {
+                chromosome: vec![a],
+                prob: 1.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_meiosis_linked_recombination() {
+        let a = gene_allele("e1", "unc-1", 0.0);
+        let b = gene_allele("e2", "dpy-2", 10.0);
+        let pair = build_pair(vec![a.to_top_het(), b.to_top_het()]);
+
+        let options = pair.meiosis();
+        let probs: Vec<(Vec<String>, f64)> = options
+            .into_iter()
+            .map(|opt| {
+                (
+                    opt.chromosome
+                        .into_iter()
+                        .map(|allele| allele.name)
+                        .collect(),
+                    (opt.prob * 1000.0).round() / 1000.0,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            probs,
+            vec![
+                (vec!["e1".to_owned(), "e2".to_owned()], 0.45),
+                (vec!["e1".to_owned(), "+".to_owned()], 0.05),
+                (vec!["+".to_owned(), "+".to_owned()], 0.45),
+                (vec!["+".to_owned(), "e2".to_owned()], 0.05),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_genotype_string() {
+        let a = gene_allele("e1", "unc-1", 0.0);
+        let b = gene_allele("e2", "dpy-2", 10.0);
+
+        let homo = build_pair(vec![a.to_homo(), b.to_homo()]);
+        assert_eq!(homo.to_genotype_string(), "unc-1(e1) dpy-2(e2) IV");
+
+        let trans = build_pair(vec![a.to_bot_het(), b.to_top_het()]);
+        assert_eq!(trans.to_genotype_string(), "unc-1(e1) +/+ dpy-2(e2) IV");
+    }
+
+    #[test]
+    fn test_sort_unmapped_last() {
+        let mut unmapped = gene_allele("e1", "unc-1", 0.0);
+        unmapped.locus.gen_loc = None;
+        // past the end of any real map, as an extrapolated estimate can be
+        let far = gene_allele("e2", "dpy-2", 60.0);
+        let near = gene_allele("e3", "lin-3", -60.0);
+
+        let pair = build_pair(vec![unmapped.to_homo(), far.to_homo(), near.to_homo()]);
+        assert_eq!(
+            pair.to_genotype_string(),
+            "lin-3(e3) dpy-2(e2) unc-1(e1) IV"
+        );
+    }
+}
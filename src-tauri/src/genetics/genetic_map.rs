@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+
+/// A chromosome's genetic map as a function of physical position, fitted from the loci
+/// whose genetic and physical locations are both known. Between those loci it's
+/// piecewise linear, and past either end it follows the average slope of the whole map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneticMap {
+    /// (physical location, genetic location), never decreasing in either
+    knots: Vec<(f64, f64)>,
+}
+
+impl GeneticMap {
+    /// Needs at least two different physical locations. Where the genetic locations run
+    /// backwards along the chromosome, they're averaged out so the map never decreases.
+    pub fn fit(mut points: Vec<(i32, f64)>) -> Option<GeneticMap> {
+        points.sort_by(|(phys1, gen1), (phys2, gen2)| {
+            phys1
+                .cmp(phys2)
+                .then(gen1.partial_cmp(gen2).unwrap_or(Ordering::Equal))
+        });
+
+        // loci at the same physical location count as one point, weighted by how many
+        let mut groups: Vec<(f64, f64, f64)> = vec![];
+        for (phys, gen) in points {
+            match groups.last_mut() {
+                Some((group_phys, sum, weight)) if *group_phys == phys as f64 => {
+                    *sum += gen;
+                    *weight += 1.0;
+                }
+                _ => groups.push((phys as f64, gen, 1.0)),
+            }
+        }
+        if groups.len() < 2 {
+            return None;
+        }
+
+        // pool adjacent violators: merge neighbouring blocks until their means increase
+        let mut blocks: Vec<(usize, f64, f64)> = vec![];
+        for &(_, sum, weight) in &groups {
+            blocks.push((1, sum, weight));
+            while let [.., (len1, sum1, weight1), (len2, sum2, weight2)] = blocks[..] {
+                if sum1 / weight1 <= sum2 / weight2 {
+                    break;
+                }
+                blocks.pop();
+                blocks.pop();
+                blocks.push((len1 + len2, sum1 + sum2, weight1 + weight2));
+            }
+        }
+
+        let mut knots = vec![];
+        let mut groups = groups.into_iter();
+        for (len, sum, weight) in blocks {
+            for (phys, _, _) in groups.by_ref().take(len) {
+                knots.push((phys, sum / weight));
+            }
+        }
+        Some(GeneticMap { knots })
+    }
+
+    /// The genetic location (cM) of a physical location (bp)
+    pub fn estimate(&self, phys_loc: i32) -> f64 {
+        let phys = phys_loc as f64;
+        let (first, last) = (self.knots[0], self.knots[self.knots.len() - 1]);
+        let (start, slope) = match self.knots.partition_point(|(knot, _)| *knot < phys) {
+            0 => (first, (last.1 - first.1) / (last.0 - first.0)),
+            i if i == self.knots.len() => (last, (last.1 - first.1) / (last.0 - first.0)),
+            i => {
+                let (prev, next) = (self.knots[i - 1], self.knots[i]);
+                (prev, (next.1 - prev.1) / (next.0 - prev.0))
+            }
+        };
+        start.1 + (phys - start.0) * slope
+    }
+}
+
+/// A locus's genetic location (cM) and whether it was estimated: its measured one if it
+/// has one, otherwise the genetic map's estimate at its physical location
+pub fn locate(
+    gen_loc: Option<f64>,
+    phys_loc: Option<i32>,
+    genetic_map: Option<&GeneticMap>,
+) -> Option<(f64, bool)> {
+    match (gen_loc, phys_loc, genetic_map) {
+        (Some(gen_loc), _, _) => Some((gen_loc, false)),
+        (None, Some(phys_loc), Some(genetic_map)) => Some((genetic_map.estimate(phys_loc), true)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::genetics::genetic_map::{locate, GeneticMap};
+    use pretty_assertions::assert_eq;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{actual} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn test_fit_too_few_points() {
+        assert_eq!(GeneticMap::fit(vec![]), None);
+        assert_eq!(GeneticMap::fit(vec![(100, 1.0)]), None);
+        assert_eq!(GeneticMap::fit(vec![(100, 1.0), (100, 2.0)]), None);
+    }
+
+    #[test]
+    fn test_estimate_between_points() {
+        let map = GeneticMap::fit(vec![(3000, 5.0), (1000, 1.0), (2000, 2.0)]).unwrap();
+
+        assert_close(map.estimate(1000), 1.0);
+        assert_close(map.estimate(1500), 1.5);
+        assert_close(map.estimate(2000), 2.0);
+        assert_close(map.estimate(2250), 2.75);
+        assert_close(map.estimate(3000), 5.0);
+    }
+
+    #[test]
+    fn test_estimate_past_the_ends() {
+        let map = GeneticMap::fit(vec![(1000, 1.0), (2000, 2.0), (3000, 5.0)]).unwrap();
+
+        // 4 cM over 2000 bp
+        assert_close(map.estimate(0), -1.0);
+        assert_close(map.estimate(4000), 7.0);
+    }
+
+    #[test]
+    fn test_fit_is_monotone() {
+        // 3.0 then 1.0 runs backwards, so both become their average
+        let map =
+            GeneticMap::fit(vec![(1000, 0.0), (2000, 3.0), (3000, 1.0), (4000, 4.0)]).unwrap();
+
+        assert_close(map.estimate(2000), 2.0);
+        assert_close(map.estimate(2500), 2.0);
+        assert_close(map.estimate(3000), 2.0);
+        assert_close(map.estimate(3500), 3.0);
+    }
+
+    #[test]
+    fn test_locate() {
+        let map = GeneticMap::fit(vec![(1000, 1.0), (2000, 2.0)]).unwrap();
+
+        assert_eq!(
+            locate(Some(5.0), Some(1500), Some(&map)),
+            Some((5.0, false))
+        );
+        assert_eq!(locate(None, Some(1500), Some(&map)), Some((1.5, true)));
+        assert_eq!(locate(None, Some(1500), None), None);
+        assert_eq!(locate(None, None, Some(&map)), None);
+    }
+
+    #[test]
+    fn test_fit_averages_shared_physical_locations() {
+        let map = GeneticMap::fit(vec![(1000, 1.0), (1000, 2.0), (2000, 3.0)]).unwrap();
+
+        assert_close(map.estimate(1000), 1.5);
+        assert_close(map.estimate(1500), 2.25);
+    }
+}
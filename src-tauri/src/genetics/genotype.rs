@@ -0,0 +1,312 @@
+use super::{allele::WILD_ALLELE_NAME, GeneticsError};
+use crate::models::{chromosome_name::ChromosomeName, strain_allele::StrainAllele};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use ts_rs::TS;
+
+/// Wild isolates, N2 included, are described instead of given a genotype, e.g.
+/// "C. elegans wild isolate."
+const WILD_TYPE_DESCRIPTIONS: &[&str] = &["wild isolate", "wild type", "wild-type"];
+
+/// A strain whose genotype text disagrees with its strain_alleles rows
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_GenotypeMismatch.ts")]
+#[serde(rename = "db_GenotypeMismatch")]
+pub struct GenotypeMismatch {
+    #[serde(rename = "strainName")]
+    pub strain_name: String,
+    pub genotype: String,
+    /// Alleles described by the genotype text, empty if it couldn't be parsed
+    #[serde(rename = "parsedAlleles")]
+    pub parsed_alleles: Vec<StrainAllele>,
+    /// Alleles stored in strain_alleles
+    #[serde(rename = "storedAlleles")]
+    pub stored_alleles: Vec<StrainAllele>,
+    #[serde(rename = "parseError")]
+    pub parse_error: Option<String>,
+}
+
+/// Parses a genotype in WormBase/CGC notation (e.g. "unc-119(ed3) III; oxIs363 IV." or
+/// "fbl-1(hd43)/dpy-20(e1282) unc-24(e138) IV.") into strain_alleles rows.
+/// Chromosomes are separated by ';', the chromosome before and after '/' are the top
+/// and bottom chromosomes, and bracketed transgene contents are ignored.
+/// Rearrangements may list the chromosomes they span, as in "eT1(III;V)".
+/// Extrachromosomal arrays are recorded on the top chromosome only, and wild type
+/// descriptions have no alleles.
+pub fn parse_genotype(
+    strain_name: &str,
+    genotype: &str,
+) -> Result<Vec<StrainAllele>, GeneticsError> {
+    let invalid = |reason: &str| GeneticsError::InvalidGenotype(format!("{genotype}: {reason}"));
+    let lowercase_genotype = genotype.to_lowercase();
+    if WILD_TYPE_DESCRIPTIONS
+        .iter()
+        .any(|description| lowercase_genotype.contains(description))
+    {
+        return Ok(vec![]);
+    }
+
+    let genotype_text = strip_brackets(genotype).ok_or_else(|| invalid("unbalanced brackets"))?;
+    let genotype_text = genotype_text.trim().trim_end_matches('.');
+
+    let chrom_texts =
+        split_chromosomes(genotype_text).ok_or_else(|| invalid("unbalanced parentheses"))?;
+
+    let mut strain_alleles: Vec<StrainAllele> = vec![];
+    for chrom_text in chrom_texts {
+        let mut chrom_text = chrom_text.trim();
+        if chrom_text.is_empty() {
+            return Err(invalid("empty chromosome"));
+        }
+        let mut is_eca = false;
+        if let Some((alleles_text, label)) = chrom_text.rsplit_once(char::is_whitespace) {
+            if let Ok(chrom_name) = ChromosomeName::from_str(label) {
+                is_eca = chrom_name == ChromosomeName::Ex;
+                chrom_text = alleles_text.trim();
+            }
+        }
+
+        let sides: Vec<&str> = chrom_text.split('/').collect();
+        let (top, bot) = match sides.as_slice() {
+            [homo] => (parse_alleles(homo)?, parse_alleles(homo)?),
+            [top, bot] => (parse_alleles(top)?, parse_alleles(bot)?),
+            _ => return Err(invalid("more than two chromosomes in a pair")),
+        };
+
+        for allele_name in top.iter().chain(bot.iter()) {
+            if strain_alleles
+                .iter()
+                .any(|strain_allele| &strain_allele.allele_name == allele_name)
+            {
+                continue;
+            }
+            let is_on_top = top.contains(allele_name);
+            let is_on_bot = bot.contains(allele_name);
+            let is_array = is_eca || is_extrachromosomal_array(allele_name);
+            strain_alleles.push(StrainAllele {
+                strain_name: strain_name.to_owned(),
+                allele_name: allele_name.clone(),
+                is_on_top: is_on_top || is_array,
+                is_on_bot: is_on_bot && !is_array,
+            });
+        }
+    }
+
+    strain_alleles.sort_by(|a, b| a.allele_name.cmp(&b.allele_name));
+    Ok(strain_alleles)
+}
+
+/// Removes bracketed transgene contents, e.g. "oxIs363 [unc-122p::GFP + unc-119(+)]"
+fn strip_brackets(genotype: &str) -> Option<String> {
+    let mut stripped = String::with_capacity(genotype.len());
+    let mut depth: usize = 0;
+    for c in genotype.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.checked_sub(1)?,
+            _ if depth == 0 => stripped.push(c),
+            _ => (),
+        }
+    }
+    (depth == 0).then(|| stripped)
+}
+
+/// Splits at the ';'s outside of parentheses, so a rearrangement's chromosomes stay
+/// with it
+fn split_chromosomes(genotype_text: &str) -> Option<Vec<&str>> {
+    let mut chrom_texts = vec![];
+    let mut start = 0;
+    let mut depth: usize = 0;
+    for (i, c) in genotype_text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ';' if depth == 0 => {
+                chrom_texts.push(&genotype_text[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    chrom_texts.push(&genotype_text[start..]);
+    Some(chrom_texts)
+}
+
+/// Returns the non-wild allele names of one chromosome, written as "gene(allele)",
+/// a bare allele or rearrangement name such as "oxIs363", a rearrangement with its
+/// chromosomes such as "eT1(III;V)", or "+"
+fn parse_alleles(chrom_text: &str) -> Result<Vec<String>, GeneticsError> {
+    let mut allele_names = vec![];
+    for token in chrom_text.split_whitespace() {
+        let allele_name = match token.split_once('(') {
+            Some((name, rest)) => {
+                let allele_name = rest.strip_suffix(')').unwrap_or_default();
+                if is_valid_allele_name(name) && is_chromosome_list(allele_name) {
+                    name
+                } else if !is_valid_gene_name(name) || !is_valid_allele_name(allele_name) {
+                    return Err(GeneticsError::InvalidGenotype(format!(
+                        "{token} is not a valid allele"
+                    )));
+                } else {
+                    allele_name
+                }
+            }
+            None if token == WILD_ALLELE_NAME => continue,
+            None if is_valid_allele_name(token) => token,
+            None => {
+                return Err(GeneticsError::InvalidGenotype(format!(
+                    "{token} is not a valid allele"
+                )))
+            }
+        };
+        if allele_name != WILD_ALLELE_NAME {
+            allele_names.push(allele_name.to_owned());
+        }
+    }
+    Ok(allele_names)
+}
+
+/// Gene names may be systematic names such as "T14B4.7"
+fn is_valid_gene_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+fn is_valid_allele_name(name: &str) -> bool {
+    name == WILD_ALLELE_NAME
+        || (name.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+}
+
+/// E.g. "III;V"
+fn is_chromosome_list(text: &str) -> bool {
+    text.split(';')
+        .all(|name| ChromosomeName::from_str(name.trim()).is_ok())
+}
+
+/// Arrays follow the "<lab>Ex<number>" naming convention, e.g. "oxEx2254"
+fn is_extrachromosomal_array(allele_name: &str) -> bool {
+    allele_name.split_once("Ex").map_or(false, |(lab, number)| {
+        !lab.is_empty()
+            && lab.chars().all(|c| c.is_ascii_lowercase())
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::genetics::genotype::parse_genotype;
+    use crate::models::strain_allele::StrainAllele;
+    use pretty_assertions::assert_eq;
+
+    fn strain_allele(allele_name: &str, is_on_top: bool, is_on_bot: bool) -> StrainAllele {
+        StrainAllele {
+            strain_name: "S1".to_owned(),
+            allele_name: allele_name.to_owned(),
+            is_on_top,
+            is_on_bot,
+        }
+    }
+
+    #[test]
+    fn test_parse_homozygous() {
+        assert_eq!(
+            parse_genotype(
+                "S1",
+                "unc-119(ed3) III; oxIs363 [unc-122p::GFP + unc-119(+)] IV."
+            )
+            .unwrap(),
+            vec![
+                strain_allele("ed3", true, true),
+                strain_allele("oxIs363", true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_heterozygous() {
+        assert_eq!(
+            parse_genotype(
+                "S1",
+                "fbl-1(hd43)/dpy-20(e1282) unc-24(e138) IV; +/lin-15B(n744) X."
+            )
+            .unwrap(),
+            vec![
+                strain_allele("e1282", false, true),
+                strain_allele("e138", false, true),
+                strain_allele("hd43", true, false),
+                strain_allele("n744", false, true),
+            ]
+        );
+        assert_eq!(
+            parse_genotype("S1", "dpy-10(cn64) unc-4(e120)/dpy-10(cn64) + II.").unwrap(),
+            vec![
+                strain_allele("cn64", true, true),
+                strain_allele("e120", true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_arrays() {
+        assert_eq!(
+            parse_genotype("S1", "unc-119(ed3) III; oxEx2254.").unwrap(),
+            vec![
+                strain_allele("ed3", true, true),
+                strain_allele("oxEx2254", true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rearrangements() {
+        assert_eq!(
+            parse_genotype("S1", "eT1(III;V).").unwrap(),
+            vec![strain_allele("eT1", true, true)]
+        );
+        assert_eq!(
+            parse_genotype(
+                "S1",
+                "unc-36(e251) III; hT2[bli-4(e937) let-?(q782) qIs48](I;III)."
+            )
+            .unwrap(),
+            vec![
+                strain_allele("e251", true, true),
+                strain_allele("hT2", true, true),
+            ]
+        );
+        assert_eq!(
+            parse_genotype("S1", "mIn1[dpy-10(e128)] II; +/eT1 V.").unwrap(),
+            vec![
+                strain_allele("eT1", false, true),
+                strain_allele("mIn1", true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_wild_type() {
+        assert_eq!(
+            parse_genotype("S1", "C. elegans wild isolate.").unwrap(),
+            vec![]
+        );
+        assert_eq!(parse_genotype("S1", "Wild type.").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_genotype("S1", "C. briggsae isolate.").is_err());
+        assert!(parse_genotype("S1", "a/b/c II.").is_err());
+        assert!(parse_genotype("S1", "oxIs363 [unc-122p::GFP IV.").is_err());
+        assert!(parse_genotype("S1", "unc-119(ed3 III.").is_err());
+        assert!(parse_genotype("S1", "eT1(III;V.").is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use super::strain::Strain;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_OffspringAllele.ts")]
+#[serde(rename = "db_OffspringAllele")]
+pub struct OffspringAllele {
+    #[serde(rename = "alleleName")]
+    pub allele_name: String,
+    #[serde(rename = "isOnTop")]
+    pub is_on_top: bool,
+    #[serde(rename = "isOnBot")]
+    pub is_on_bot: bool,
+}
+
+/// A possible child of a cross, with its alleles laid out like strain_alleles rows
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_Offspring.ts")]
+#[serde(rename = "db_Offspring")]
+pub struct Offspring {
+    pub genotype: String,
+    pub alleles: Vec<OffspringAllele>,
+    pub probability: f64,
+}
+
+impl From<&Strain> for Offspring {
+    fn from(strain: &Strain) -> Offspring {
+        let alleles = strain
+            .get_sorted_chrom_pairs()
+            .into_iter()
+            .flat_map(|chrom_pair| chrom_pair.simplify().allele_pairs)
+            .flat_map(|pair| {
+                if pair.is_homo() {
+                    return vec![OffspringAllele {
+                        allele_name: pair.top.name,
+                        is_on_top: true,
+                        is_on_bot: true,
+                    }];
+                }
+                [(pair.top, true), (pair.bot, false)]
+                    .into_iter()
+                    .filter(|(allele, _)| !allele.is_wild())
+                    .map(|(allele, is_top)| OffspringAllele {
+                        allele_name: allele.name,
+                        is_on_top: is_top,
+                        is_on_bot: !is_top,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Offspring {
+            genotype: strain.to_genotype_string(true, false),
+            alleles,
+            probability: strain.probability,
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use super::{
+    allele::Allele, allele_pair::AllelePair, chromosome_order, chromosome_pair::ChromosomePair,
+    GeneticsError,
+};
+use crate::models::chromosome_name::ChromosomeName;
+use std::{cmp::Ordering, collections::HashMap};
+
+/// One chromosome from each chromosome pair of a parent, sorted by chromosome
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gamete {
+    pub chromosomes: Vec<Vec<Allele>>,
+    pub prob: f64,
+}
+
+/// The genotype of a specimen, grouped by chromosome
+#[derive(Clone, Debug)]
+pub struct Strain {
+    pub chrom_pair_map: HashMap<Option<ChromosomeName>, ChromosomePair>,
+    pub probability: f64,
+}
+
+impl Strain {
+    pub fn new(allele_pairs: Vec<AllelePair>) -> Result<Self, GeneticsError> {
+        let mut strain = Self {
+            chrom_pair_map: HashMap::new(),
+            probability: 1.0,
+        };
+        for pair in allele_pairs {
+            strain.add_pair_to_strain(pair)?;
+        }
+        Ok(strain)
+    }
+
+    fn add_pair_to_strain(&mut self, allele_pair: AllelePair) -> Result<(), GeneticsError> {
+        let chrom_pair = self
+            .chrom_pair_map
+            .entry(allele_pair.get_chrom_name())
+            .or_default();
+        if chrom_pair.contains_same_gene_or_variation_as(&allele_pair) {
+            return Err(GeneticsError::DuplicateLocus(allele_pair.to_string()));
+        }
+        chrom_pair.insert_pair(allele_pair);
+        Ok(())
+    }
+
+    /// Males carry a single X chromosome, represented by a wild bottom chromosome
+    pub fn to_male(&self) -> Strain {
+        let mut male = self.clone();
+        if let Some(x_pair) = male.chrom_pair_map.get_mut(&Some(ChromosomeName::X)) {
+            for pair in x_pair.allele_pairs.iter_mut() {
+                pair.bot = pair.top.to_wild();
+            }
+        }
+        male
+    }
+
+    fn get_sorted_chrom_names(&self) -> Vec<Option<ChromosomeName>> {
+        let mut chrom_names: Vec<Option<ChromosomeName>> =
+            self.chrom_pair_map.keys().copied().collect();
+        chrom_names.sort_by_key(chromosome_order);
+        chrom_names
+    }
+
+    pub fn get_sorted_chrom_pairs(&self) -> Vec<&ChromosomePair> {
+        self.get_sorted_chrom_names()
+            .iter()
+            .map(|chrom_name| &self.chrom_pair_map[chrom_name])
+            .collect()
+    }
+
+    /// Genotype notation, e.g. "dpy-10(e128) II; unc-119(ed3)/+ III."
+    /// * `simplify` - leave out chromosomes that only carry wild alleles
+    /// * `exclude_eca` - leave out extrachromosomal arrays
+    pub fn to_genotype_string(&self, simplify: bool, exclude_eca: bool) -> String {
+        let genotype = self
+            .get_sorted_chrom_pairs()
+            .into_iter()
+            .filter(|pair| !((simplify && pair.is_wild()) || (exclude_eca && pair.is_eca())))
+            .map(|pair| pair.to_genotype_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        match genotype.is_empty() {
+            true => "(Wild)".to_owned(),
+            false => genotype + ".",
+        }
+    }
+
+    /// Checks if both strains carry the same non-wild alleles, ignoring chromosome orientation
+    pub fn equals(&self, other: &Strain) -> bool {
+        let non_wild_chroms = |strain: &Strain| -> Vec<Option<ChromosomeName>> {
+            strain
+                .chrom_pair_map
+                .iter()
+                .filter(|(_, pair)| !pair.is_wild())
+                .map(|(chrom_name, _)| *chrom_name)
+                .collect()
+        };
+        let chrom_names = non_wild_chroms(self);
+        let other_chrom_names = non_wild_chroms(other);
+
+        chrom_names.len() == other_chrom_names.len()
+            && chrom_names.iter().all(|chrom_name| {
+                match (
+                    self.chrom_pair_map.get(chrom_name),
+                    other.chrom_pair_map.get(chrom_name),
+                ) {
+                    (Some(pair), Some(other_pair)) => pair.equals(other_pair),
+                    _ => false,
+                }
+            })
+    }
+
+    /// Adds a wild allele pair for every locus of {other} this strain doesn't have yet,
+    /// so both parents line up locus by locus in a cross
+    pub fn fill_wilds_from(&mut self, other: &Strain) {
+        for chrom_name in other.get_sorted_chrom_names() {
+            let other_pair = &other.chrom_pair_map[&chrom_name];
+            self.chrom_pair_map
+                .entry(chrom_name)
+                .or_default()
+                .fill_wilds_from(other_pair);
+        }
+    }
+
+    /// All gametes this strain can produce, with their probabilities
+    pub fn meiosis(&self) -> Vec<Gamete> {
+        let initial = vec![Gamete {
+            chromosomes: vec![],
+            prob: 1.0,
+        }];
+        self.get_sorted_chrom_pairs()
+            .into_iter()
+            .map(|pair| pair.meiosis())
+            .fold(initial, |gametes, chrom_opts| {
+                gametes
+                    .iter()
+                    .flat_map(|gamete| {
+                        chrom_opts.iter().map(move |chrom_opt| {
+                            let mut chromosomes = gamete.chromosomes.clone();
+                            chromosomes.push(chrom_opt.chromosome.clone());
+                            Gamete {
+                                chromosomes,
+                                prob: gamete.prob * chrom_opt.prob,
+                            }
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    /// Combines every pair of gametes into the resulting offspring, most likely first
+    pub fn fertilize(
+        gametes1: &[Gamete],
+        gametes2: &[Gamete],
+    ) -> Result<Vec<Strain>, GeneticsError> {
+        let mut strains: Vec<Strain> = vec![];
+        for gamete1 in gametes1 {
+            for gamete2 in gametes2 {
+                let allele_pairs = gamete1
+                    .chromosomes
+                    .iter()
+                    .zip(gamete2.chromosomes.iter())
+                    .map(|(top, bot)| ChromosomePair::build_from_chroms(top, bot))
+                    .filter(|pair| !pair.is_eca() || !pair.is_wild())
+                    .flat_map(|pair| pair.allele_pairs)
+                    .collect();
+                let mut strain = Strain::new(allele_pairs)?;
+                strain.probability = gamete1.prob * gamete2.prob;
+                strains.push(strain);
+            }
+        }
+
+        let mut strains = Self::reduce_strains(strains);
+        Self::normalize_eca_options(&mut strains);
+        strains.sort_by(|a, b| {
+            b.probability
+                .partial_cmp(&a.probability)
+                .unwrap_or(Ordering::Equal)
+        });
+        Ok(strains)
+    }
+
+    /// Combines probabilities of equivalent strains so the resulting list is unique
+    fn reduce_strains(strains: Vec<Strain>) -> Vec<Strain> {
+        let mut reduced: Vec<Strain> = vec![];
+        for strain in strains {
+            match reduced.iter_mut().find(|existing| existing.equals(&strain)) {
+                Some(existing) => existing.probability += strain.probability,
+                None => reduced.push(strain),
+            }
+        }
+        reduced
+    }
+
+    /// Extrachromosomal arrays are inherited at unknown rates, so strains that only
+    /// differ by arrays share the average probability of the group
+    fn normalize_eca_options(strains: &mut [Strain]) {
+        let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, strain) in strains.iter().enumerate() {
+            groups
+                .entry(strain.to_genotype_string(false, true))
+                .or_default()
+                .push(idx);
+        }
+        for idxs in groups.values() {
+            let total: f64 = idxs.iter().map(|&idx| strains[idx].probability).sum();
+            let average = total / idxs.len() as f64;
+            for &idx in idxs {
+                strains[idx].probability = average;
+            }
+        }
+    }
+
+    /// Crosses this strain (providing the top chromosomes) with {other}
+    pub fn cross_with(&self, other: &Strain) -> Result<Vec<Strain>, GeneticsError> {
+        let mut this = self.clone();
+        let mut other = other.clone();
+        this.fill_wilds_from(&other);
+        other.fill_wilds_from(&this);
+        Self::fertilize(&this.meiosis(), &other.meiosis())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::genetics::allele::{Allele, Locus};
+    use crate::genetics::strain::Strain;
+    use crate::models::chromosome_name::ChromosomeName;
+    use pretty_assertions::assert_eq;
+
+    fn gene_allele(name: &str, gene: &str, chrom: ChromosomeName, gen_loc: f64) -> Allele {
+        Allele::new(
+            name.to_owned(),
+            Locus {
+                name: gene.to_owned(),
+                gene_name: Some(gene.to_owned()),
+                is_gene: true,
+                chromosome: Some(chrom),
+                gen_loc: Some(gen_loc),
+                gen_loc_estimated: false,
+            },
+        )
+    }
+
+    fn summarize(strains: &[Strain]) -> Vec<(String, f64)> {
+        strains
+            .iter()
+            .map(|strain| {
+                (
+                    strain.to_genotype_string(true, false),
+                    (strain.probability * 10000.0).round() / 10000.0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_self_cross_het() {
+        let a = gene_allele("e128", "dpy-10", ChromosomeName::Ii, 0.0);
+        let strain = Strain::new(vec![a.to_top_het()]).unwrap();
+
+        let offspring = strain.cross_with(&strain).unwrap();
+        assert_eq!(
+            summarize(&offspring),
+            vec![
+                ("dpy-10(e128)/+ II.".to_owned(), 0.5),
+                ("dpy-10(e128) II.".to_owned(), 0.25),
+                ("(Wild)".to_owned(), 0.25),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cross_linked_with_wild() {
+        let a = gene_allele("e1", "unc-1", ChromosomeName::Iv, 0.0);
+        let b = gene_allele("e2", "dpy-2", ChromosomeName::Iv, 10.0);
+        let herm = Strain::new(vec![a.to_top_het(), b.to_top_het()]).unwrap();
+        let wild = Strain::new(vec![]).unwrap();
+
+        let offspring = herm.cross_with(&wild).unwrap();
+        assert_eq!(
+            summarize(&offspring),
+            vec![
+                ("unc-1(e1) dpy-2(e2)/+ + IV.".to_owned(), 0.45),
+                ("(Wild)".to_owned(), 0.45),
+                ("unc-1(e1)/+ IV.".to_owned(), 0.05),
+                ("dpy-2(e2)/+ IV.".to_owned(), 0.05),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cross_multiple_chromosomes() {
+        let a = gene_allele("ed3", "unc-119", ChromosomeName::Iii, 5.59);
+        let b = gene_allele("n765", "lin-15B", ChromosomeName::X, 22.95);
+        let c = gene_allele("e1282", "dpy-20", ChromosomeName::Iv, 5.22);
+        let herm = Strain::new(vec![a.to_top_het(), b.to_homo()]).unwrap();
+        let male = Strain::new(vec![c.to_homo(), b.to_homo()])
+            .unwrap()
+            .to_male();
+
+        let offspring = herm.cross_with(&male).unwrap();
+        let total: f64 = offspring.iter().map(|strain| strain.probability).sum();
+        assert_eq!(offspring.len(), 4);
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // crossing is deterministic
+        let again = herm.cross_with(&male).unwrap();
+        assert_eq!(summarize(&offspring), summarize(&again));
+    }
+}
@@ -1,21 +1,53 @@
+pub mod aggregate;
 pub mod allele;
 pub mod allele_expr;
+pub mod audit;
+pub mod backup;
 pub mod bulk;
+pub mod cascade;
+pub mod chromosome_map;
 pub mod condition;
 pub mod cross_design;
 pub mod expr_relation;
 pub mod gene;
+pub mod genetics;
+pub mod journal;
 pub mod mock;
+pub mod page;
 pub mod phenotype;
+pub mod query;
+#[cfg(feature = "regex")]
+pub mod regexp;
+pub mod search;
+pub mod seed;
 pub mod strain;
 pub mod strain_allele;
 pub mod task;
+pub mod task_condition;
+pub mod task_dependency;
 pub mod variation;
+pub mod workspace;
 
 pub const SQLITE_BIND_LIMIT: usize = 32766;
 
+/// A row type read straight from a single table
+pub trait TableRow {
+    const TABLE: &'static str;
+    const COLUMNS: &'static [&'static str];
+    const KEY_COLUMNS: &'static [&'static str];
+}
+
+use crate::models::filter::FilterTypeError;
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Sqlite};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+    Pool, Row, Sqlite,
+};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 use thiserror::Error;
 use ts_rs::TS;
 
@@ -33,8 +65,86 @@ pub enum DbError {
     Delete(String),
     #[error("Failed to execute bulk insert: {0}")]
     BulkInsert(String),
+    #[error("Failed to compute cross: {0}")]
+    Genetics(String),
+    #[error("Failed to export: {0}")]
+    Export(String),
+    #[error("Failed to back up or restore database: {0}")]
+    Backup(String),
+    #[error("Failed to open workspace: {0}")]
+    Workspace(String),
+    #[error("Failed to audit database: {0}")]
+    Audit(String),
+    #[error("Failed to undo or redo change: {0}")]
+    Journal(String),
+    #[error("Refusing to delete every row: {0}")]
+    UnfilteredDelete(String),
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+}
+
+impl From<FilterTypeError> for DbError {
+    fn from(e: FilterTypeError) -> Self {
+        eprint!("Filter error: {e}");
+        DbError::InvalidFilter(e.to_string())
+    }
+}
+
+/// Quotes a table or column name for use in a query
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Quotes a list of column names, separated by commas
+fn quote_all(identifiers: &[String]) -> String {
+    identifiers
+        .iter()
+        .map(|identifier| quote(identifier))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 pub struct InnerDbState {
     pub conn_pool: Pool<Sqlite>,
 }
+
+impl InnerDbState {
+    /// Opens the database file at given path, creating it if needed, and brings it up
+    /// to date with the migrations
+    pub async fn connect(database_path: &Path) -> Result<Pool<Sqlite>> {
+        let pool_timeout = Duration::from_secs(30);
+        let connection_options = SqliteConnectOptions::new()
+            .filename(database_path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(pool_timeout);
+
+        let pool_options = SqlitePoolOptions::new()
+            .max_connections(100)
+            .acquire_timeout(pool_timeout);
+        #[cfg(feature = "regex")]
+        let pool_options = pool_options.after_connect(|conn, _| Box::pin(regexp::register(conn)));
+        let sqlite_pool = pool_options.connect_with(connection_options).await?;
+
+        sqlx::migrate!().run(&sqlite_pool).await?;
+        Self::install_journal(&sqlite_pool).await?;
+
+        Ok(sqlite_pool)
+    }
+
+    /// Canonical path of the open database file, or None for in-memory databases
+    pub async fn get_database_path(&self) -> Result<Option<PathBuf>, DbError> {
+        match sqlx::query("SELECT file FROM pragma_database_list WHERE name = 'main'")
+            .fetch_one(&self.conn_pool)
+            .await
+            .and_then(|row| row.try_get::<String, _>("file"))
+        {
+            Ok(file) => Ok(Path::new(&file).canonicalize().ok()),
+            Err(e) => {
+                eprint!("Get database path error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use super::{page::get_value, DbError, InnerDbState};
+use crate::models::{
+    filter::{ColumnType, FilterQueryBuilder},
+    FieldNameEnum,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{QueryBuilder, Row, Sqlite};
+use ts_rs::TS;
+
+/// A summary of a column over each group of rows
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[ts(export_to = "../src/models/db/db_Metric.ts")]
+#[serde(rename = "db_Metric")]
+pub enum Metric<F> {
+    /// Rows where the column isn't NULL
+    Count(F),
+    Min(F),
+    Max(F),
+    Avg(F),
+}
+
+impl<F: FieldNameEnum> Metric<F> {
+    fn get_sql(&self) -> Result<String, DbError> {
+        let (function, field_name) = match self {
+            Metric::Count(field_name) => {
+                return Ok(format!("COUNT({})", field_name.get_col_name()))
+            }
+            Metric::Min(field_name) => ("MIN", field_name),
+            Metric::Max(field_name) => ("MAX", field_name),
+            Metric::Avg(field_name) => ("AVG", field_name),
+        };
+        match field_name.get_col_type() {
+            ColumnType::Int | ColumnType::Real => {
+                Ok(format!("{function}({})", field_name.get_col_name()))
+            }
+            _ => Err(DbError::InvalidFilter(format!(
+                "{} isn't a numeric column",
+                field_name.get_col_name()
+            ))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_AggregateRow.ts")]
+#[serde(rename = "db_AggregateRow")]
+pub struct AggregateRow {
+    /// The value of the column grouped by
+    #[ts(type = "string | number | null")]
+    pub group: Value,
+    pub count: u32,
+    /// One for each metric asked for, None where the column is NULL in every row
+    pub values: Vec<Option<f64>>,
+}
+
+fn aggregate_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Aggregate error: {e}");
+    DbError::Query(e.to_string())
+}
+
+impl InnerDbState {
+    /// Counts the filtered rows in a table for each value of a column, along with the
+    /// metrics, in order of the column
+    pub(super) async fn aggregate_table<F: FieldNameEnum>(
+        &self,
+        table: &str,
+        filter: &impl FilterQueryBuilder<F>,
+        group_by: &F,
+        metrics: &[Metric<F>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate(&format!("FROM {table}"), None, filter, group_by, metrics)
+            .await
+    }
+
+    /// Like `aggregate_table`, but over any FROM clause. With joins, give a column for
+    /// each group to count the distinct values of instead of the rows.
+    pub(super) async fn aggregate<F: FieldNameEnum>(
+        &self,
+        from: &str,
+        count_distinct: Option<&str>,
+        filter: &impl FilterQueryBuilder<F>,
+        group_by: &F,
+        metrics: &[Metric<F>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        let group_col = group_by.get_col_name();
+        let count = match count_distinct {
+            Some(col_name) => format!("COUNT(DISTINCT {col_name})"),
+            None => "COUNT(*)".to_string(),
+        };
+
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new(format!("SELECT {group_col}, {count} AS count"));
+        for metric in metrics {
+            qb.push(format!(", {}", metric.get_sql()?));
+        }
+        qb.push(format!(" {from} "));
+        if filter.has_conditions() {
+            qb.push(" WHERE ");
+            filter.add_filtered_query(&mut qb, false, false)?;
+        }
+        qb.push(format!(" GROUP BY {group_col} ORDER BY {group_col}"));
+
+        qb.build()
+            .fetch_all(&self.conn_pool)
+            .await
+            .map_err(aggregate_error)?
+            .iter()
+            .map(|row| {
+                Ok(AggregateRow {
+                    group: get_value(row, 0)?,
+                    count: row.try_get("count")?,
+                    values: (0..metrics.len())
+                        .map(|i| Ok(get_value(row, i + 2)?.as_f64()))
+                        .collect::<Result<_, sqlx::Error>>()?,
+                })
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .map_err(aggregate_error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::aggregate::{AggregateRow, Metric};
+    use crate::interface::DbError;
+    use crate::models::filter::{Filter, FilterGroup};
+    use crate::models::gene::GeneFieldName;
+    use crate::models::phenotype::PhenotypeFieldName;
+    use crate::models::strain::StrainJoinFieldName;
+    use crate::InnerDbState;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+    use sqlx::{Pool, Sqlite};
+
+    fn get_row(group: Value, count: u32, values: Vec<Option<f64>>) -> AggregateRow {
+        AggregateRow {
+            group,
+            count,
+            values,
+        }
+    }
+
+    fn get_filter<T>(filters: Vec<Vec<(T, Filter)>>) -> FilterGroup<T>
+    where
+        T: ts_rs::TS + std::cmp::Eq + std::hash::Hash,
+    {
+        FilterGroup::<T> {
+            filters,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        }
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_aggregate_genes(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let metrics = [
+            Metric::Count(GeneFieldName::GeneticLoc),
+            Metric::Min(GeneFieldName::GeneticLoc),
+            Metric::Max(GeneFieldName::GeneticLoc),
+        ];
+
+        let rows = state
+            .aggregate_genes(&get_filter(vec![]), &GeneFieldName::Chromosome, &metrics)
+            .await?;
+        assert_eq!(
+            rows,
+            vec![
+                get_row(json!("II"), 1, vec![Some(1.0), Some(0.0), Some(0.0)]),
+                get_row(json!("III"), 1, vec![Some(1.0), Some(5.59), Some(5.59)]),
+                get_row(json!("IV"), 4, vec![Some(4.0), Some(3.51), Some(5.22)]),
+                get_row(json!("X"), 2, vec![Some(2.0), Some(-1.35), Some(22.95)]),
+            ]
+        );
+
+        let filter = get_filter(vec![vec![(
+            GeneFieldName::Chromosome,
+            Filter::Equal("X".into()),
+        )]]);
+        let rows = state
+            .aggregate_genes(&filter, &GeneFieldName::Chromosome, &[])
+            .await?;
+        assert_eq!(rows, vec![get_row(json!("X"), 2, vec![])]);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_aggregate_phenotypes(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let rows = state
+            .aggregate_phenotypes(
+                &get_filter(vec![]),
+                &PhenotypeFieldName::Lethal,
+                &[Metric::Avg(PhenotypeFieldName::MaturationDays)],
+            )
+            .await?;
+        assert_eq!(
+            rows,
+            vec![
+                get_row(Value::Null, 6, vec![None]),
+                get_row(json!(0), 18, vec![Some(55.0 / 17.0)]),
+                get_row(json!(1), 5, vec![None]),
+            ]
+        );
+
+        let res = state
+            .aggregate_phenotypes(
+                &get_filter(vec![]),
+                &PhenotypeFieldName::Lethal,
+                &[Metric::Avg(PhenotypeFieldName::ShortName)],
+            )
+            .await;
+        assert!(matches!(res, Err(DbError::InvalidFilter(_))));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_aggregate_joined_strains(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        // strains are counted once per chromosome their alleles are on
+        let rows = state
+            .aggregate_joined_strains(
+                &get_filter(vec![]),
+                &StrainJoinFieldName::Gene(GeneFieldName::Chromosome),
+                &[],
+            )
+            .await?;
+        assert_eq!(
+            rows,
+            vec![
+                get_row(Value::Null, 2, vec![]),
+                get_row(json!("II"), 2, vec![]),
+                get_row(json!("III"), 2, vec![]),
+                get_row(json!("IV"), 1, vec![]),
+                get_row(json!("X"), 1, vec![]),
+            ]
+        );
+        Ok(())
+    }
+}
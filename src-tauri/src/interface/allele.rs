@@ -1,11 +1,85 @@
-use super::{bulk::Bulk, DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest, PageSource},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
-    allele::{Allele, AlleleFieldName},
-    filter::{Count, FilterGroup, FilterQueryBuilder},
+    allele::{Allele, AlleleFieldName, AlleleJoinFieldName},
+    filter::{Conjunction, Count, FilterQueryBuilder},
     gene::{Gene, GeneFieldName},
 };
 use anyhow::Result;
-use sqlx::{QueryBuilder, Row, Sqlite};
+use csv::Writer;
+use serde_json::{json, Value};
+use sqlx::{sqlite::SqliteRow, FromRow, QueryBuilder, Row, Sqlite};
+use std::io::Write;
+
+impl TableRow for Allele {
+    const TABLE: &'static str = "alleles";
+    const COLUMNS: &'static [&'static str] =
+        &["name", "contents", "systematic_gene_name", "variation_name"];
+    const KEY_COLUMNS: &'static [&'static str] = &["name"];
+}
+
+impl BulkInsertable for Allele {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.name),
+            json!(self.contents),
+            json!(self.systematic_gene_name),
+            json!(self.variation_name),
+        ]
+    }
+}
+
+/// Alleles with their gene and variation, which the columns of AlleleJoinFieldName refer to
+const ALLELE_JOIN: &str = "
+    FROM alleles
+    LEFT JOIN genes ON alleles.systematic_gene_name IS genes.systematic_name
+    LEFT JOIN variations ON alleles.variation_name IS variations.allele_name
+    ";
+
+/// Allele columns to select from ALLELE_JOIN
+const JOINED_ALLELE_COLUMNS: &str =
+    "alleles.name, alleles.contents, alleles.systematic_gene_name, alleles.variation_name";
+
+/// Alleles with their gene, which get_filtered_alleles_with_gene_filter reads rows from
+const ALLELE_GENE_COLUMNS: &str = "name, contents, systematic_gene_name, variation_name,
+    systematic_name, descriptive_name, chromosome, phys_loc, gen_loc, recomb_suppressor_start, recomb_suppressor_end";
+const ALLELE_GENE_JOIN: &str = "
+    FROM alleles
+    LEFT JOIN genes
+    ON systematic_gene_name IS systematic_name
+    ";
+
+fn get_allele_and_gene(row: &SqliteRow) -> Result<(Allele, Gene), sqlx::Error> {
+    let allele = Allele {
+        name: row.try_get(0)?,
+        contents: row.try_get(1)?,
+        systematic_gene_name: row.try_get(2)?,
+        variation_name: row.try_get(3)?,
+    };
+    let gene = Gene {
+        systematic_name: row.try_get(4)?,
+        descriptive_name: row.try_get(5)?,
+        chromosome: row
+            .try_get::<Option<String>, _>(6)?
+            .map(|v: String| v.into()),
+        phys_loc: row.try_get::<Option<i64>, _>(7)?.map(|v| v as i32),
+        gen_loc: row.try_get(8)?,
+        recomb_suppressor: match (
+            row.try_get::<Option<i64>, _>(9)?,
+            row.try_get::<Option<i64>, _>(10)?,
+        ) {
+            (Some(start), Some(end)) => Some((start as i32, end as i32)),
+            _ => None,
+        },
+    };
+    Ok((allele, gene))
+}
 
 impl InnerDbState {
     pub async fn get_alleles(&self) -> Result<Vec<Allele>, DbError> {
@@ -28,12 +102,12 @@ impl InnerDbState {
 
     pub async fn get_filtered_alleles(
         &self,
-        filter: &FilterGroup<AlleleFieldName>,
+        filter: &impl FilterQueryBuilder<AlleleFieldName>,
     ) -> Result<Vec<Allele>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT name, contents, systematic_gene_name, variation_name FROM alleles",
         );
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
         match qb
             .build_query_as::<Allele>()
             .fetch_all(&self.conn_pool)
@@ -47,13 +121,50 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_alleles_page(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<Allele>, DbError> {
+        self.get_page::<Allele, _>(filter, page).await
+    }
+
+    pub async fn query_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleFieldName>,
+        facets: Vec<AlleleFieldName>,
+    ) -> Result<QueryResult<Allele, AlleleFieldName>, DbError> {
+        self.query::<Allele, _>(filter, facets).await
+    }
+
+    /// Like `query_alleles`, but the filter and facets can use the allele's gene and
+    /// variation too, such as counting the alleles on each chromosome
+    pub async fn query_joined_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleJoinFieldName>,
+        facets: Vec<AlleleJoinFieldName>,
+    ) -> Result<QueryResult<Allele, AlleleJoinFieldName>, DbError> {
+        self.query_from(JOINED_ALLELE_COLUMNS, ALLELE_JOIN, filter, facets)
+            .await
+    }
+
+    pub async fn aggregate_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleFieldName>,
+        group_by: &AlleleFieldName,
+        metrics: &[Metric<AlleleFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("alleles", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_alleles(
         &self,
-        filter: &FilterGroup<AlleleFieldName>,
+        filter: &impl FilterQueryBuilder<AlleleFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT COUNT(*) as count FROM alleles");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -68,63 +179,110 @@ impl InnerDbState {
         }
     }
 
+    /// Filters and orders alleles on their own columns and those of their gene and variation
+    pub async fn get_filtered_joined_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleJoinFieldName>,
+    ) -> Result<Vec<Allele>, DbError> {
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new(format!("SELECT {JOINED_ALLELE_COLUMNS}"));
+        qb.push(ALLELE_JOIN);
+        filter.add_filtered_query(&mut qb, true, true)?;
+        match qb
+            .build_query_as::<Allele>()
+            .fetch_all(&self.conn_pool)
+            .await
+        {
+            Ok(exprs) => Ok(exprs),
+            Err(e) => {
+                eprint!("Get Filtered Joined Allele error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+
+    /// A page of the joined alleles, with ties broken by allele name
+    pub async fn get_filtered_joined_alleles_page(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleJoinFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<Allele>, DbError> {
+        let source = PageSource {
+            columns: JOINED_ALLELE_COLUMNS,
+            from: ALLELE_JOIN,
+            key_columns: &["alleles.name"],
+        };
+        self.get_row_page(
+            &source,
+            filter.get_order_by(),
+            filter.has_conditions(),
+            |qb| Ok(filter.add_filtered_query(qb, false, false)?),
+            page,
+        )
+        .await?
+        .try_map(|row| Allele::from_row(&row))
+        .map_err(|e| {
+            eprint!("Get Filtered Joined Allele Page error: {e}");
+            DbError::Query(e.to_string())
+        })
+    }
+
+    pub async fn get_count_filtered_joined_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleJoinFieldName>,
+    ) -> Result<u32, DbError> {
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COUNT(*) as count");
+        qb.push(ALLELE_JOIN);
+        filter.add_filtered_query(&mut qb, true, false)?;
+
+        match qb
+            .build_query_as::<Count>()
+            .fetch_one(&self.conn_pool)
+            .await
+        {
+            Ok(count) => Ok(count.count),
+            Err(e) => {
+                eprint!("Get Filtered Joined Alleles Count error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+
+    /// Aggregates alleles joined to their genes and variations, counting each allele
+    /// once per group
+    pub async fn aggregate_joined_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleJoinFieldName>,
+        group_by: &AlleleJoinFieldName,
+        metrics: &[Metric<AlleleJoinFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate(ALLELE_JOIN, Some("alleles.name"), filter, group_by, metrics)
+            .await
+    }
+
+    /// The allele filter's ordering and pagination apply to the results
     pub async fn get_filtered_alleles_with_gene_filter(
         &self,
-        allele_filter: &FilterGroup<AlleleFieldName>,
-        gene_filter: &FilterGroup<GeneFieldName>,
+        allele_filter: &impl FilterQueryBuilder<AlleleFieldName>,
+        gene_filter: &impl FilterQueryBuilder<GeneFieldName>,
+        conjunction: Conjunction,
     ) -> Result<Vec<(Allele, Gene)>, DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-            "SELECT name, contents, systematic_gene_name, variation_name, 
-            systematic_name, descriptive_name, chromosome, phys_loc, gen_loc, recomb_suppressor_start, recomb_suppressor_end 
-            FROM alleles 
-            LEFT JOIN genes 
-            ON systematic_gene_name IS systematic_name 
-            ",
-        );
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new(format!("SELECT {ALLELE_GENE_COLUMNS} {ALLELE_GENE_JOIN}"));
 
-        if !allele_filter.filters.is_empty() || !gene_filter.filters.is_empty() {
+        if allele_filter.has_conditions() || gene_filter.has_conditions() {
             qb.push(" WHERE ");
         }
-        allele_filter.add_filtered_query(&mut qb, false, true);
-
-        if !allele_filter.filters.is_empty() && !gene_filter.filters.is_empty() {
-            qb.push(" OR ");
-        }
-        gene_filter.add_filtered_query(&mut qb, false, true);
+        Self::push_allele_gene_conditions(&mut qb, allele_filter, gene_filter, &conjunction)?;
+        allele_filter.add_ordering(&mut qb, true);
 
-        match qb.build().fetch_all(&self.conn_pool).await {
-            Ok(exprs) => {
-                let tuples: Vec<(Allele, Gene)> = exprs
-                    .into_iter()
-                    .map(|row| {
-                        let a = Allele {
-                            name: row.get(0),
-                            contents: row.get(1),
-                            systematic_gene_name: row.get(2),
-                            variation_name: row.get(3),
-                        };
-                        let g = Gene {
-                            systematic_name: row.get(4),
-                            descriptive_name: row.get(5),
-                            chromosome: row.get::<Option<String>, _>(6).map(|v: String| v.into()),
-                            phys_loc: row.get::<Option<i64>, _>(7).map(|v| v as i32),
-                            gen_loc: row.get(8),
-                            recomb_suppressor: match (
-                                row.get::<Option<i64>, _>(9),
-                                row.get::<Option<i64>, _>(10),
-                            ) {
-                                (Some(start), Some(end)) => Some((start as i32, end as i32)),
-                                _ => None,
-                            },
-                        };
-                        (a, g)
-                    })
-                    .collect();
-
-                Ok(tuples)
-            }
-
-            // Ok(exprs.into_iter().collect()),
+        match qb
+            .build()
+            .fetch_all(&self.conn_pool)
+            .await
+            .and_then(|rows| rows.iter().map(get_allele_and_gene).collect())
+        {
+            Ok(tuples) => Ok(tuples),
             Err(e) => {
                 eprint!("Get Filtered Allele error: {e}");
                 Err(DbError::Query(e.to_string()))
@@ -132,6 +290,50 @@ impl InnerDbState {
         }
     }
 
+    /// A page of alleles with their genes, in the allele filter's order with ties broken
+    /// by allele name
+    pub async fn get_filtered_alleles_with_gene_filter_page(
+        &self,
+        allele_filter: &impl FilterQueryBuilder<AlleleFieldName>,
+        gene_filter: &impl FilterQueryBuilder<GeneFieldName>,
+        conjunction: Conjunction,
+        page: &PageRequest,
+    ) -> Result<Page<(Allele, Gene)>, DbError> {
+        let source = PageSource {
+            columns: ALLELE_GENE_COLUMNS,
+            from: ALLELE_GENE_JOIN,
+            key_columns: &["name"],
+        };
+        self.get_row_page(
+            &source,
+            allele_filter.get_order_by(),
+            allele_filter.has_conditions() || gene_filter.has_conditions(),
+            |qb| Self::push_allele_gene_conditions(qb, allele_filter, gene_filter, &conjunction),
+            page,
+        )
+        .await?
+        .try_map(|row| get_allele_and_gene(&row))
+        .map_err(|e| {
+            eprint!("Get Filtered Allele Page error: {e}");
+            DbError::Query(e.to_string())
+        })
+    }
+
+    /// The conditions of both filters, without WHERE
+    fn push_allele_gene_conditions(
+        qb: &mut QueryBuilder<Sqlite>,
+        allele_filter: &impl FilterQueryBuilder<AlleleFieldName>,
+        gene_filter: &impl FilterQueryBuilder<GeneFieldName>,
+        conjunction: &Conjunction,
+    ) -> Result<(), DbError> {
+        allele_filter.add_filtered_query(qb, false, false)?;
+        if allele_filter.has_conditions() && gene_filter.has_conditions() {
+            qb.push(conjunction.get_sql());
+        }
+        gene_filter.add_filtered_query(qb, false, false)?;
+        Ok(())
+    }
+
     pub async fn insert_allele(&self, allele: &Allele) -> Result<(), DbError> {
         match sqlx::query!(
             "INSERT INTO alleles (name, contents, systematic_gene_name, variation_name)
@@ -153,70 +355,52 @@ impl InnerDbState {
         }
     }
 
-    pub async fn insert_alleles(&self, bulk: Bulk<Allele>) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.len()
-            )));
-        }
-        let bind_limit = SQLITE_BIND_LIMIT / 4;
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-                "INSERT OR IGNORE INTO alleles (name, contents, systematic_gene_name, variation_name) ",
-            );
+    pub async fn insert_alleles(
+        &self,
+        bulk: Bulk<Allele>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
+    }
 
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.name)
-                    .push_bind(item.contents)
-                    .push_bind(item.systematic_gene_name)
-                    .push_bind(item.variation_name);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk Insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            };
-        }
-        Ok(())
+    pub async fn export_filtered_alleles<W: Write>(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<Allele, _, _>(filter, writer).await
     }
 
-    pub async fn delete_filtered_alleles(
+    /// Counts the rows in each table that deleting the filtered alleles would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_alleles(
         &self,
-        filter: &FilterGroup<AlleleFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM alleles");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter: &impl FilterQueryBuilder<AlleleFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("alleles", filter).await
+    }
 
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete Allele error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+    pub async fn delete_filtered_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("alleles", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::interface::bulk::Bulk;
-    use crate::interface::mock;
-    use crate::models::allele::AlleleFieldName;
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
+    use crate::interface::page::{PageRequest, RowCount};
+    use crate::interface::{mock, SQLITE_BIND_LIMIT};
+    use crate::models::allele::{AlleleFieldName, AlleleJoinFieldName};
     use crate::models::chromosome_name::ChromosomeName;
-    use crate::models::filter::{Filter, FilterGroup, Order};
+    use crate::models::filter::{Conjunction, Filter, FilterGroup, FilterValue, Order};
     use crate::models::gene::GeneFieldName;
+    use crate::models::variation::VariationFieldName;
     use crate::models::{allele::Allele, gene::Gene, variation::Variation};
     use crate::InnerDbState;
     use anyhow::Result;
@@ -367,7 +551,9 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<Allele> = Bulk::from_reader(&mut reader);
 
-        state.insert_alleles(bulk).await?;
+        state
+            .insert_alleles(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let expected = vec![
             Allele {
@@ -396,11 +582,11 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
                 filters: vec![vec![
                     (
                         AlleleFieldName::SysGeneName,
-                        Filter::Equal("F27D9.1".to_owned()),
+                        Filter::Equal("F27D9.1".into()),
                     ),
                     (
                         AlleleFieldName::SysGeneName,
-                        Filter::Equal("T14B4.7".to_owned()),
+                        Filter::Equal("T14B4.7".into()),
                     ),
                 ]],
                 order_by: vec![(AlleleFieldName::Name, Order::Asc)],
@@ -420,7 +606,7 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
             .get_filtered_alleles(&FilterGroup::<AlleleFieldName> {
                 filters: vec![vec![(
                     AlleleFieldName::Name,
-                    Filter::Equal("non-existant-allele".to_owned()),
+                    Filter::Equal("non-existant-allele".into()),
                 )]],
                 order_by: vec![],
                 limit: None,
@@ -503,6 +689,179 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         Ok(())
     }
 
+    async fn get_allele_names(state: &InnerDbState, filter: Filter) -> Result<Vec<String>> {
+        Ok(state
+            .get_filtered_alleles(&FilterGroup::<AlleleFieldName> {
+                filters: vec![vec![(AlleleFieldName::Name, filter)]],
+                order_by: vec![(AlleleFieldName::Name, Order::Asc)],
+                limit: None,
+                offset: None,
+            })
+            .await?
+            .into_iter()
+            .map(|allele| allele.name)
+            .collect())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_filter_alleles_in_list(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let allele_count = state.get_alleles().await?.len();
+
+        let names: Vec<FilterValue> = vec!["ed3".into(), "e128".into(), "x1".into()];
+        assert_eq!(
+            get_allele_names(&state, Filter::In(names.clone())).await?,
+            vec!["e128", "ed3"]
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::NotIn(names)).await?.len(),
+            allele_count - 2
+        );
+
+        // more names than there are bind parameters
+        let mut names: Vec<FilterValue> = (0..SQLITE_BIND_LIMIT)
+            .map(|i| format!("x{i}").into())
+            .collect();
+        names.push("ed3".into());
+        assert_eq!(
+            get_allele_names(&state, Filter::In(names)).await?,
+            vec!["ed3"]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_filter_alleles_by_pattern(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let allele_count = state.get_alleles().await?.len();
+
+        assert_eq!(
+            get_allele_names(&state, Filter::StartsWith("OXI".to_string())).await?,
+            vec!["oxIs12", "oxIs363", "oxIs644"]
+        );
+        assert_eq!(
+            get_allele_names(
+                &state,
+                Filter::CaseSensitive(true, Box::new(Filter::StartsWith("OXI".to_string())))
+            )
+            .await?,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::EndsWith("(V)".to_string())).await?,
+            vec!["eT1(V)"]
+        );
+        assert_eq!(
+            get_allele_names(
+                &state,
+                Filter::CaseSensitive(true, Box::new(Filter::StartsWith("tmC5[".to_string())))
+            )
+            .await?,
+            vec!["tmC5[F36H1.3(tmIs1220)]"]
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::NotLike("ox".to_string()))
+                .await?
+                .len(),
+            allele_count - 9
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::Glob("e1*".to_string())).await?,
+            vec!["e128", "e1282", "e138"]
+        );
+        assert_eq!(
+            get_allele_names(
+                &state,
+                Filter::CaseSensitive(false, Box::new(Filter::Glob("OXIS*".to_string())))
+            )
+            .await?,
+            vec!["oxIs12", "oxIs363", "oxIs644"]
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::Equal("ED3".into())).await?,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            get_allele_names(
+                &state,
+                Filter::CaseSensitive(false, Box::new(Filter::Equal("ED3".into())))
+            )
+            .await?,
+            vec!["ed3"]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_filter_alleles_like_is_literal(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let allele_count = state.get_alleles().await?.len();
+
+        // _ and % aren't wildcards, so these don't match e128 or e1282
+        assert_eq!(
+            get_allele_names(&state, Filter::Like("e_28".to_string())).await?,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::Like("e%8".to_string())).await?,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::NotLike("e_28".to_string()))
+                .await?
+                .len(),
+            allele_count
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::Like("(V".to_string())).await?,
+            vec!["eT1(V)"]
+        );
+        Ok(())
+    }
+
+    #[cfg(not(feature = "regex"))]
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_filter_alleles_regex_not_enabled(pool: Pool<Sqlite>) -> Result<()> {
+        use crate::interface::DbError;
+        let state = InnerDbState { conn_pool: pool };
+
+        let res = get_allele_names(&state, Filter::Regex("^ox".to_string())).await;
+        assert!(matches!(
+            res.unwrap_err().downcast_ref::<DbError>(),
+            Some(DbError::InvalidFilter(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "regex")]
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_filter_alleles_regex(
+        pool_options: sqlx::sqlite::SqlitePoolOptions,
+        connect_options: sqlx::sqlite::SqliteConnectOptions,
+    ) -> Result<()> {
+        use crate::interface::{regexp, DbError};
+        let pool = pool_options
+            .after_connect(|conn, _| Box::pin(regexp::register(conn)))
+            .connect_with(connect_options)
+            .await?;
+        let state = InnerDbState { conn_pool: pool };
+
+        assert_eq!(
+            get_allele_names(&state, Filter::Regex(r"^ox(Is|Ti)\d+$".to_string())).await?,
+            vec!["oxIs12", "oxIs363", "oxIs644", "oxTi302", "oxTi75"]
+        );
+        assert_eq!(
+            get_allele_names(&state, Filter::Regex(r"^e\d{3}$".to_string())).await?,
+            vec!["e128", "e138"]
+        );
+        let res = get_allele_names(&state, Filter::Regex("(".to_string())).await;
+        assert!(matches!(
+            res.unwrap_err().downcast_ref::<DbError>(),
+            Some(DbError::Query(_))
+        ));
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("full_db"))]
     async fn test_get_filtered_alleles_with_gene_filter(pool: Pool<Sqlite>) -> Result<()> {
         let state = InnerDbState { conn_pool: pool };
@@ -525,13 +884,141 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         };
 
         let exprs = state
-            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter)
+            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter, Conjunction::Or)
             .await?;
 
         assert_eq!(exprs, mock::allele::get_filtered_alleles_with_gene_filter());
         Ok(())
     }
 
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_filtered_joined_alleles_page(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = FilterGroup::<AlleleJoinFieldName> {
+            filters: vec![vec![
+                (
+                    AlleleJoinFieldName::Gene(GeneFieldName::Chromosome),
+                    Filter::Equal("IV".into()),
+                ),
+                (
+                    AlleleJoinFieldName::Variation(VariationFieldName::Chromosome),
+                    Filter::Equal("IV".into()),
+                ),
+            ]],
+            order_by: vec![(
+                AlleleJoinFieldName::Gene(GeneFieldName::Chromosome),
+                Order::Asc,
+            )],
+            limit: None,
+            offset: None,
+        };
+
+        // alleles of variations have no gene chromosome, so come first
+        let mut pages = vec![];
+        let mut cursor = None;
+        loop {
+            let page = state
+                .get_filtered_joined_alleles_page(
+                    &filter,
+                    &PageRequest {
+                        cursor,
+                        limit: 3,
+                        count: true,
+                    },
+                )
+                .await?;
+            assert_eq!(page.total, Some(RowCount::Exact(7)));
+            pages.push(
+                page.rows
+                    .into_iter()
+                    .map(|allele| allele.name)
+                    .collect::<Vec<_>>(),
+            );
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(
+            pages,
+            vec![
+                vec!["oxIs363", "tmC5", "tmC5[F36H1.3(tmIs1220)]"],
+                vec!["e1282", "e138", "hd43"],
+                vec!["ox1059"],
+            ]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_filtered_alleles_with_gene_filter_page(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let allele_filter = &FilterGroup::<AlleleFieldName> {
+            filters: vec![vec![(AlleleFieldName::Name, Filter::Like("e".to_owned()))]],
+            order_by: vec![(AlleleFieldName::Name, Order::Asc)],
+            limit: Some(1),
+            offset: None,
+        };
+        let gene_filter = &FilterGroup::<GeneFieldName> {
+            filters: vec![vec![(
+                GeneFieldName::DescName,
+                Filter::Like("unc".to_owned()),
+            )]],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+
+        let mut pages = vec![];
+        let mut cursor = None;
+        loop {
+            let page = state
+                .get_filtered_alleles_with_gene_filter_page(
+                    allele_filter,
+                    gene_filter,
+                    Conjunction::Or,
+                    &PageRequest {
+                        cursor,
+                        limit: 4,
+                        count: true,
+                    },
+                )
+                .await?;
+            assert_eq!(page.total, Some(RowCount::Exact(9)));
+            pages.push(
+                page.rows
+                    .into_iter()
+                    .map(|(allele, gene)| (allele.name, gene.descriptive_name))
+                    .collect::<Vec<_>>(),
+            );
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        let get_pair =
+            |allele: &str, gene: Option<&str>| (allele.to_string(), gene.map(String::from));
+        assert_eq!(
+            pages,
+            vec![
+                vec![
+                    get_pair("e128", Some("dpy-10")),
+                    get_pair("e1282", Some("dpy-20")),
+                    get_pair("e138", Some("unc-24")),
+                    get_pair("ed3", Some("unc-119")),
+                ],
+                vec![
+                    get_pair("eT1(III)", None),
+                    get_pair("eT1(V)", None),
+                    get_pair("md299", Some("unc-18")),
+                    get_pair("oxEx219999", None),
+                ],
+                vec![get_pair("oxEx2254", None)],
+            ]
+        );
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("full_db"))]
     async fn test_get_filtered_alleles_with_empty_gene_filter(pool: Pool<Sqlite>) -> Result<()> {
         let state = InnerDbState { conn_pool: pool };
@@ -551,7 +1038,7 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         };
 
         let exprs = state
-            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter)
+            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter, Conjunction::Or)
             .await?;
 
         assert_eq!(
@@ -585,7 +1072,7 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         };
 
         let exprs = state
-            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter)
+            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter, Conjunction::Or)
             .await?;
 
         assert_eq!(
@@ -616,13 +1103,92 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         };
 
         let exprs = state
-            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter)
+            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter, Conjunction::Or)
             .await?;
 
         assert_eq!(exprs, mock::allele::get_alleles_with_genes());
         Ok(())
     }
 
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_filtered_alleles_with_gene_filter_and_conjunction(
+        pool: Pool<Sqlite>,
+    ) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let allele_filter = &FilterGroup::<AlleleFieldName> {
+            filters: vec![vec![(AlleleFieldName::Name, Filter::Like("e".to_owned()))]],
+            order_by: vec![(AlleleFieldName::Name, Order::Asc)],
+            limit: Some(1),
+            offset: Some(1),
+        };
+
+        let gene_filter = &FilterGroup::<GeneFieldName> {
+            filters: vec![vec![(
+                GeneFieldName::Chromosome,
+                Filter::Equal("IV".into()),
+            )]],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+
+        // e1282 and e138 are both on IV, and the allele filter pages past the first
+        let exprs = state
+            .get_filtered_alleles_with_gene_filter(allele_filter, gene_filter, Conjunction::And)
+            .await?;
+        let names: Vec<(String, Option<String>)> = exprs
+            .into_iter()
+            .map(|(allele, gene)| (allele.name, gene.descriptive_name))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("e138".to_string(), Some("unc-24".to_string()))]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_filtered_joined_alleles(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        // alleles on X, whether through their gene or their variation
+        let filter = FilterGroup::<AlleleJoinFieldName> {
+            filters: vec![vec![
+                (
+                    AlleleJoinFieldName::Gene(GeneFieldName::Chromosome),
+                    Filter::Equal("X".into()),
+                ),
+                (
+                    AlleleJoinFieldName::Variation(VariationFieldName::Chromosome),
+                    Filter::Equal("X".into()),
+                ),
+            ]],
+            order_by: vec![
+                (
+                    AlleleJoinFieldName::Gene(GeneFieldName::GeneticLoc),
+                    Order::Desc,
+                ),
+                (
+                    AlleleJoinFieldName::Allele(AlleleFieldName::Name),
+                    Order::Asc,
+                ),
+            ],
+            limit: Some(2),
+            offset: Some(1),
+        };
+
+        let names: Vec<String> = state
+            .get_filtered_joined_alleles(&filter)
+            .await?
+            .into_iter()
+            .map(|allele| allele.name)
+            .collect();
+        assert_eq!(names, vec!["n765", "md299"]);
+        assert_eq!(state.get_count_filtered_joined_alleles(&filter).await?, 4);
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("allele"))]
     async fn test_delete_single_allele(pool: Pool<Sqlite>) -> Result<()> {
         let state = InnerDbState { conn_pool: pool };
@@ -630,16 +1196,15 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         assert_eq!(alleles.len(), mock::allele::get_alleles().len());
 
         let delete_filter = &FilterGroup::<AlleleFieldName> {
-            filters: vec![vec![(
-                AlleleFieldName::Name,
-                Filter::Equal("cn64".to_string()),
-            )]],
+            filters: vec![vec![(AlleleFieldName::Name, Filter::Equal("cn64".into()))]],
             order_by: vec![],
             limit: None,
             offset: None,
         };
 
-        state.delete_filtered_alleles(delete_filter).await?;
+        state
+            .delete_filtered_alleles(delete_filter, DeleteMode::Restrict)
+            .await?;
 
         alleles = state.get_alleles().await?;
         assert_eq!(alleles.len(), mock::allele::get_alleles().len() - 1);
@@ -670,7 +1235,9 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         let filtered_len = alleles.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_alleles(filter).await?;
+        state
+            .delete_filtered_alleles(filter, DeleteMode::Restrict)
+            .await?;
         alleles = state.get_alleles().await?;
 
         assert_eq!(alleles.len(), orig_len - filtered_len);
@@ -686,14 +1253,14 @@ oxTi302,[Peft-3::mCherry; cbr-unc-119(+)],,oxTi302"
         let mut alleles: Vec<Allele> = state.get_alleles().await?;
         assert_eq!(alleles.len(), mock::allele::get_alleles().len());
 
-        let filter = &FilterGroup::<AlleleFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_alleles(filter).await?;
+        let preview = state.preview_delete_all(DeletableTable::Alleles).await?;
+        state
+            .delete_all(
+                DeletableTable::Alleles,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         alleles = state.get_alleles().await?;
 
         assert_eq!(alleles.len(), 0);
@@ -1,12 +1,48 @@
-use super::{bulk::Bulk, DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
     allele_expr::{AlleleExpression, AlleleExpressionDb, AlleleExpressionFieldName},
-    filter::{Count, FilterGroup, FilterQueryBuilder},
+    filter::{Count, FilterQueryBuilder},
 };
 use anyhow::Result;
+use csv::Writer;
+use serde_json::{json, Value};
 use sqlx::{QueryBuilder, Sqlite};
+use std::io::Write;
 
 //select allele_name, expressing_phenotype_name, expressing_phenotype_wild, dominance from allele_exprs order by allele_name, expressing_phenotype_name, expressing_phenotype_wild
+impl TableRow for AlleleExpressionDb {
+    const TABLE: &'static str = "allele_exprs";
+    const COLUMNS: &'static [&'static str] = &[
+        "allele_name",
+        "expressing_phenotype_name",
+        "expressing_phenotype_wild",
+        "dominance",
+    ];
+    const KEY_COLUMNS: &'static [&'static str] = &[
+        "allele_name",
+        "expressing_phenotype_name",
+        "expressing_phenotype_wild",
+    ];
+}
+
+impl BulkInsertable for AlleleExpressionDb {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.allele_name),
+            json!(self.expressing_phenotype_name),
+            json!(self.expressing_phenotype_wild),
+            json!(self.dominance),
+        ]
+    }
+}
+
 impl InnerDbState {
     pub async fn get_allele_exprs(&self) -> Result<Vec<AlleleExpression>, DbError> {
         match sqlx::query_as!(
@@ -30,11 +66,11 @@ impl InnerDbState {
 
     pub async fn get_filtered_allele_exprs(
         &self,
-        filter: &FilterGroup<AlleleExpressionFieldName>,
+        filter: &impl FilterQueryBuilder<AlleleExpressionFieldName>,
     ) -> Result<Vec<AlleleExpression>, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT allele_name, expressing_phenotype_name, expressing_phenotype_wild, dominance FROM allele_exprs");
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<AlleleExpressionDb>()
@@ -49,13 +85,45 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_allele_exprs_page(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleExpressionFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<AlleleExpression>, DbError> {
+        Ok(self
+            .get_page::<AlleleExpressionDb, _>(filter, page)
+            .await?
+            .map(AlleleExpression::from))
+    }
+
+    pub async fn query_allele_exprs(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleExpressionFieldName>,
+        facets: Vec<AlleleExpressionFieldName>,
+    ) -> Result<QueryResult<AlleleExpression, AlleleExpressionFieldName>, DbError> {
+        Ok(self
+            .query::<AlleleExpressionDb, _>(filter, facets)
+            .await?
+            .map(AlleleExpression::from))
+    }
+
+    pub async fn aggregate_allele_exprs(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleExpressionFieldName>,
+        group_by: &AlleleExpressionFieldName,
+        metrics: &[Metric<AlleleExpressionFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("allele_exprs", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_allele_exprs(
         &self,
-        filter: &FilterGroup<AlleleExpressionFieldName>,
+        filter: &impl FilterQueryBuilder<AlleleExpressionFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT COUNT(*) as count FROM allele_exprs");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -91,70 +159,51 @@ impl InnerDbState {
         }
     }
 
-    pub async fn insert_allele_exprs(&self, bulk: Bulk<AlleleExpressionDb>) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.len()
-            )));
-        }
-        let bind_limit = SQLITE_BIND_LIMIT / 4;
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-                "INSERT OR IGNORE INTO allele_exprs (allele_name, expressing_phenotype_name, expressing_phenotype_wild, dominance) "
-            );
-
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.allele_name)
-                    .push_bind(item.expressing_phenotype_name)
-                    .push_bind(item.expressing_phenotype_wild)
-                    .push_bind(item.dominance);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk Insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            }
-        }
-        Ok(())
+    pub async fn insert_allele_exprs(
+        &self,
+        bulk: Bulk<AlleleExpressionDb>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
     }
-    pub async fn delete_filtered_allele_exprs(
+
+    pub async fn export_filtered_allele_exprs<W: Write>(
         &self,
-        filter: &FilterGroup<AlleleExpressionFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM allele_exprs");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter: &impl FilterQueryBuilder<AlleleExpressionFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<AlleleExpressionDb, _, _>(filter, writer)
+            .await
+    }
 
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete Allele Expression error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+    /// Counts the rows in each table that deleting the filtered allele expressions would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_allele_exprs(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleExpressionFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("allele_exprs", filter).await
+    }
+
+    pub async fn delete_filtered_allele_exprs(
+        &self,
+        filter: &impl FilterQueryBuilder<AlleleExpressionFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("allele_exprs", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
     use std::io::BufReader;
 
-    use crate::interface::bulk::Bulk;
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
     use crate::interface::mock;
     use crate::models::allele_expr::{AlleleExpressionDb, AlleleExpressionFieldName};
     use crate::models::chromosome_name::ChromosomeName;
-    use crate::models::expr_relation::ExpressionRelationFieldName;
     use crate::models::filter::{Filter, FilterGroup, Order};
     use crate::models::{
         allele::Allele, allele_expr::AlleleExpression, gene::Gene, phenotype::Phenotype,
@@ -179,7 +228,7 @@ mod test {
             .get_filtered_allele_exprs(&FilterGroup::<AlleleExpressionFieldName> {
                 filters: vec![vec![(
                     AlleleExpressionFieldName::AlleleName,
-                    Filter::Equal("cn64".to_owned()),
+                    Filter::Equal("cn64".into()),
                 )]],
                 order_by: vec![(AlleleExpressionFieldName::AlleleName, Order::Asc)],
                 limit: None,
@@ -203,7 +252,7 @@ mod test {
                     )],
                     vec![(
                         AlleleExpressionFieldName::ExpressingPhenotypeName,
-                        Filter::Equal("unc-119".to_string()),
+                        Filter::Equal("unc-119".into()),
                     )],
                 ],
                 order_by: vec![(AlleleExpressionFieldName::AlleleName, Order::Asc)],
@@ -386,7 +435,9 @@ cn64,\"dpy-10\",0,0"
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<AlleleExpressionDb> = Bulk::from_reader(&mut reader);
 
-        state.insert_allele_exprs(bulk).await?;
+        state
+            .insert_allele_exprs(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let expected_exprs: Vec<AlleleExpression> = vec![AlleleExpression {
             allele_name: "cn64".to_string(),
@@ -412,11 +463,11 @@ cn64,\"dpy-10\",0,0"
             filters: vec![
                 vec![(
                     AlleleExpressionFieldName::AlleleName,
-                    Filter::Equal("cn64".to_string()),
+                    Filter::Equal("cn64".into()),
                 )],
                 vec![(
                     AlleleExpressionFieldName::ExpressingPhenotypeName,
-                    Filter::Equal("dpy-10".to_string()),
+                    Filter::Equal("dpy-10".into()),
                 )],
                 vec![(
                     AlleleExpressionFieldName::ExpressingPhenotypeWild,
@@ -428,7 +479,9 @@ cn64,\"dpy-10\",0,0"
             offset: None,
         };
 
-        state.delete_filtered_allele_exprs(delete_filter).await?;
+        state
+            .delete_filtered_allele_exprs(delete_filter, DeleteMode::Restrict)
+            .await?;
 
         allele_exprs = state.get_allele_exprs().await?;
         assert_eq!(
@@ -453,7 +506,7 @@ cn64,\"dpy-10\",0,0"
         let filter = &FilterGroup::<AlleleExpressionFieldName> {
             filters: vec![vec![(
                 AlleleExpressionFieldName::AlleleName,
-                Filter::Equal("cn64".to_string()),
+                Filter::Equal("cn64".into()),
             )]],
             order_by: vec![],
             limit: None,
@@ -464,7 +517,9 @@ cn64,\"dpy-10\",0,0"
         let filtered_len = allele_exprs.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_allele_exprs(filter).await?;
+        state
+            .delete_filtered_allele_exprs(filter, DeleteMode::Restrict)
+            .await?;
         allele_exprs = state.get_allele_exprs().await?;
 
         assert_eq!(allele_exprs.len(), orig_len - filtered_len);
@@ -478,13 +533,15 @@ cn64,\"dpy-10\",0,0"
         let state = InnerDbState { conn_pool: pool };
 
         // remove dependencies
+        let preview = state
+            .preview_delete_all(DeletableTable::ExprRelations)
+            .await?;
         state
-            .delete_filtered_expr_relations(&FilterGroup::<ExpressionRelationFieldName> {
-                filters: vec![],
-                order_by: vec![],
-                limit: None,
-                offset: None,
-            })
+            .delete_all(
+                DeletableTable::ExprRelations,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
             .await?;
 
         let mut allele_exprs: Vec<AlleleExpression> = state.get_allele_exprs().await?;
@@ -493,14 +550,16 @@ cn64,\"dpy-10\",0,0"
             mock::allele_expr::get_allele_exprs().len()
         );
 
-        let filter = &FilterGroup::<AlleleExpressionFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_allele_exprs(filter).await?;
+        let preview = state
+            .preview_delete_all(DeletableTable::AlleleExprs)
+            .await?;
+        state
+            .delete_all(
+                DeletableTable::AlleleExprs,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         allele_exprs = state.get_allele_exprs().await?;
 
         assert_eq!(allele_exprs.len(), 0);
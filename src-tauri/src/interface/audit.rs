@@ -0,0 +1,525 @@
+use super::{quote, quote_all, DbError, InnerDbState};
+use crate::models::chromosome_name::ChromosomeName;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Row, Sqlite, Transaction};
+use std::{collections::HashMap, str::FromStr};
+use ts_rs::TS;
+
+/// Repairing creates or deletes rows that may have problems of their own, so it goes
+/// round until nothing is left, giving up after this many rounds
+const MAX_REPAIR_ROUNDS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_AuditProblemKind.ts")]
+#[serde(rename = "db_AuditProblemKind")]
+pub enum AuditProblemKind {
+    /// A foreign key points at a row that doesn't exist
+    MissingParent,
+    /// The genetic location is out of order with the physical location
+    InconsistentLocation,
+    UnknownChromosome,
+}
+
+/// Something wrong with a single row, which is referred to by its primary key as a
+/// json array
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_AuditProblem.ts")]
+#[serde(rename = "db_AuditProblem")]
+pub struct AuditProblem {
+    pub table: String,
+    pub key: String,
+    pub kind: AuditProblemKind,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_AuditRow.ts")]
+#[serde(rename = "db_AuditRow")]
+pub struct AuditRow {
+    pub table: String,
+    pub key: String,
+}
+
+/// How to fix rows with missing parents. Other problems are only ever reported, as
+/// there's no telling which of the values is the right one.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_RepairMode.ts")]
+#[serde(rename = "db_RepairMode")]
+pub enum RepairMode {
+    /// Delete the orphans, along with any rows that are orphaned by that in turn
+    DeleteOrphans,
+    /// Create the missing parents with placeholder values. Orphans whose parent
+    /// can't sensibly be made up (tasks of a missing cross design) are deleted.
+    CreatePlaceholders,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_AuditReport.ts")]
+#[serde(rename = "db_AuditReport")]
+pub struct AuditReport {
+    /// Problems found before any repair
+    pub problems: Vec<AuditProblem>,
+    pub deleted: Vec<AuditRow>,
+    pub created: Vec<AuditRow>,
+}
+
+/// A row reported by `PRAGMA foreign_key_check`, with the columns of the broken key
+struct ForeignKeyViolation {
+    table: String,
+    rowid: i64,
+    parent: String,
+    from: Vec<String>,
+    to: Vec<String>,
+    /// Primary key of the row, as a json array
+    key: String,
+    /// Values of the `from` columns, as a json array
+    values: String,
+}
+
+#[derive(FromRow)]
+struct LocatedRow {
+    table_name: String,
+    key: String,
+    name: String,
+    chromosome: String,
+    phys_loc: i64,
+    gen_loc: f64,
+}
+
+/// Values for the required columns of a placeholder parent that aren't part of the
+/// key, as sql expressions. None if the table shouldn't get placeholders.
+fn get_placeholder_values(table: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    match table {
+        "genes" | "variations" | "alleles" => Some(&[]),
+        "phenotypes" => Some(&[
+            ("short_name", "''"),
+            ("description", "'Placeholder created by database repair'"),
+        ]),
+        "conditions" => Some(&[("description", "'Placeholder created by database repair'")]),
+        "allele_exprs" => Some(&[("dominance", "0")]),
+        "strains" => Some(&[
+            ("genotype", "''"),
+            ("description", "'Placeholder created by database repair'"),
+        ]),
+        _ => None,
+    }
+}
+
+fn audit_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Audit error: {e}");
+    DbError::Audit(e.to_string())
+}
+
+impl InnerDbState {
+    /// Looks for rows with missing parents, unknown chromosomes and genetic locations
+    /// that disagree with the physical ones. With a repair mode, rows with missing
+    /// parents are then fixed in a single transaction.
+    pub async fn audit_database(&self, repair: Option<RepairMode>) -> Result<AuditReport, DbError> {
+        let mut tx = self.conn_pool.begin().await.map_err(audit_error)?;
+        let mut report = AuditReport::default();
+
+        for violation in Self::get_foreign_key_violations(&mut tx)
+            .await
+            .map_err(audit_error)?
+        {
+            report.problems.push(AuditProblem {
+                message: format!(
+                    "{} {} has no matching {} in {}",
+                    violation.from.join(", "),
+                    violation.values,
+                    violation.to.join(", "),
+                    violation.parent
+                ),
+                table: violation.table,
+                key: violation.key,
+                kind: AuditProblemKind::MissingParent,
+            });
+        }
+        report.problems.extend(
+            Self::get_chromosome_problems(&mut tx)
+                .await
+                .map_err(audit_error)?,
+        );
+        report.problems.extend(
+            Self::get_location_problems(&mut tx)
+                .await
+                .map_err(audit_error)?,
+        );
+
+        let mode = match repair {
+            Some(mode) => mode,
+            None => {
+                tx.rollback().await.map_err(audit_error)?;
+                return Ok(report);
+            }
+        };
+        // parents and children are fixed in whatever order they come up, so only
+        // check the keys once the repair is done
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut tx)
+            .await
+            .map_err(audit_error)?;
+        for _ in 0..MAX_REPAIR_ROUNDS {
+            let violations = Self::get_foreign_key_violations(&mut tx)
+                .await
+                .map_err(audit_error)?;
+            if violations.is_empty() {
+                tx.commit().await.map_err(audit_error)?;
+                return Ok(report);
+            }
+            Self::repair_violations(&mut tx, mode, violations, &mut report)
+                .await
+                .map_err(audit_error)?;
+        }
+        tx.rollback().await.map_err(audit_error)?;
+        Err(DbError::Audit(
+            "Rows with missing parents remain after repair".to_owned(),
+        ))
+    }
+
+    async fn get_foreign_key_violations(
+        tx: &mut Transaction<'_, Sqlite>,
+    ) -> Result<Vec<ForeignKeyViolation>, sqlx::Error> {
+        let rows = sqlx::query("PRAGMA foreign_key_check")
+            .fetch_all(&mut *tx)
+            .await?;
+        let mut key_columns: HashMap<(String, i64), (Vec<String>, Vec<String>)> = HashMap::new();
+        let mut violations = vec![];
+        for row in rows {
+            let table: String = row.try_get(0)?;
+            let rowid: i64 = row.try_get(1)?;
+            let parent: String = row.try_get(2)?;
+            let fkid: i64 = row.try_get(3)?;
+
+            let (from, to) = match key_columns.get(&(table.clone(), fkid)) {
+                Some(columns) => columns.clone(),
+                None => {
+                    let columns = Self::get_foreign_key_columns(tx, &table, fkid).await?;
+                    key_columns.insert((table.clone(), fkid), columns.clone());
+                    columns
+                }
+            };
+
+            let described = sqlx::query(&format!(
+                "SELECT json_array({}), json_array({}) FROM {} WHERE rowid = ?",
+                quote_all(&Self::get_primary_key(tx, &table).await?),
+                quote_all(&from),
+                quote(&table),
+            ))
+            .bind(rowid)
+            .fetch_one(&mut *tx)
+            .await?;
+            violations.push(ForeignKeyViolation {
+                key: described.try_get(0)?,
+                values: described.try_get(1)?,
+                table,
+                rowid,
+                parent,
+                from,
+                to,
+            });
+        }
+        Ok(violations)
+    }
+
+    /// The columns of the table's foreign key with given id, and the ones they refer to
+    async fn get_foreign_key_columns(
+        tx: &mut Transaction<'_, Sqlite>,
+        table: &str,
+        fkid: i64,
+    ) -> Result<(Vec<String>, Vec<String>), sqlx::Error> {
+        let columns = sqlx::query(
+            "SELECT \"from\", \"to\" FROM pragma_foreign_key_list(?) WHERE id = ? ORDER BY seq",
+        )
+        .bind(table)
+        .bind(fkid)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut from = vec![];
+        let mut to = vec![];
+        for column in columns {
+            let from_column: String = column.try_get(0)?;
+            // a key that refers to the parent's primary key may leave out its columns
+            let to_column: Option<String> = column.try_get(1)?;
+            to.push(to_column.unwrap_or_else(|| from_column.clone()));
+            from.push(from_column);
+        }
+        Ok((from, to))
+    }
+
+    async fn get_primary_key(
+        tx: &mut Transaction<'_, Sqlite>,
+        table: &str,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query("SELECT name FROM pragma_table_info(?) WHERE pk > 0 ORDER BY pk")
+            .bind(table)
+            .fetch_all(&mut *tx)
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect()
+    }
+
+    async fn get_chromosome_problems(
+        tx: &mut Transaction<'_, Sqlite>,
+    ) -> Result<Vec<AuditProblem>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT 'genes', json_array(systematic_name), chromosome FROM genes
+            WHERE chromosome IS NOT NULL
+            UNION ALL
+            SELECT 'variations', json_array(allele_name), chromosome FROM variations
+            WHERE chromosome IS NOT NULL",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut problems = vec![];
+        for row in rows {
+            let chromosome: String = row.try_get(2)?;
+            if ChromosomeName::from_str(&chromosome).is_err() {
+                problems.push(AuditProblem {
+                    table: row.try_get(0)?,
+                    key: row.try_get(1)?,
+                    kind: AuditProblemKind::UnknownChromosome,
+                    message: format!("{chromosome} is not a known chromosome"),
+                });
+            }
+        }
+        Ok(problems)
+    }
+
+    /// Genetic locations should grow along with physical locations on each chromosome,
+    /// so every row is compared with the one before it in physical order
+    async fn get_location_problems(
+        tx: &mut Transaction<'_, Sqlite>,
+    ) -> Result<Vec<AuditProblem>, sqlx::Error> {
+        let rows: Vec<LocatedRow> = sqlx::query_as(
+            "SELECT 'genes' AS table_name, json_array(systematic_name) AS key,
+                COALESCE(descriptive_name, systematic_name) AS name, chromosome, phys_loc, gen_loc
+            FROM genes
+            WHERE chromosome IS NOT NULL AND phys_loc IS NOT NULL AND gen_loc IS NOT NULL
+            UNION ALL
+            SELECT 'variations', json_array(allele_name), allele_name, chromosome, phys_loc,
+                gen_loc
+            FROM variations
+            WHERE chromosome IS NOT NULL AND phys_loc IS NOT NULL AND gen_loc IS NOT NULL
+            ORDER BY chromosome, phys_loc",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let problems = rows
+            .windows(2)
+            .filter(|pair| pair[0].chromosome == pair[1].chromosome)
+            .filter(|pair| pair[1].gen_loc < pair[0].gen_loc)
+            .map(|pair| AuditProblem {
+                table: pair[1].table_name.clone(),
+                key: pair[1].key.clone(),
+                kind: AuditProblemKind::InconsistentLocation,
+                message: format!(
+                    "Genetic location {} of {} is below {} of {}, which comes before it at physical location {} on chromosome {}",
+                    pair[1].gen_loc,
+                    pair[1].name,
+                    pair[0].gen_loc,
+                    pair[0].name,
+                    pair[0].phys_loc,
+                    pair[1].chromosome
+                ),
+            })
+            .collect();
+        Ok(problems)
+    }
+
+    async fn repair_violations(
+        tx: &mut Transaction<'_, Sqlite>,
+        mode: RepairMode,
+        violations: Vec<ForeignKeyViolation>,
+        report: &mut AuditReport,
+    ) -> Result<(), sqlx::Error> {
+        for violation in violations {
+            let placeholder_values = match mode {
+                RepairMode::CreatePlaceholders => get_placeholder_values(&violation.parent),
+                RepairMode::DeleteOrphans => None,
+            };
+            match placeholder_values {
+                Some(placeholder_values) => {
+                    let mut columns = violation.to.clone();
+                    let mut values: Vec<String> =
+                        violation.from.iter().map(|column| quote(column)).collect();
+                    for (column, value) in placeholder_values {
+                        columns.push(column.to_string());
+                        values.push(value.to_string());
+                    }
+                    let res = sqlx::query(&format!(
+                        "INSERT OR IGNORE INTO {} ({}) SELECT {} FROM {} WHERE rowid = ?",
+                        quote(&violation.parent),
+                        quote_all(&columns),
+                        values.join(", "),
+                        quote(&violation.table),
+                    ))
+                    .bind(violation.rowid)
+                    .execute(&mut *tx)
+                    .await?;
+                    if res.rows_affected() > 0 {
+                        report.created.push(AuditRow {
+                            table: violation.parent,
+                            key: violation.values,
+                        });
+                    }
+                }
+                None => {
+                    let res = sqlx::query(&format!(
+                        "DELETE FROM {} WHERE rowid = ?",
+                        quote(&violation.table)
+                    ))
+                    .bind(violation.rowid)
+                    .execute(&mut *tx)
+                    .await?;
+                    if res.rows_affected() > 0 {
+                        report.deleted.push(AuditRow {
+                            table: violation.table,
+                            key: violation.key,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::{
+        audit::{AuditProblem, AuditProblemKind, AuditRow, RepairMode},
+        InnerDbState,
+    };
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    /// Writes rows the foreign keys would otherwise refuse
+    async fn insert_unchecked(pool: &Pool<Sqlite>, statements: &[&str]) -> Result<()> {
+        let mut conn = pool.acquire().await?;
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut conn)
+            .await?;
+        for statement in statements {
+            sqlx::query(statement).execute(&mut conn).await?;
+        }
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    fn audit_row(table: &str, key: &str) -> AuditRow {
+        AuditRow {
+            table: table.to_owned(),
+            key: key.to_owned(),
+        }
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_audit_database(pool: Pool<Sqlite>) -> Result<()> {
+        insert_unchecked(
+            &pool,
+            &[
+                "INSERT INTO genes (systematic_name, descriptive_name, chromosome, phys_loc, gen_loc)
+                VALUES ('Z1', 'bad-1', 'IV', 9000000, 1.0), ('Z2', NULL, 'VII', NULL, NULL)",
+                "INSERT INTO alleles (name, systematic_gene_name) VALUES ('ghost1', 'NOPE')",
+            ],
+        )
+        .await?;
+        let state = InnerDbState { conn_pool: pool };
+
+        let report = state.audit_database(None).await?;
+        assert_eq!(
+            report.problems,
+            vec![
+                AuditProblem {
+                    table: "alleles".to_owned(),
+                    key: "[\"ghost1\"]".to_owned(),
+                    kind: AuditProblemKind::MissingParent,
+                    message: "systematic_gene_name [\"NOPE\"] has no matching systematic_name in genes"
+                        .to_owned(),
+                },
+                AuditProblem {
+                    table: "genes".to_owned(),
+                    key: "[\"Z2\"]".to_owned(),
+                    kind: AuditProblemKind::UnknownChromosome,
+                    message: "VII is not a known chromosome".to_owned(),
+                },
+                AuditProblem {
+                    table: "genes".to_owned(),
+                    key: "[\"Z1\"]".to_owned(),
+                    kind: AuditProblemKind::InconsistentLocation,
+                    message: "Genetic location 1 of bad-1 is below 3.51 of unc-24, which comes before it at physical location 7979870 on chromosome IV"
+                        .to_owned(),
+                },
+            ]
+        );
+        assert!(report.deleted.is_empty() && report.created.is_empty());
+        // nothing is changed without a repair mode
+        assert_eq!(state.audit_database(None).await?, report);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_repair_delete_orphans(pool: Pool<Sqlite>) -> Result<()> {
+        insert_unchecked(
+            &pool,
+            &[
+                "INSERT INTO alleles (name, systematic_gene_name) VALUES ('ghost1', 'NOPE')",
+                "INSERT INTO strains (name, genotype) VALUES ('S1', 'ghost1/+')",
+                "INSERT INTO strain_alleles (strain_name, allele_name, is_on_top, is_on_bot)
+                VALUES ('S1', 'ghost1', 1, 0)",
+            ],
+        )
+        .await?;
+        let state = InnerDbState { conn_pool: pool };
+
+        let report = state
+            .audit_database(Some(RepairMode::DeleteOrphans))
+            .await?;
+        assert_eq!(report.problems.len(), 1);
+        assert_eq!(
+            report.deleted,
+            vec![
+                audit_row("alleles", "[\"ghost1\"]"),
+                audit_row("strain_alleles", "[\"S1\",\"ghost1\"]"),
+            ]
+        );
+        assert_eq!(report.created, vec![]);
+        assert_eq!(state.audit_database(None).await?.problems, vec![]);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_repair_create_placeholders(pool: Pool<Sqlite>) -> Result<()> {
+        insert_unchecked(
+            &pool,
+            &[
+                "INSERT INTO alleles (name, systematic_gene_name) VALUES ('ghost1', 'NOPE')",
+                "INSERT INTO strain_alleles (strain_name, allele_name, is_on_top, is_on_bot)
+                VALUES ('S2', 'ghost2', 1, 0)",
+            ],
+        )
+        .await?;
+        let state = InnerDbState { conn_pool: pool };
+
+        let report = state
+            .audit_database(Some(RepairMode::CreatePlaceholders))
+            .await?;
+        assert_eq!(report.problems.len(), 3);
+        assert_eq!(
+            report.created,
+            vec![
+                audit_row("genes", "[\"NOPE\"]"),
+                audit_row("alleles", "[\"ghost2\"]"),
+                audit_row("strains", "[\"S2\"]"),
+            ]
+        );
+        assert_eq!(report.deleted, vec![]);
+        assert_eq!(state.audit_database(None).await?.problems, vec![]);
+        Ok(())
+    }
+}
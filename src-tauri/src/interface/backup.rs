@@ -0,0 +1,369 @@
+use super::{DbError, InnerDbState};
+use anyhow::Result;
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions, Connection, Row};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use ts_rs::TS;
+
+/// Automatic snapshots beyond this many are deleted, oldest first
+const MAX_SNAPSHOTS: usize = 20;
+/// Snapshots are kept in this directory next to the database, named after it
+const SNAPSHOT_DIR: &str = "snapshots";
+const SNAPSHOT_EXTENSION: &str = ".sqlite";
+const SNAPSHOT_TIME_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+/// On launch, a snapshot is only taken if the newest one is older than this, so
+/// restarting the app doesn't rotate out the older ones
+const STARTUP_SNAPSHOT_INTERVAL_HOURS: i64 = 24;
+
+/// A copy of the database taken at a point in time
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_Snapshot.ts")]
+#[serde(rename = "db_Snapshot")]
+pub struct Snapshot {
+    pub name: String,
+    /// Local time the snapshot was taken, as "YYYY-MM-DD HH:MM:SS"
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    /// Size in bytes
+    #[ts(type = "number")]
+    pub size: u64,
+}
+
+impl Snapshot {
+    /// Returns None if the file isn't a snapshot of the database named `database_name`
+    fn from_path(path: &Path, database_name: &str) -> Option<Snapshot> {
+        let name = path.file_name()?.to_str()?;
+        let time = name
+            .strip_prefix(database_name)?
+            .strip_prefix('-')?
+            .strip_suffix(SNAPSHOT_EXTENSION)?;
+        let created_at = NaiveDateTime::parse_from_str(time, SNAPSHOT_TIME_FORMAT).ok()?;
+        Some(Snapshot {
+            name: name.to_owned(),
+            created_at: created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            size: fs::metadata(path).ok()?.len(),
+        })
+    }
+}
+
+fn backup_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Backup error: {e}");
+    DbError::Backup(e.to_string())
+}
+
+impl InnerDbState {
+    /// Writes a consistent copy of the database to given path, replacing any file there
+    pub async fn backup_to(&self, path: &Path) -> Result<(), DbError> {
+        let live_path = self.get_database_path().await?;
+        if live_path.is_some() && path.canonicalize().ok() == live_path {
+            return Err(DbError::Backup(
+                "Cannot back up the database onto itself".to_owned(),
+            ));
+        }
+
+        // VACUUM INTO refuses to overwrite, so write next to the target and move it over
+        let tmp_path = path.with_extension("sqlite-tmp");
+        if tmp_path.exists() {
+            fs::remove_file(&tmp_path).map_err(backup_error)?;
+        }
+        sqlx::query("VACUUM INTO ?")
+            .bind(tmp_path.to_string_lossy().to_string())
+            .execute(&self.conn_pool)
+            .await
+            .map_err(backup_error)?;
+        fs::rename(&tmp_path, path).map_err(backup_error)
+    }
+
+    /// Backs up the database into its snapshot directory and prunes old snapshots
+    pub async fn create_snapshot(&self) -> Result<Snapshot, DbError> {
+        let (snapshot_dir, database_name) = self.get_snapshot_location().await?;
+        fs::create_dir_all(&snapshot_dir).map_err(backup_error)?;
+        let path = snapshot_dir.join(format!(
+            "{database_name}-{}{SNAPSHOT_EXTENSION}",
+            Local::now().format(SNAPSHOT_TIME_FORMAT)
+        ));
+        self.backup_to(&path).await?;
+
+        for old_snapshot in self.list_snapshots().await?.iter().skip(MAX_SNAPSHOTS) {
+            fs::remove_file(snapshot_dir.join(&old_snapshot.name)).map_err(backup_error)?;
+        }
+        Snapshot::from_path(&path, &database_name)
+            .ok_or_else(|| DbError::Backup("Snapshot was not written".to_owned()))
+    }
+
+    /// Takes a snapshot unless the newest one is recent, returning it if one was taken
+    pub async fn create_startup_snapshot(&self) -> Result<Option<Snapshot>, DbError> {
+        if let Some(newest) = self.list_snapshots().await?.first() {
+            let created_at = NaiveDateTime::parse_from_str(&newest.created_at, "%Y-%m-%d %H:%M:%S")
+                .map_err(backup_error)?;
+            let age = Local::now().naive_local() - created_at;
+            if age < chrono::Duration::hours(STARTUP_SNAPSHOT_INTERVAL_HOURS) {
+                return Ok(None);
+            }
+        }
+        self.create_snapshot().await.map(Some)
+    }
+
+    /// Snapshots of the open database, newest first
+    pub async fn list_snapshots(&self) -> Result<Vec<Snapshot>, DbError> {
+        let (snapshot_dir, database_name) = self.get_snapshot_location().await?;
+        if !snapshot_dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut snapshots: Vec<Snapshot> = fs::read_dir(&snapshot_dir)
+            .map_err(backup_error)?
+            .filter_map(|entry| Snapshot::from_path(&entry.ok()?.path(), &database_name))
+            .collect();
+        // names sort chronologically
+        snapshots.sort_by(|a, b| b.name.cmp(&a.name));
+        Ok(snapshots)
+    }
+
+    /// Replaces the open database with one of its snapshots and reconnects to it.
+    /// The current database is snapshotted first, so a restore can itself be undone.
+    pub async fn restore_snapshot(&mut self, name: &str) -> Result<(), DbError> {
+        let database_path = self
+            .get_database_path()
+            .await?
+            .ok_or_else(|| DbError::Backup("The database is not stored in a file".to_owned()))?;
+        let (snapshot_dir, _) = self.get_snapshot_location().await?;
+        let snapshot = match self
+            .list_snapshots()
+            .await?
+            .into_iter()
+            .find(|snapshot| snapshot.name == name)
+        {
+            Some(snapshot) => snapshot_dir.join(snapshot.name),
+            None => return Err(DbError::Backup(format!("Snapshot {name} does not exist"))),
+        };
+        Self::check_integrity(&snapshot).await?;
+        // taking a snapshot can prune the one being restored, so restore from a copy
+        let restore_path = snapshot.with_extension("sqlite-restore");
+        fs::copy(&snapshot, &restore_path).map_err(backup_error)?;
+        if let Err(e) = self.create_snapshot().await {
+            fs::remove_file(&restore_path).map_err(backup_error)?;
+            return Err(e);
+        }
+
+        let replaced = match self.close_pool(&database_path).await {
+            true => {
+                Self::replace_database_file(&restore_path, &database_path).map_err(backup_error)
+            }
+            false => Err(DbError::Backup("The database is still in use".to_owned())),
+        };
+        fs::remove_file(&restore_path).map_err(backup_error)?;
+        // reconnect even if the file couldn't be replaced, so the app keeps working
+        self.conn_pool = Self::connect(&database_path).await.map_err(backup_error)?;
+        replaced
+    }
+
+    /// The snapshot directory of the open database, and the name its snapshots start with
+    async fn get_snapshot_location(&self) -> Result<(PathBuf, String), DbError> {
+        let database_path = self
+            .get_database_path()
+            .await?
+            .ok_or_else(|| DbError::Backup("The database is not stored in a file".to_owned()))?;
+        let database_name = database_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let snapshot_dir = database_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(SNAPSHOT_DIR);
+        Ok((snapshot_dir, database_name))
+    }
+
+    async fn check_integrity(path: &Path) -> Result<(), DbError> {
+        let mut conn = SqliteConnectOptions::new()
+            .filename(path)
+            .read_only(true)
+            .connect()
+            .await
+            .map_err(backup_error)?;
+        let result: String = sqlx::query("PRAGMA quick_check")
+            .fetch_one(&mut conn)
+            .await
+            .and_then(|row| row.try_get(0))
+            .map_err(backup_error)?;
+        conn.close().await.map_err(backup_error)?;
+        match result.as_str() {
+            "ok" => Ok(()),
+            _ => Err(DbError::Backup(format!("Snapshot is corrupted: {result}"))),
+        }
+    }
+
+    /// Connections still on their way back to the pool when it's closed only get closed
+    /// by a later call, and the last one to close checkpoints and removes the write-ahead
+    /// log. Returns false if that doesn't happen within 5 seconds.
+    async fn close_pool(&self, database_path: &Path) -> bool {
+        let wal_path = Self::get_journal_path(database_path, "-wal");
+        for _ in 0..100 {
+            self.conn_pool.close().await;
+            if self.conn_pool.size() == 0 && !wal_path.exists() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        false
+    }
+
+    fn replace_database_file(snapshot: &Path, database_path: &Path) -> std::io::Result<()> {
+        let shm_path = Self::get_journal_path(database_path, "-shm");
+        if shm_path.exists() {
+            fs::remove_file(shm_path)?;
+        }
+        fs::copy(snapshot, database_path).map(|_| ())
+    }
+
+    fn get_journal_path(database_path: &Path, suffix: &str) -> PathBuf {
+        let mut journal_path = database_path.as_os_str().to_owned();
+        journal_path.push(suffix);
+        PathBuf::from(journal_path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::backup::{MAX_SNAPSHOTS, SNAPSHOT_DIR};
+    use crate::interface::{cascade::DeleteMode, DbError, InnerDbState};
+    use crate::models::{
+        filter::{Filter, FilterGroup},
+        gene::{Gene, GeneFieldName},
+    };
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use std::{fs, path::PathBuf, time::Duration};
+
+    fn get_test_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("ww-backup-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn get_gene() -> Gene {
+        Gene {
+            systematic_name: "T14B4.7".to_string(),
+            descriptive_name: Some("dpy-10".to_string()),
+            chromosome: None,
+            phys_loc: Some(6710149),
+            gen_loc: Some(0.0),
+            recomb_suppressor: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() -> Result<()> {
+        let dir = get_test_dir();
+        fs::create_dir_all(&dir)?;
+        let database_path = dir.join("worm.sqlite");
+        let mut state = InnerDbState {
+            conn_pool: InnerDbState::connect(&database_path).await?,
+        };
+
+        state.insert_gene(&get_gene()).await?;
+        let snapshot = state.create_snapshot().await?;
+        state
+            .delete_filtered_genes(
+                &FilterGroup::<GeneFieldName> {
+                    filters: vec![vec![(
+                        GeneFieldName::SysName,
+                        Filter::Equal("T14B4.7".into()),
+                    )]],
+                    order_by: vec![],
+                    limit: None,
+                    offset: None,
+                },
+                DeleteMode::Restrict,
+            )
+            .await?;
+        assert_eq!(state.get_genes().await?, vec![]);
+
+        state.restore_snapshot(&snapshot.name).await?;
+        assert_eq!(state.get_genes().await?, vec![get_gene()]);
+
+        let snapshots = state.list_snapshots().await?;
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1], snapshot);
+
+        state.conn_pool.close().await;
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_oldest_snapshot_when_full() -> Result<()> {
+        let dir = get_test_dir();
+        fs::create_dir_all(&dir)?;
+        let database_path = dir.join("worm.sqlite");
+        let mut state = InnerDbState {
+            conn_pool: InnerDbState::connect(&database_path).await?,
+        };
+
+        state.insert_gene(&get_gene()).await?;
+        let oldest = state.create_snapshot().await?;
+        sqlx::query("DELETE FROM genes")
+            .execute(&state.conn_pool)
+            .await?;
+        for _ in 1..MAX_SNAPSHOTS {
+            // snapshot names are only unique to the millisecond
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            state.create_snapshot().await?;
+        }
+        assert_eq!(state.list_snapshots().await?.len(), MAX_SNAPSHOTS);
+
+        // the snapshot taken before restoring prunes the one being restored
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        state.restore_snapshot(&oldest.name).await?;
+        assert_eq!(state.get_genes().await?, vec![get_gene()]);
+        let snapshots = state.list_snapshots().await?;
+        assert_eq!(snapshots.len(), MAX_SNAPSHOTS);
+        assert!(!snapshots.contains(&oldest));
+        assert_eq!(fs::read_dir(dir.join(SNAPSHOT_DIR))?.count(), MAX_SNAPSHOTS);
+
+        state.conn_pool.close().await;
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_startup_snapshot_skipped_when_recent() -> Result<()> {
+        let dir = get_test_dir();
+        fs::create_dir_all(&dir)?;
+        let database_path = dir.join("worm.sqlite");
+        let state = InnerDbState {
+            conn_pool: InnerDbState::connect(&database_path).await?,
+        };
+
+        let snapshot = state.create_startup_snapshot().await?;
+        assert!(snapshot.is_some());
+        assert_eq!(state.create_startup_snapshot().await?, None);
+        assert_eq!(state.list_snapshots().await?, vec![snapshot.unwrap()]);
+
+        state.conn_pool.close().await;
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_missing_snapshot() -> Result<()> {
+        let dir = get_test_dir();
+        fs::create_dir_all(&dir)?;
+        let database_path = dir.join("worm.sqlite");
+        let mut state = InnerDbState {
+            conn_pool: InnerDbState::connect(&database_path).await?,
+        };
+
+        let res = state.restore_snapshot("../worm.sqlite").await;
+        assert!(matches!(res, Err(DbError::Backup(_))));
+        let res = state.backup_to(&database_path).await;
+        assert!(matches!(res, Err(DbError::Backup(_))));
+
+        state.conn_pool.close().await;
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,14 +1,105 @@
+use super::{DbError, InnerDbState, TableRow, SQLITE_BIND_LIMIT};
+use crate::models::{filter::FilterQueryBuilder, FieldNameEnum};
 use anyhow::Result;
-use csv::Reader;
-use serde::de::DeserializeOwned;
-use std::path::Path;
+use csv::{ErrorKind, Reader, StringRecord, Writer};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{sqlite::SqliteRow, Connection, FromRow, QueryBuilder, Row, Sqlite, Transaction};
+use std::{collections::HashMap, fs::File, io::Write, path::Path};
+use ts_rs::TS;
+
+/// A line of an import that could not be read or inserted
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_RejectedRow.ts")]
+#[serde(rename = "db_RejectedRow")]
+pub struct RejectedRow {
+    pub line: usize,
+    pub column: Option<String>,
+    pub reason: String,
+}
+
+/// What to do with an imported row whose primary key already exists
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_ConflictPolicy.ts")]
+#[serde(rename = "db_ConflictPolicy")]
+pub enum ConflictPolicy {
+    /// Keep the existing row
+    Ignore,
+    /// Replace every field of the existing row
+    Overwrite,
+    /// Only fill in fields of the existing row that are null
+    OverwriteNullFields,
+    /// Reject the row
+    Fail,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_FieldChange.ts")]
+#[serde(rename = "db_FieldChange")]
+pub struct FieldChange {
+    pub column: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// An existing row that was changed by an import
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_RowDiff.ts")]
+#[serde(rename = "db_RowDiff")]
+pub struct RowDiff {
+    pub line: usize,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Outcome of a bulk import, with rows referred to by their line in the file
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_BulkReport.ts")]
+#[serde(rename = "db_BulkReport")]
+pub struct BulkReport {
+    pub inserted: Vec<usize>,
+    pub updated: Vec<RowDiff>,
+    /// Rows whose primary key already exists, and that didn't change anything
+    pub skipped: Vec<usize>,
+    pub rejected: Vec<RejectedRow>,
+    /// False for dry runs and for imports with rejected rows, which are rolled back
+    pub committed: bool,
+}
+
+/// File format of an export
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_ExportFormat.ts")]
+#[serde(rename = "db_ExportFormat")]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+}
+
+impl ExportFormat {
+    /// Creates (or truncates) the file at given path
+    pub fn writer(self, path: &Path) -> Result<Writer<File>> {
+        Ok(csv::WriterBuilder::new()
+            .delimiter(match self {
+                ExportFormat::Csv => b',',
+                ExportFormat::Tsv => b'\t',
+            })
+            .from_path(path)?)
+    }
+}
+
+/// A row type that can be bulk inserted into its table
+pub trait BulkInsertable: TableRow + DeserializeOwned {
+    /// Values in the order of `COLUMNS`, as SQLite stores them (booleans as 0 or 1)
+    fn values(&self) -> Vec<Value>;
+}
 
 pub struct Bulk<T>
 where
     T: DeserializeOwned,
 {
     pub data: Vec<T>,
-    pub errors: Vec<(usize, String)>,
+    /// Line in the file of each row of data
+    pub lines: Vec<usize>,
+    pub errors: Vec<RejectedRow>,
 }
 impl<T: DeserializeOwned> Bulk<T> {
     /// Reads in data from csv/tsv at given path
@@ -29,14 +120,434 @@ impl<T: DeserializeOwned> Bulk<T> {
 
     pub fn from_reader<K: std::io::Read>(reader: &mut Reader<K>) -> Self {
         let mut data: Vec<T> = vec![];
-        let mut errors: Vec<(usize, String)> = vec![];
-        for (i, rec) in reader.deserialize::<T>().enumerate() {
-            match rec {
-                Ok(val) => data.push(val),
-                Err(e) => errors.push((i, e.to_string())),
+        let mut lines: Vec<usize> = vec![];
+        let mut errors: Vec<RejectedRow> = vec![];
+
+        let headers = match reader.has_headers() {
+            true => reader.headers().ok().cloned(),
+            false => None,
+        };
+        let mut record = StringRecord::new();
+        loop {
+            match reader.read_record(&mut record) {
+                Ok(false) => break,
+                Ok(true) => {
+                    let line = record.position().map_or(0, |pos| pos.line() as usize);
+                    match record.deserialize::<T>(headers.as_ref()) {
+                        Ok(val) => {
+                            data.push(val);
+                            lines.push(line);
+                        }
+                        Err(e) => errors.push(Self::rejected_row(line, &e, headers.as_ref())),
+                    }
+                }
+                Err(e) => {
+                    let line = e.position().map_or(0, |pos| pos.line() as usize);
+                    let is_io = matches!(e.kind(), ErrorKind::Io(_));
+                    errors.push(Self::rejected_row(line, &e, headers.as_ref()));
+                    if is_io {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Self {
+            data,
+            lines,
+            errors,
+        }
+    }
+
+    fn rejected_row(line: usize, e: &csv::Error, headers: Option<&StringRecord>) -> RejectedRow {
+        match e.kind() {
+            ErrorKind::Deserialize { err, .. } => RejectedRow {
+                line,
+                column: err.field().map(|field| match headers {
+                    Some(headers) => headers
+                        .get(field as usize)
+                        .map_or(field.to_string(), |name| name.to_owned()),
+                    None => field.to_string(),
+                }),
+                reason: err.kind().to_string(),
+            },
+            _ => RejectedRow {
+                line,
+                column: None,
+                reason: e.to_string(),
+            },
+        }
+    }
+}
+
+/// What happens to a single row of an import
+enum RowOutcome {
+    Insert,
+    Update(Vec<FieldChange>),
+    Skip,
+    Reject(String),
+}
+
+/// A row ready to be imported, with its values and key in the stored representation
+struct ImportRow {
+    line: usize,
+    key: String,
+    values: Vec<Value>,
+}
+
+/// Tracks the stored values of every imported key, as they change during the import
+struct ImportState {
+    policy: ConflictPolicy,
+    columns: &'static [&'static str],
+    stored: HashMap<String, Vec<Value>>,
+}
+
+impl ImportState {
+    fn plan(&self, row: &ImportRow, pending: &HashMap<String, Vec<Value>>) -> RowOutcome {
+        let old_values = match pending.get(&row.key).or_else(|| self.stored.get(&row.key)) {
+            Some(old_values) => old_values,
+            None => return RowOutcome::Insert,
+        };
+        let changes: Vec<FieldChange> = old_values
+            .iter()
+            .zip(row.values.iter())
+            .zip(self.columns.iter())
+            .filter(|((old, new), _)| match self.policy {
+                ConflictPolicy::OverwriteNullFields => old.is_null() && !new.is_null(),
+                _ => old != new,
+            })
+            .map(|((old, new), column)| FieldChange {
+                column: column.to_string(),
+                old: value_to_string(old),
+                new: value_to_string(new),
+            })
+            .collect();
+        match self.policy {
+            ConflictPolicy::Ignore => RowOutcome::Skip,
+            ConflictPolicy::Fail => {
+                RowOutcome::Reject(format!("A row with key {} already exists", row.key))
+            }
+            _ if changes.is_empty() => RowOutcome::Skip,
+            _ => RowOutcome::Update(changes),
+        }
+    }
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn push_value(qb: &mut QueryBuilder<'_, Sqlite>, value: &Value) {
+    match value {
+        Value::Null => qb.push_bind(None::<String>),
+        Value::Bool(v) => qb.push_bind(*v),
+        Value::Number(n) => match n.as_i64() {
+            Some(v) => qb.push_bind(v),
+            None => qb.push_bind(n.as_f64()),
+        },
+        Value::String(v) => qb.push_bind(v.clone()),
+        other => qb.push_bind(other.to_string()),
+    };
+}
+
+impl InnerDbState {
+    /// Imports every row in a single transaction, which is only committed if all rows
+    /// could be read and imported and this isn't a dry run
+    pub async fn insert_bulk<T: BulkInsertable>(
+        &self,
+        bulk: Bulk<T>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        let mut tx = match self.conn_pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => {
+                eprint!("Bulk insert error: {e}");
+                return Err(DbError::BulkInsert(e.to_string()));
+            }
+        };
+        let mut report = Self::import_bulk(&mut tx, bulk, policy).await?;
+
+        report.committed = !dry_run && report.rejected.is_empty();
+        let res = match report.committed {
+            true => tx.commit().await,
+            false => tx.rollback().await,
+        };
+        match res {
+            Ok(_) => Ok(report),
+            Err(e) => {
+                eprint!("Bulk insert error: {e}");
+                Err(DbError::BulkInsert(e.to_string()))
+            }
+        }
+    }
+
+    /// Imports every row within given transaction, leaving it to the caller to commit.
+    /// Rows that fail to import are undone on their own and reported as rejected.
+    pub(super) async fn import_bulk<T: BulkInsertable>(
+        tx: &mut Transaction<'_, Sqlite>,
+        bulk: Bulk<T>,
+        policy: ConflictPolicy,
+    ) -> Result<BulkReport, DbError> {
+        let mut report = BulkReport {
+            rejected: bulk.errors,
+            ..Default::default()
+        };
+        let key_idxs: Vec<usize> = T::KEY_COLUMNS
+            .iter()
+            .filter_map(|key| T::COLUMNS.iter().position(|col| col == key))
+            .collect();
+        let rows: Vec<ImportRow> = bulk
+            .lines
+            .into_iter()
+            .zip(bulk.data)
+            .map(|(line, item)| {
+                let values = item.values();
+                let key = Value::Array(key_idxs.iter().map(|&idx| values[idx].clone()).collect());
+                ImportRow {
+                    line,
+                    key: key.to_string(),
+                    values,
+                }
+            })
+            .collect();
+
+        let stored = match Self::get_stored_values::<T>(tx, &rows).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                eprint!("Bulk insert error: {e}");
+                return Err(DbError::BulkInsert(e.to_string()));
+            }
+        };
+        let mut state = ImportState {
+            policy,
+            columns: T::COLUMNS,
+            stored,
+        };
+
+        let bind_limit = SQLITE_BIND_LIMIT / T::COLUMNS.len();
+        for chunk in rows.chunks(bind_limit - 1) {
+            if Self::import_chunk::<T>(tx, &mut state, chunk, &mut report)
+                .await
+                .is_err()
+            {
+                // the failed statement is undone on its own, so retry one row at a time
+                // to find out which rows were at fault
+                for row in chunk {
+                    let single = std::slice::from_ref(row);
+                    if let Err(e) =
+                        Self::import_chunk::<T>(tx, &mut state, single, &mut report).await
+                    {
+                        report.rejected.push(RejectedRow {
+                            line: row.line,
+                            column: None,
+                            reason: e.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        report.rejected.sort_by_key(|row| row.line);
+        Ok(report)
+    }
+
+    /// Fetches the current values of every row that shares a key with an imported row
+    async fn get_stored_values<T: BulkInsertable>(
+        tx: &mut Transaction<'_, Sqlite>,
+        rows: &[ImportRow],
+    ) -> Result<HashMap<String, Vec<Value>>, sqlx::Error> {
+        let keys = format!(
+            "[{}]",
+            rows.iter()
+                .map(|row| row.key.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let key_columns = T::KEY_COLUMNS.join(", ");
+        let stored_rows = sqlx::query(&format!(
+            "SELECT json_array({key_columns}) AS key, json_array({}) AS vals FROM {}
+            WHERE json_array({key_columns}) IN (SELECT value FROM json_each(?))",
+            T::COLUMNS.join(", "),
+            T::TABLE,
+        ))
+        .bind(keys)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let parse = |json: String| {
+            serde_json::from_str::<Value>(&json).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+        };
+        let mut stored = HashMap::new();
+        for stored_row in stored_rows {
+            let key = parse(stored_row.try_get("key")?)?.to_string();
+            let values = match parse(stored_row.try_get("vals")?)? {
+                Value::Array(values) => values,
+                _ => vec![],
+            };
+            stored.insert(key, values);
+        }
+        Ok(stored)
+    }
+
+    /// Writes the rows of a chunk that should be inserted or updated, and records the
+    /// outcome of every row if that succeeds
+    async fn import_chunk<T: BulkInsertable>(
+        tx: &mut Transaction<'_, Sqlite>,
+        state: &mut ImportState,
+        chunk: &[ImportRow],
+        report: &mut BulkReport,
+    ) -> Result<(), sqlx::Error> {
+        let mut pending: HashMap<String, Vec<Value>> = HashMap::new();
+        let mut outcomes: Vec<(&ImportRow, RowOutcome)> = vec![];
+        for row in chunk {
+            let outcome = state.plan(row, &pending);
+            if matches!(outcome, RowOutcome::Insert | RowOutcome::Update(_)) {
+                let mut new_values = row.values.clone();
+                if state.policy == ConflictPolicy::OverwriteNullFields {
+                    if let Some(old_values) =
+                        pending.get(&row.key).or_else(|| state.stored.get(&row.key))
+                    {
+                        new_values = old_values
+                            .iter()
+                            .zip(row.values.iter())
+                            .map(|(old, new)| match old.is_null() {
+                                true => new.clone(),
+                                false => old.clone(),
+                            })
+                            .collect();
+                    }
+                }
+                pending.insert(row.key.clone(), new_values);
+            }
+            outcomes.push((row, outcome));
+        }
+
+        // a savepoint, so a failed chunk leaves nothing behind
+        let mut savepoint = tx.begin().await?;
+        let inserted: Vec<&ImportRow> = outcomes
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, RowOutcome::Insert))
+            .map(|(row, _)| *row)
+            .collect();
+        if !inserted.is_empty() {
+            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+                "INSERT INTO {} ({}) VALUES ",
+                T::TABLE,
+                T::COLUMNS.join(", ")
+            ));
+            for (row_idx, row) in inserted.iter().enumerate() {
+                qb.push(if row_idx == 0 { "(" } else { ", (" });
+                for (idx, value) in row.values.iter().enumerate() {
+                    if idx > 0 {
+                        qb.push(", ");
+                    }
+                    push_value(&mut qb, value);
+                }
+                qb.push(")");
+            }
+            qb.build().execute(&mut *savepoint).await?;
+        }
+
+        // updates write the final values of each key, which already account for
+        // every row of the chunk. Keys are compared with IS since they may contain nulls.
+        let mut updated_keys: Vec<&String> = vec![];
+        for (row, outcome) in outcomes.iter() {
+            if matches!(outcome, RowOutcome::Update(_)) && !updated_keys.contains(&&row.key) {
+                updated_keys.push(&row.key);
+            }
+        }
+        for key in updated_keys {
+            let values = &pending[key];
+            let mut qb: QueryBuilder<Sqlite> =
+                QueryBuilder::new(format!("UPDATE {} SET ", T::TABLE));
+            let mut is_first = true;
+            for (col, value) in T::COLUMNS.iter().zip(values.iter()) {
+                if T::KEY_COLUMNS.contains(col) {
+                    continue;
+                }
+                qb.push(format!("{}{col} = ", if is_first { "" } else { ", " }));
+                push_value(&mut qb, value);
+                is_first = false;
+            }
+            let mut is_first = true;
+            for (col, value) in T::COLUMNS.iter().zip(values.iter()) {
+                if !T::KEY_COLUMNS.contains(col) {
+                    continue;
+                }
+                qb.push(format!(
+                    " {} {col} IS ",
+                    if is_first { "WHERE" } else { "AND" }
+                ));
+                push_value(&mut qb, value);
+                is_first = false;
             }
+            qb.build().execute(&mut *savepoint).await?;
         }
+        savepoint.commit().await?;
 
-        Self { data, errors }
+        state.stored.extend(pending);
+        for (row, outcome) in outcomes {
+            match outcome {
+                RowOutcome::Insert => report.inserted.push(row.line),
+                RowOutcome::Update(changes) => report.updated.push(RowDiff {
+                    line: row.line,
+                    changes,
+                }),
+                RowOutcome::Skip => report.skipped.push(row.line),
+                RowOutcome::Reject(reason) => report.rejected.push(RejectedRow {
+                    line: row.line,
+                    column: None,
+                    reason,
+                }),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl InnerDbState {
+    /// Writes every row matching the filter, ignoring its limit and offset, with the
+    /// same headers `Bulk` reads so the file can be imported again as is.
+    /// Returns the number of rows written.
+    pub async fn export_bulk<T, F, W>(
+        &self,
+        filter: &impl FilterQueryBuilder<F>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError>
+    where
+        T: BulkInsertable + Serialize + for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+        F: FieldNameEnum,
+        W: Write,
+    {
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+            "SELECT {} FROM {}",
+            T::COLUMNS.join(", "),
+            T::TABLE
+        ));
+        filter.add_filtered_query(&mut qb, true, false)?;
+
+        let rows = match qb.build_query_as::<T>().fetch_all(&self.conn_pool).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                eprint!("Export error: {e}");
+                return Err(DbError::Query(e.to_string()));
+            }
+        };
+
+        let written = rows
+            .iter()
+            .try_for_each(|row| writer.serialize(row))
+            .and_then(|_| Ok(writer.flush()?));
+        match written {
+            Ok(_) => Ok(rows.len()),
+            Err(e) => {
+                eprint!("Export error: {e}");
+                Err(DbError::Export(e.to_string()))
+            }
+        }
     }
 }
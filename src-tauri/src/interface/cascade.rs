@@ -0,0 +1,650 @@
+use super::{quote, quote_all, DbError, InnerDbState};
+use crate::models::{filter::FilterQueryBuilder, FieldNameEnum};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Row, Sqlite, Transaction};
+use std::collections::{hash_map::DefaultHasher, BTreeSet};
+use std::hash::{Hash, Hasher};
+use ts_rs::TS;
+
+/// What happens to rows that refer to deleted rows
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_DeleteMode.ts")]
+#[serde(rename = "db_DeleteMode")]
+pub enum DeleteMode {
+    /// Refuse to delete anything if other rows refer to the deleted ones
+    Restrict,
+    /// Delete the referring rows too, and the rows referring to those
+    Cascade,
+    /// Set the referring columns to null, which fails for required columns
+    SetNull,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_DependentRows.ts")]
+#[serde(rename = "db_DependentRows")]
+pub struct DependentRows {
+    pub table: String,
+    pub rows: u32,
+}
+
+/// Rows matched by a delete's filter, and rows in other tables that depend on them
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_DeleteReport.ts")]
+#[serde(rename = "db_DeleteReport")]
+pub struct DeleteReport {
+    pub deleted: u32,
+    pub dependents: Vec<DependentRows>,
+}
+
+/// A foreign key of `table` that refers to `parent`
+#[derive(Clone)]
+struct ForeignKey {
+    table: String,
+    parent: String,
+    from: Vec<String>,
+    to: Vec<String>,
+}
+
+/// The rows a delete touches, by rowid. The first entry holds the rows matched by the
+/// filter, the others the rows that depend on them.
+struct AffectedRows {
+    tables: Vec<(String, BTreeSet<i64>)>,
+    /// Foreign keys pointing straight at the matched rows, with the rows they belong to
+    direct: Vec<(ForeignKey, BTreeSet<i64>)>,
+}
+
+impl AffectedRows {
+    fn get_report(&self) -> DeleteReport {
+        DeleteReport {
+            deleted: self.tables[0].1.len() as u32,
+            dependents: self.tables[1..]
+                .iter()
+                .map(|(table, rowids)| DependentRows {
+                    table: table.clone(),
+                    rows: rowids.len() as u32,
+                })
+                .collect(),
+        }
+    }
+}
+
+fn to_json(rowids: &BTreeSet<i64>) -> String {
+    serde_json::to_string(rowids).unwrap_or_default()
+}
+
+fn select_rowids(table: &str) -> QueryBuilder<'_, Sqlite> {
+    QueryBuilder::new(format!("SELECT rowid FROM {}", quote(table)))
+}
+
+fn delete_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Delete error: {e}");
+    DbError::Delete(e.to_string())
+}
+
+/// Tables that can be emptied with a single delete
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_DeletableTable.ts")]
+#[serde(rename = "db_DeletableTable")]
+pub enum DeletableTable {
+    Alleles,
+    AlleleExprs,
+    Conditions,
+    ExprRelations,
+    Genes,
+    Phenotypes,
+    Strains,
+    StrainAlleles,
+    TaskConditions,
+    TaskDependencies,
+    Variations,
+}
+
+impl DeletableTable {
+    pub fn get_table_name(&self) -> &'static str {
+        match self {
+            DeletableTable::Alleles => "alleles",
+            DeletableTable::AlleleExprs => "allele_exprs",
+            DeletableTable::Conditions => "conditions",
+            DeletableTable::ExprRelations => "expr_relations",
+            DeletableTable::Genes => "genes",
+            DeletableTable::Phenotypes => "phenotypes",
+            DeletableTable::Strains => "strains",
+            DeletableTable::StrainAlleles => "strain_alleles",
+            DeletableTable::TaskConditions => "task_conditions",
+            DeletableTable::TaskDependencies => "task_dependencies",
+            DeletableTable::Variations => "variations",
+        }
+    }
+}
+
+/// What deleting every row of a table would remove, and the confirmation that has
+/// to be passed back to actually delete it
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_DeleteAllPreview.ts")]
+#[serde(rename = "db_DeleteAllPreview")]
+pub struct DeleteAllPreview {
+    pub report: DeleteReport,
+    pub confirmation: String,
+}
+
+impl InnerDbState {
+    /// An opaque confirmation hashed from the rows deleting every row of the table would
+    /// remove and the latest journal entry, so it has to come from a preview and goes
+    /// stale as soon as anything changes
+    async fn get_confirmation(
+        tx: &mut Transaction<'_, Sqlite>,
+        table: &str,
+        affected: &AffectedRows,
+    ) -> Result<String, sqlx::Error> {
+        let journal_head: i64 = sqlx::query("SELECT COALESCE(MAX(id), 0) FROM journal_entries")
+            .fetch_one(&mut *tx)
+            .await?
+            .try_get(0)?;
+        let mut hasher = DefaultHasher::new();
+        (table, &affected.tables, journal_head).hash(&mut hasher);
+        Ok(format!("{:016x}", hasher.finish()))
+    }
+
+    /// Counts the rows a cascading delete would remove, without deleting anything
+    pub(super) async fn preview_delete<T: FieldNameEnum>(
+        &self,
+        table: &str,
+        filter: &impl FilterQueryBuilder<T>,
+    ) -> Result<DeleteReport, DbError> {
+        let mut tx = self.conn_pool.begin().await.map_err(delete_error)?;
+        let mut qb = select_rowids(table);
+        filter.add_filtered_query(&mut qb, true, false)?;
+        let matched = Self::get_matched_rows(&mut tx, qb)
+            .await
+            .map_err(delete_error)?;
+        let affected = Self::get_affected_rows(&mut tx, table, matched)
+            .await
+            .map_err(delete_error)?;
+        tx.rollback().await.map_err(delete_error)?;
+        Ok(affected.get_report())
+    }
+
+    /// Deletes the rows matching the filter, dealing with the rows that refer to them
+    /// according to `mode`. Either everything is deleted or nothing is. A filter
+    /// without any conditions is rejected rather than taken to match every row, use
+    /// [`InnerDbState::delete_all`] for that.
+    pub(super) async fn delete_filtered<T: FieldNameEnum>(
+        &self,
+        table: &str,
+        filter: &impl FilterQueryBuilder<T>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        if !filter.has_conditions() {
+            return Err(DbError::UnfilteredDelete(format!(
+                "the filter for {table} has no conditions"
+            )));
+        }
+        let mut tx = self.conn_pool.begin().await.map_err(delete_error)?;
+        let mut qb = select_rowids(table);
+        filter.add_filtered_query(&mut qb, true, false)?;
+        let matched = Self::get_matched_rows(&mut tx, qb)
+            .await
+            .map_err(delete_error)?;
+        let affected = Self::get_affected_rows(&mut tx, table, matched)
+            .await
+            .map_err(delete_error)?;
+        let report = Self::delete_affected_rows(&mut tx, affected, mode).await?;
+        tx.commit().await.map_err(delete_error)?;
+        Ok(report)
+    }
+
+    /// Counts the rows deleting every row of the table would remove, along with the
+    /// confirmation [`InnerDbState::delete_all`] expects
+    pub async fn preview_delete_all(
+        &self,
+        table: DeletableTable,
+    ) -> Result<DeleteAllPreview, DbError> {
+        let table = table.get_table_name();
+        let mut tx = self.conn_pool.begin().await.map_err(delete_error)?;
+        let matched = Self::get_matched_rows(&mut tx, select_rowids(table))
+            .await
+            .map_err(delete_error)?;
+        let affected = Self::get_affected_rows(&mut tx, table, matched)
+            .await
+            .map_err(delete_error)?;
+        let confirmation = Self::get_confirmation(&mut tx, table, &affected)
+            .await
+            .map_err(delete_error)?;
+        tx.rollback().await.map_err(delete_error)?;
+
+        Ok(DeleteAllPreview {
+            report: affected.get_report(),
+            confirmation,
+        })
+    }
+
+    /// Deletes every row of the table, if `confirmation` matches the one from the
+    /// latest preview
+    pub async fn delete_all(
+        &self,
+        table: DeletableTable,
+        mode: DeleteMode,
+        confirmation: &str,
+    ) -> Result<DeleteReport, DbError> {
+        let table = table.get_table_name();
+        let mut tx = self.conn_pool.begin().await.map_err(delete_error)?;
+        let matched = Self::get_matched_rows(&mut tx, select_rowids(table))
+            .await
+            .map_err(delete_error)?;
+        let affected = Self::get_affected_rows(&mut tx, table, matched)
+            .await
+            .map_err(delete_error)?;
+        let expected = Self::get_confirmation(&mut tx, table, &affected)
+            .await
+            .map_err(delete_error)?;
+        if confirmation != expected {
+            return Err(DbError::UnfilteredDelete(format!(
+                "the confirmation for {table} is missing or out of date"
+            )));
+        }
+        let report = Self::delete_affected_rows(&mut tx, affected, mode).await?;
+        tx.commit().await.map_err(delete_error)?;
+        Ok(report)
+    }
+
+    async fn delete_affected_rows(
+        tx: &mut Transaction<'_, Sqlite>,
+        affected: AffectedRows,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        let mut report = affected.get_report();
+        let deleted = match mode {
+            DeleteMode::Restrict => {
+                if let Some(dependent) = report.dependents.first() {
+                    return Err(DbError::Delete(format!(
+                        "{} rows in {} depend on the rows to delete",
+                        dependent.rows, dependent.table
+                    )));
+                }
+                &affected.tables[..1]
+            }
+            DeleteMode::Cascade => &affected.tables[..],
+            DeleteMode::SetNull => {
+                report.dependents = vec![];
+                for (key, rowids) in affected.direct.iter() {
+                    Self::set_null(tx, key, rowids)
+                        .await
+                        .map_err(delete_error)?;
+                    report.dependents.push(DependentRows {
+                        table: key.table.clone(),
+                        rows: rowids.len() as u32,
+                    });
+                }
+                &affected.tables[..1]
+            }
+        };
+
+        // rows are deleted parents first, so only check the keys at the end
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await
+            .map_err(delete_error)?;
+        for (table, rowids) in deleted {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE rowid IN (SELECT value FROM json_each(?))",
+                quote(table)
+            ))
+            .bind(to_json(rowids))
+            .execute(&mut *tx)
+            .await
+            .map_err(delete_error)?;
+        }
+        Ok(report)
+    }
+
+    async fn get_matched_rows(
+        tx: &mut Transaction<'_, Sqlite>,
+        mut qb: QueryBuilder<'_, Sqlite>,
+    ) -> Result<BTreeSet<i64>, sqlx::Error> {
+        qb.build()
+            .fetch_all(&mut *tx)
+            .await?
+            .iter()
+            .map(|row| row.try_get(0))
+            .collect()
+    }
+
+    /// Follows the matched rows to every row that refers to them, directly or
+    /// through other rows
+    async fn get_affected_rows(
+        tx: &mut Transaction<'_, Sqlite>,
+        table: &str,
+        matched: BTreeSet<i64>,
+    ) -> Result<AffectedRows, sqlx::Error> {
+        let mut affected = AffectedRows {
+            tables: vec![(table.to_owned(), matched.clone())],
+            direct: vec![],
+        };
+        let mut pending = vec![(table.to_owned(), matched, true)];
+        while let Some((parent, parent_rowids, is_matched)) = pending.pop() {
+            if parent_rowids.is_empty() {
+                continue;
+            }
+            for key in Self::get_referring_keys(tx, &parent).await? {
+                let rowids: BTreeSet<i64> = sqlx::query(&format!(
+                    "SELECT rowid FROM {} WHERE ({}) IN
+                    (SELECT {} FROM {} WHERE rowid IN (SELECT value FROM json_each(?)))",
+                    quote(&key.table),
+                    quote_all(&key.from),
+                    quote_all(&key.to),
+                    quote(&key.parent),
+                ))
+                .bind(to_json(&parent_rowids))
+                .fetch_all(&mut *tx)
+                .await?
+                .iter()
+                .map(|row| row.try_get(0))
+                .collect::<Result<_, _>>()?;
+                if rowids.is_empty() {
+                    continue;
+                }
+
+                // rows already known to be affected aren't followed again, so cycles end
+                let new_rowids: BTreeSet<i64> =
+                    match affected.tables.iter_mut().find(|(t, _)| *t == key.table) {
+                        Some((_, known)) => {
+                            let new_rowids: BTreeSet<i64> =
+                                rowids.difference(known).copied().collect();
+                            known.extend(new_rowids.iter().copied());
+                            new_rowids
+                        }
+                        None => {
+                            affected.tables.push((key.table.clone(), rowids.clone()));
+                            rowids.clone()
+                        }
+                    };
+                if is_matched {
+                    affected.direct.push((key.clone(), rowids));
+                }
+                pending.push((key.table, new_rowids, false));
+            }
+        }
+        Ok(affected)
+    }
+
+    /// Foreign keys in any table that refer to given table
+    async fn get_referring_keys(
+        tx: &mut Transaction<'_, Sqlite>,
+        parent: &str,
+    ) -> Result<Vec<ForeignKey>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT m.name, f.id, f.\"from\", f.\"to\"
+            FROM sqlite_master AS m JOIN pragma_foreign_key_list(m.name) AS f
+            WHERE m.type = 'table' AND f.\"table\" = ?
+            ORDER BY m.name, f.id, f.seq",
+        )
+        .bind(parent)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut keys: Vec<(i64, ForeignKey)> = vec![];
+        for row in rows {
+            let table: String = row.try_get(0)?;
+            let id: i64 = row.try_get(1)?;
+            let from: String = row.try_get(2)?;
+            // a key that refers to the parent's primary key may leave out its columns
+            let to: String = row.try_get::<Option<String>, _>(3)?.unwrap_or(from.clone());
+            match keys.last_mut() {
+                Some((last_id, key)) if *last_id == id && key.table == table => {
+                    key.from.push(from);
+                    key.to.push(to);
+                }
+                _ => keys.push((
+                    id,
+                    ForeignKey {
+                        table,
+                        parent: parent.to_owned(),
+                        from: vec![from],
+                        to: vec![to],
+                    },
+                )),
+            }
+        }
+        Ok(keys.into_iter().map(|(_, key)| key).collect())
+    }
+
+    async fn set_null(
+        tx: &mut Transaction<'_, Sqlite>,
+        key: &ForeignKey,
+        rowids: &BTreeSet<i64>,
+    ) -> Result<(), sqlx::Error> {
+        let columns = key
+            .from
+            .iter()
+            .map(|column| format!("{} = NULL", quote(column)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sqlx::query(&format!(
+            "UPDATE {} SET {columns} WHERE rowid IN (SELECT value FROM json_each(?))",
+            quote(&key.table)
+        ))
+        .bind(to_json(rowids))
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::{
+        cascade::{DeletableTable, DeleteMode, DeleteReport, DependentRows},
+        DbError, InnerDbState,
+    };
+    use crate::models::{
+        allele::{Allele, AlleleFieldName},
+        filter::{Filter, FilterGroup},
+        gene::GeneFieldName,
+        phenotype::PhenotypeFieldName,
+    };
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    fn get_unc_119_filter() -> FilterGroup<GeneFieldName> {
+        FilterGroup::<GeneFieldName> {
+            filters: vec![vec![(
+                GeneFieldName::DescName,
+                Filter::Equal("unc-119".into()),
+            )]],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        }
+    }
+
+    fn get_ed3_filter() -> FilterGroup<AlleleFieldName> {
+        FilterGroup::<AlleleFieldName> {
+            filters: vec![vec![(AlleleFieldName::Name, Filter::Equal("ed3".into()))]],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        }
+    }
+
+    fn dependent(table: &str, rows: u32) -> DependentRows {
+        DependentRows {
+            table: table.to_owned(),
+            rows,
+        }
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_preview_delete_genes(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let gene_count = state.get_genes().await?.len();
+
+        let preview = state.preview_delete_genes(&get_unc_119_filter()).await?;
+        assert_eq!(
+            preview,
+            DeleteReport {
+                deleted: 1,
+                dependents: vec![
+                    dependent("alleles", 1),
+                    dependent("allele_exprs", 1),
+                    dependent("strain_alleles", 2),
+                ],
+            }
+        );
+        assert_eq!(state.get_genes().await?.len(), gene_count);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_genes_restrict(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let gene_count = state.get_genes().await?.len();
+
+        let res = state
+            .delete_filtered_genes(&get_unc_119_filter(), DeleteMode::Restrict)
+            .await;
+        assert!(matches!(res, Err(DbError::Delete(_))));
+        assert_eq!(state.get_genes().await?.len(), gene_count);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_genes_cascade(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let gene_count = state.get_genes().await?.len();
+        let preview = state.preview_delete_genes(&get_unc_119_filter()).await?;
+
+        let report = state
+            .delete_filtered_genes(&get_unc_119_filter(), DeleteMode::Cascade)
+            .await?;
+        assert_eq!(report, preview);
+        assert_eq!(state.get_genes().await?.len(), gene_count - 1);
+        assert_eq!(state.get_filtered_alleles(&get_ed3_filter()).await?, vec![]);
+        assert_eq!(state.audit_database(None).await?.problems, vec![]);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_genes_set_null(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let report = state
+            .delete_filtered_genes(&get_unc_119_filter(), DeleteMode::SetNull)
+            .await?;
+        assert_eq!(
+            report,
+            DeleteReport {
+                deleted: 1,
+                dependents: vec![dependent("alleles", 1)],
+            }
+        );
+        assert_eq!(
+            state.get_filtered_alleles(&get_ed3_filter()).await?,
+            vec![Allele {
+                name: "ed3".to_string(),
+                contents: None,
+                systematic_gene_name: None,
+                variation_name: None,
+            }]
+        );
+        assert_eq!(state.audit_database(None).await?.problems, vec![]);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_phenotypes_set_null_required(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = FilterGroup::<PhenotypeFieldName> {
+            filters: vec![vec![(
+                PhenotypeFieldName::Name,
+                Filter::Equal("unc-119".into()),
+            )]],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+        let phenotype_count = state.get_phenotypes().await?.len();
+
+        // allele expressions can't do without their phenotype
+        let res = state
+            .delete_filtered_phenotypes(&filter, DeleteMode::SetNull)
+            .await;
+        assert!(matches!(res, Err(DbError::Delete(_))));
+        assert_eq!(state.get_phenotypes().await?.len(), phenotype_count);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_empty_filter(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let allele_count = state.get_alleles().await?.len();
+
+        let res = state
+            .delete_filtered_alleles(
+                &FilterGroup::<AlleleFieldName> {
+                    filters: vec![],
+                    order_by: vec![],
+                    limit: None,
+                    offset: None,
+                },
+                DeleteMode::Cascade,
+            )
+            .await;
+        assert!(matches!(res, Err(DbError::UnfilteredDelete(_))));
+        assert_eq!(state.get_alleles().await?.len(), allele_count);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_all_confirmation(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let gene_count = state.get_genes().await?.len();
+
+        let preview = state.preview_delete_all(DeletableTable::Genes).await?;
+        assert_eq!(preview.report.deleted as usize, gene_count);
+        let res = state
+            .delete_all(DeletableTable::Genes, DeleteMode::Cascade, "")
+            .await;
+        assert!(matches!(res, Err(DbError::UnfilteredDelete(_))));
+        // the confirmation can't be made up without a preview
+        let res = state
+            .delete_all(
+                DeletableTable::Genes,
+                DeleteMode::Cascade,
+                &format!("delete all {gene_count} genes"),
+            )
+            .await;
+        assert!(matches!(res, Err(DbError::UnfilteredDelete(_))));
+        assert_eq!(
+            state.preview_delete_all(DeletableTable::Genes).await?,
+            preview
+        );
+
+        // deleting a gene makes the earlier confirmation stale
+        state
+            .delete_filtered_genes(&get_unc_119_filter(), DeleteMode::Cascade)
+            .await?;
+        let res = state
+            .delete_all(
+                DeletableTable::Genes,
+                DeleteMode::Cascade,
+                &preview.confirmation,
+            )
+            .await;
+        assert!(matches!(res, Err(DbError::UnfilteredDelete(_))));
+        assert_eq!(state.get_genes().await?.len(), gene_count - 1);
+
+        let preview = state.preview_delete_all(DeletableTable::Genes).await?;
+        let report = state
+            .delete_all(
+                DeletableTable::Genes,
+                DeleteMode::Cascade,
+                &preview.confirmation,
+            )
+            .await?;
+        assert_eq!(report, preview.report);
+        assert_eq!(state.get_genes().await?.len(), 0);
+        assert_eq!(state.audit_database(None).await?.problems, vec![]);
+        Ok(())
+    }
+}
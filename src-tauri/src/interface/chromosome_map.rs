@@ -0,0 +1,357 @@
+use super::{DbError, InnerDbState};
+use crate::genetics::genetic_map::locate;
+use crate::models::{
+    allele::{AlleleFieldName, AlleleJoinFieldName},
+    chromosome_name::ChromosomeName,
+    filter::{Filter, FilterGroup, Order},
+    gene::GeneFieldName,
+    variation::VariationFieldName,
+};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::HashMap};
+use ts_rs::TS;
+
+/// Which gene or variation a locus is
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, TS)]
+#[ts(export, export_to = "../src/models/db/db_LocusKey.ts")]
+#[serde(rename = "db_LocusKey")]
+pub enum LocusKey {
+    /// Systematic name
+    Gene(String),
+    /// Allele name
+    Variation(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_MapLocus.ts")]
+#[serde(rename = "db_MapLocus")]
+pub struct MapLocus {
+    pub key: LocusKey,
+    /// The gene's descriptive name if it has one
+    pub name: String,
+    #[serde(rename = "physLoc")]
+    pub phys_loc: Option<i32>,
+    #[serde(rename = "geneticLoc")]
+    pub gen_loc: f64,
+    /// Whether gen_loc was estimated from phys_loc, for lack of a measured one
+    pub estimated: bool,
+    /// cM from the locus before it on the map, None for the first
+    pub distance: Option<f64>,
+    pub alleles: Vec<String>,
+}
+
+/// Where a gene or variation, such as a balancer, keeps the chromosome from recombining
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_SuppressedRegion.ts")]
+#[serde(rename = "db_SuppressedRegion")]
+pub struct SuppressedRegion {
+    pub key: LocusKey,
+    pub name: String,
+    #[serde(rename = "physStart")]
+    pub phys_start: i32,
+    #[serde(rename = "physEnd")]
+    pub phys_end: i32,
+    /// From the first to the last mapped locus inside the region, if there are any
+    #[serde(rename = "geneticSpan")]
+    pub gen_span: Option<(f64, f64)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_ChromosomeMap.ts")]
+#[serde(rename = "db_ChromosomeMap")]
+pub struct ChromosomeMap {
+    pub chromosome: ChromosomeName,
+    /// Genes and variations with a genetic location, or a physical location to estimate
+    /// one from, in order along the chromosome
+    pub loci: Vec<MapLocus>,
+    #[serde(rename = "suppressedRegions")]
+    pub suppressed_regions: Vec<SuppressedRegion>,
+}
+
+/// What the map needs of a gene or a variation
+struct LocusRow {
+    key: LocusKey,
+    name: String,
+    phys_loc: Option<i32>,
+    gen_loc: Option<f64>,
+    recomb_suppressor: Option<(i32, i32)>,
+}
+
+fn get_filter<T>(field_name: T, chromosome: &ChromosomeName) -> FilterGroup<T>
+where
+    T: ts_rs::TS + std::cmp::Eq + std::hash::Hash,
+{
+    FilterGroup::<T> {
+        filters: vec![vec![(
+            field_name,
+            Filter::Equal(chromosome.to_string().into()),
+        )]],
+        order_by: vec![],
+        limit: None,
+        offset: None,
+    }
+}
+
+impl InnerDbState {
+    /// Lays out the mapped genes and variations on a chromosome with their alleles, and
+    /// where recombination is suppressed. With a range, only the loci between those
+    /// genetic locations (inclusive) and the regions that could overlap them are kept.
+    pub async fn get_chromosome_map(
+        &self,
+        chromosome: ChromosomeName,
+        range: Option<(f64, f64)>,
+    ) -> Result<ChromosomeMap, DbError> {
+        let genes = self
+            .get_filtered_genes(&get_filter(GeneFieldName::Chromosome, &chromosome))
+            .await?;
+        let variations = self
+            .get_filtered_variations(&get_filter(VariationFieldName::Chromosome, &chromosome))
+            .await?;
+        let rows: Vec<LocusRow> = genes
+            .into_iter()
+            .map(|gene| LocusRow {
+                name: gene
+                    .descriptive_name
+                    .unwrap_or_else(|| gene.systematic_name.clone()),
+                key: LocusKey::Gene(gene.systematic_name),
+                phys_loc: gene.phys_loc,
+                gen_loc: gene.gen_loc,
+                recomb_suppressor: gene.recomb_suppressor,
+            })
+            .chain(variations.into_iter().map(|variation| LocusRow {
+                name: variation.allele_name.clone(),
+                key: LocusKey::Variation(variation.allele_name),
+                phys_loc: variation.phys_loc,
+                gen_loc: variation.gen_loc,
+                recomb_suppressor: variation.recomb_suppressor,
+            }))
+            .collect();
+
+        let mut alleles: HashMap<LocusKey, Vec<String>> = HashMap::new();
+        let allele_filter = FilterGroup::<AlleleJoinFieldName> {
+            filters: vec![vec![
+                (
+                    AlleleJoinFieldName::Gene(GeneFieldName::Chromosome),
+                    Filter::Equal(chromosome.to_string().into()),
+                ),
+                (
+                    AlleleJoinFieldName::Variation(VariationFieldName::Chromosome),
+                    Filter::Equal(chromosome.to_string().into()),
+                ),
+            ]],
+            order_by: vec![(
+                AlleleJoinFieldName::Allele(AlleleFieldName::Name),
+                Order::Asc,
+            )],
+            limit: None,
+            offset: None,
+        };
+        for allele in self.get_filtered_joined_alleles(&allele_filter).await? {
+            let key = match (allele.systematic_gene_name, allele.variation_name) {
+                (Some(sys_name), _) => LocusKey::Gene(sys_name),
+                (None, Some(variation_name)) => LocusKey::Variation(variation_name),
+                (None, None) => continue,
+            };
+            alleles.entry(key).or_default().push(allele.name);
+        }
+
+        // the same genetic map as for crosses
+        let genetic_map = self.get_genetic_maps().await?.remove(&chromosome);
+        let mut mapped: Vec<(&LocusRow, f64, bool)> = rows
+            .iter()
+            .filter_map(|row| {
+                let (gen_loc, estimated) = locate(row.gen_loc, row.phys_loc, genetic_map.as_ref())?;
+                Some((row, gen_loc, estimated))
+            })
+            .collect();
+        mapped.sort_by(|(row1, gen_loc1, _), (row2, gen_loc2, _)| {
+            gen_loc1
+                .partial_cmp(gen_loc2)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| row1.name.cmp(&row2.name))
+        });
+
+        let in_range = |gen_loc: f64| match range {
+            Some((start, end)) => start <= gen_loc && gen_loc <= end,
+            None => true,
+        };
+        let mut loci: Vec<MapLocus> = vec![];
+        for (row, gen_loc, estimated) in mapped.iter().filter(|(_, gen_loc, _)| in_range(*gen_loc))
+        {
+            loci.push(MapLocus {
+                key: row.key.clone(),
+                name: row.name.clone(),
+                phys_loc: row.phys_loc,
+                gen_loc: *gen_loc,
+                estimated: *estimated,
+                distance: loci.last().map(|prev| gen_loc - prev.gen_loc),
+                alleles: alleles.remove(&row.key).unwrap_or_default(),
+            });
+        }
+
+        let mut suppressed_regions: Vec<SuppressedRegion> = rows
+            .iter()
+            .filter_map(|row| {
+                let (phys_start, phys_end) = row.recomb_suppressor?;
+                let inside: Vec<f64> = mapped
+                    .iter()
+                    .filter(|(locus, _, _)| {
+                        locus.phys_loc.map_or(false, |phys_loc| {
+                            phys_start <= phys_loc && phys_loc <= phys_end
+                        })
+                    })
+                    .map(|(_, gen_loc, _)| *gen_loc)
+                    .collect();
+                // mapped is in order, so the span runs from the first to the last
+                let gen_span = inside.first().zip(inside.last()).map(|(s, e)| (*s, *e));
+                Some(SuppressedRegion {
+                    key: row.key.clone(),
+                    name: row.name.clone(),
+                    phys_start,
+                    phys_end,
+                    gen_span,
+                })
+            })
+            .filter(|region| match (range, region.gen_span) {
+                (Some((start, end)), Some((span_start, span_end))) => {
+                    span_start <= end && start <= span_end
+                }
+                _ => true,
+            })
+            .collect();
+        suppressed_regions.sort_by(|region1, region2| {
+            (region1.phys_start, &region1.name).cmp(&(region2.phys_start, &region2.name))
+        });
+
+        Ok(ChromosomeMap {
+            chromosome,
+            loci,
+            suppressed_regions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::chromosome_map::{LocusKey, MapLocus, SuppressedRegion};
+    use crate::models::chromosome_name::ChromosomeName;
+    use crate::InnerDbState;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    fn get_names(loci: &[MapLocus]) -> Vec<&str> {
+        loci.iter().map(|locus| locus.name.as_str()).collect()
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_chromosome_map(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let map = state.get_chromosome_map(ChromosomeName::Iv, None).await?;
+        assert_eq!(
+            get_names(&map.loci),
+            vec!["unc-24", "fbl-1", "kin-4", "dpy-20"]
+        );
+        assert_eq!(map.loci[0].key, LocusKey::Gene("F57H12.2".to_string()));
+        assert_eq!(map.loci[0].alleles, vec!["e138"]);
+        assert_eq!(map.loci[0].distance, None);
+        let distances: Vec<f64> = map.loci[1..]
+            .iter()
+            .map(|locus| locus.distance.unwrap())
+            .collect();
+        for (distance, expected) in distances.iter().zip([0.79, 0.68, 0.24]) {
+            assert!((distance - expected).abs() < 1e-9);
+        }
+
+        // both balancers cover every mapped gene on IV
+        assert_eq!(
+            map.suppressed_regions,
+            vec![
+                SuppressedRegion {
+                    key: LocusKey::Variation("tmC5".to_string()),
+                    name: "tmC5".to_string(),
+                    phys_start: 6600000,
+                    phys_end: 12500000,
+                    gen_span: Some((3.51, 5.22)),
+                },
+                SuppressedRegion {
+                    key: LocusKey::Variation("tmC5[F36H1.3(tmIs1220)]".to_string()),
+                    name: "tmC5[F36H1.3(tmIs1220)]".to_string(),
+                    phys_start: 6600000,
+                    phys_end: 12500000,
+                    gen_span: Some((3.51, 5.22)),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_chromosome_map_estimates_gen_loc(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        sqlx::query("UPDATE genes SET gen_loc = NULL WHERE descriptive_name = 'fbl-1'")
+            .execute(&state.conn_pool)
+            .await?;
+
+        let map = state.get_chromosome_map(ChromosomeName::Iv, None).await?;
+        assert_eq!(
+            get_names(&map.loci),
+            vec!["unc-24", "fbl-1", "kin-4", "dpy-20"]
+        );
+        assert_eq!(
+            map.loci
+                .iter()
+                .map(|locus| locus.estimated)
+                .collect::<Vec<_>>(),
+            vec![false, true, false, false]
+        );
+        let expected = 3.51 + (9540806.0 - 7979870.0) * (4.98 - 3.51) / (11425742.0 - 7979870.0);
+        assert!((map.loci[1].gen_loc - expected).abs() < 1e-9);
+        assert!((map.loci[2].distance.unwrap() - (4.98 - expected)).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_chromosome_map_with_variations(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let map = state.get_chromosome_map(ChromosomeName::Ii, None).await?;
+        assert_eq!(get_names(&map.loci), vec!["oxTi75", "dpy-10", "oxSi1168"]);
+        assert_eq!(map.loci[1].alleles, vec!["cn64", "e128"]);
+        assert_eq!(map.loci[2].key, LocusKey::Variation("oxSi1168".to_string()));
+
+        let map = state
+            .get_chromosome_map(ChromosomeName::Ii, Some((0.0, 1.0)))
+            .await?;
+        assert_eq!(get_names(&map.loci), vec!["dpy-10", "oxSi1168"]);
+        assert_eq!(map.loci[0].distance, None);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_chromosome_map_suppressed_regions(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let map = state.get_chromosome_map(ChromosomeName::Iii, None).await?;
+        assert_eq!(get_names(&map.loci), vec!["unc-119"]);
+        assert_eq!(map.suppressed_regions.len(), 1);
+        assert_eq!(map.suppressed_regions[0].gen_span, Some((5.59, 5.59)));
+
+        // out of range of everything mapped on III
+        let map = state
+            .get_chromosome_map(ChromosomeName::Iii, Some((10.0, 20.0)))
+            .await?;
+        assert_eq!(map.loci, vec![]);
+        assert_eq!(map.suppressed_regions, vec![]);
+
+        // nothing is mapped on V, so there's no telling where eT1 is
+        let map = state
+            .get_chromosome_map(ChromosomeName::V, Some((10.0, 20.0)))
+            .await?;
+        assert_eq!(map.loci, vec![]);
+        assert_eq!(map.suppressed_regions.len(), 1);
+        assert_eq!(map.suppressed_regions[0].gen_span, None);
+        Ok(())
+    }
+}
@@ -1,11 +1,49 @@
-use super::{bulk::Bulk, DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
     condition::{Condition, ConditionDb, ConditionFieldName},
     expr_relation::ExpressionRelationFieldName,
-    filter::{Count, FilterGroup, FilterQueryBuilder},
+    filter::{Count, FilterQueryBuilder},
 };
 use anyhow::Result;
+use csv::Writer;
+use serde_json::{json, Value};
 use sqlx::{QueryBuilder, Sqlite};
+use std::io::Write;
+
+impl TableRow for ConditionDb {
+    const TABLE: &'static str = "conditions";
+    const COLUMNS: &'static [&'static str] = &[
+        "name",
+        "description",
+        "male_mating",
+        "lethal",
+        "female_sterile",
+        "arrested",
+        "maturation_days",
+    ];
+    const KEY_COLUMNS: &'static [&'static str] = &["name"];
+}
+
+impl BulkInsertable for ConditionDb {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.name),
+            json!(self.description),
+            json!(self.male_mating),
+            json!(self.lethal),
+            json!(self.female_sterile),
+            json!(self.arrested),
+            json!(self.maturation_days),
+        ]
+    }
+}
 
 impl InnerDbState {
     pub async fn get_conditions(&self) -> Result<Vec<Condition>, DbError> {
@@ -40,7 +78,7 @@ impl InnerDbState {
 
     pub async fn get_filtered_conditions(
         &self,
-        filter: &FilterGroup<ConditionFieldName>,
+        filter: &impl FilterQueryBuilder<ConditionFieldName>,
     ) -> Result<Vec<Condition>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT
@@ -53,7 +91,7 @@ impl InnerDbState {
             maturation_days
             FROM conditions",
         );
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<ConditionDb>()
@@ -68,13 +106,45 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_conditions_page(
+        &self,
+        filter: &impl FilterQueryBuilder<ConditionFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<Condition>, DbError> {
+        Ok(self
+            .get_page::<ConditionDb, _>(filter, page)
+            .await?
+            .map(Condition::from))
+    }
+
+    pub async fn query_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<ConditionFieldName>,
+        facets: Vec<ConditionFieldName>,
+    ) -> Result<QueryResult<Condition, ConditionFieldName>, DbError> {
+        Ok(self
+            .query::<ConditionDb, _>(filter, facets)
+            .await?
+            .map(Condition::from))
+    }
+
+    pub async fn aggregate_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<ConditionFieldName>,
+        group_by: &ConditionFieldName,
+        metrics: &[Metric<ConditionFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("conditions", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_conditions(
         &self,
-        filter: &FilterGroup<ConditionFieldName>,
+        filter: &impl FilterQueryBuilder<ConditionFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT COUNT(*) as count FROM conditions");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -91,8 +161,8 @@ impl InnerDbState {
 
     pub async fn get_altering_conditions(
         &self,
-        expr_relation_filter: &FilterGroup<ExpressionRelationFieldName>,
-        condition_filter: &FilterGroup<ConditionFieldName>,
+        expr_relation_filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
+        condition_filter: &impl FilterQueryBuilder<ConditionFieldName>,
     ) -> Result<Vec<Condition>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT DISTINCT
@@ -110,12 +180,12 @@ impl InnerDbState {
               FROM
                 expr_relations",
         );
-        expr_relation_filter.add_filtered_query(&mut qb, true, true);
+        expr_relation_filter.add_filtered_query(&mut qb, true, true)?;
         qb.push(
             ") JOIN conditions AS c 
             ON c.name == ac\n",
         );
-        condition_filter.add_filtered_query(&mut qb, true, true);
+        condition_filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<ConditionDb>()
@@ -154,77 +224,52 @@ impl InnerDbState {
         }
     }
 
-    pub async fn insert_conditions(&self, bulk: Bulk<ConditionDb>) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.len()
-            )));
-        }
-        // 7 is the number of fields here
-        let bind_limit = SQLITE_BIND_LIMIT / 7;
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-            "INSERT OR IGNORE INTO conditions (name, description, male_mating, lethal, female_sterile, arrested, maturation_days) "
-        );
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
+    pub async fn insert_conditions(
+        &self,
+        bulk: Bulk<ConditionDb>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
+    }
 
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
+    pub async fn export_filtered_conditions<W: Write>(
+        &self,
+        filter: &impl FilterQueryBuilder<ConditionFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<ConditionDb, _, _>(filter, writer).await
+    }
 
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.name)
-                    .push_bind(item.description)
-                    .push_bind(item.male_mating)
-                    .push_bind(item.lethal)
-                    .push_bind(item.female_sterile)
-                    .push_bind(item.arrested)
-                    .push_bind(item.maturation_days);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk Insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            }
-        }
-        Ok(())
+    /// Counts the rows in each table that deleting the filtered conditions would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<ConditionFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("conditions", filter).await
     }
 
     pub async fn delete_filtered_conditions(
         &self,
-        filter: &FilterGroup<ConditionFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM conditions");
-        filter.add_filtered_query(&mut qb, true, false);
-
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete Condition error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+        filter: &impl FilterQueryBuilder<ConditionFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("conditions", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
     use std::io::BufReader;
 
-    use crate::interface::bulk::Bulk;
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
     use crate::interface::mock;
     use crate::models::condition::{Condition, ConditionDb, ConditionFieldName};
     use crate::models::expr_relation::ExpressionRelationFieldName;
-    use crate::models::filter::{Filter, FilterGroup, Order};
+    use crate::models::filter::{Filter, FilterGroup, FilterValue, Order};
     use crate::InnerDbState;
     use anyhow::Result;
     use pretty_assertions::assert_eq;
@@ -247,7 +292,7 @@ mod test {
             .get_filtered_conditions(&FilterGroup::<ConditionFieldName> {
                 filters: vec![vec![(
                     ConditionFieldName::MaturationDays,
-                    Filter::LessThan("4".to_owned(), false),
+                    Filter::LessThan(FilterValue::Real(4.0), false),
                 )]],
                 order_by: vec![(ConditionFieldName::Name, Order::Asc)],
                 limit: None,
@@ -266,7 +311,7 @@ mod test {
             .get_filtered_conditions(&FilterGroup::<ConditionFieldName> {
                 filters: vec![vec![(
                     ConditionFieldName::MaturationDays,
-                    Filter::LessThan("4".to_owned(), false),
+                    Filter::LessThan(FilterValue::Real(4.0), false),
                 )]],
                 order_by: vec![(ConditionFieldName::Name, Order::Desc)],
                 limit: None,
@@ -286,7 +331,7 @@ mod test {
             .get_filtered_conditions(&FilterGroup::<ConditionFieldName> {
                 filters: vec![vec![(
                     ConditionFieldName::MaturationDays,
-                    Filter::NotEqual("3".to_owned()),
+                    Filter::NotEqual(FilterValue::Real(3.0)),
                 )]],
                 order_by: vec![(ConditionFieldName::Name, Order::Asc)],
                 limit: None,
@@ -327,11 +372,11 @@ mod test {
             filters: vec![
                 vec![(
                     ExpressionRelationFieldName::AlleleName,
-                    Filter::Equal("n765".to_owned()),
+                    Filter::Equal("n765".into()),
                 )],
                 vec![(
                     ExpressionRelationFieldName::ExpressingPhenotypeName,
-                    Filter::Equal("lin-15B".to_owned()),
+                    Filter::Equal("lin-15B".into()),
                 )],
                 vec![(
                     ExpressionRelationFieldName::ExpressingPhenotypeWild,
@@ -394,7 +439,9 @@ Tetracycline,,3,0,0,0,3"
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<ConditionDb> = Bulk::from_reader(&mut reader);
 
-        state.insert_conditions(bulk).await?;
+        state
+            .insert_conditions(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         assert_eq!(
             state.get_conditions().await?,
@@ -419,7 +466,9 @@ Tetracycline\t\t3\t0\t0\t0\t3"
             .from_reader(buf);
         let bulk: Bulk<ConditionDb> = Bulk::from_reader(&mut reader);
 
-        state.insert_conditions(bulk).await?;
+        state
+            .insert_conditions(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         assert_eq!(
             state.get_conditions().await?,
@@ -437,14 +486,16 @@ Tetracycline\t\t3\t0\t0\t0\t3"
         let delete_filter = &FilterGroup::<ConditionFieldName> {
             filters: vec![vec![(
                 ConditionFieldName::Name,
-                Filter::Equal("15C".to_string()),
+                Filter::Equal("15C".into()),
             )]],
             order_by: vec![],
             limit: None,
             offset: None,
         };
 
-        state.delete_filtered_conditions(delete_filter).await?;
+        state
+            .delete_filtered_conditions(delete_filter, DeleteMode::Restrict)
+            .await?;
 
         conditions = state.get_conditions().await?;
         assert_eq!(
@@ -469,7 +520,7 @@ Tetracycline\t\t3\t0\t0\t0\t3"
         let filter = &FilterGroup::<ConditionFieldName> {
             filters: vec![vec![(
                 ConditionFieldName::MaturationDays,
-                Filter::Equal("3".to_string()),
+                Filter::Equal(FilterValue::Real(3.0)),
             )]],
             order_by: vec![],
             limit: None,
@@ -480,7 +531,9 @@ Tetracycline\t\t3\t0\t0\t0\t3"
         let filtered_len = conditions.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_conditions(filter).await?;
+        state
+            .delete_filtered_conditions(filter, DeleteMode::Restrict)
+            .await?;
         conditions = state.get_conditions().await?;
 
         assert_eq!(conditions.len(), 1);
@@ -496,14 +549,14 @@ Tetracycline\t\t3\t0\t0\t0\t3"
         let mut conditions: Vec<Condition> = state.get_conditions().await?;
         assert_eq!(conditions.len(), mock::condition::get_conditions().len());
 
-        let filter = &FilterGroup::<ConditionFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_conditions(filter).await?;
+        let preview = state.preview_delete_all(DeletableTable::Conditions).await?;
+        state
+            .delete_all(
+                DeletableTable::Conditions,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         conditions = state.get_conditions().await?;
 
         assert_eq!(conditions.len(), 0);
@@ -1,11 +1,22 @@
-use super::{DbError, InnerDbState};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
     cross_design::{CrossDesign, CrossDesignDb, CrossDesignFieldName},
-    filter::{FilterGroup, FilterQueryBuilder},
+    filter::FilterQueryBuilder,
 };
 use anyhow::Result;
 use sqlx::{QueryBuilder, Sqlite};
 
+impl TableRow for CrossDesignDb {
+    const TABLE: &'static str = "cross_designs";
+    const COLUMNS: &'static [&'static str] = &["id", "name", "last_edited", "data", "editable"];
+    const KEY_COLUMNS: &'static [&'static str] = &["id"];
+}
+
 impl InnerDbState {
     pub async fn get_cross_designs(&self) -> Result<Vec<CrossDesign>, DbError> {
         match sqlx::query_as!(
@@ -27,11 +38,11 @@ impl InnerDbState {
 
     pub async fn get_filtered_cross_designs(
         &self,
-        filter: &FilterGroup<CrossDesignFieldName>,
+        filter: &impl FilterQueryBuilder<CrossDesignFieldName>,
     ) -> Result<Vec<CrossDesign>, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT id, name, last_edited, data, editable FROM cross_designs");
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<CrossDesignDb>()
@@ -46,6 +57,38 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_cross_designs_page(
+        &self,
+        filter: &impl FilterQueryBuilder<CrossDesignFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<CrossDesign>, DbError> {
+        Ok(self
+            .get_page::<CrossDesignDb, _>(filter, page)
+            .await?
+            .map(CrossDesign::from))
+    }
+
+    pub async fn query_cross_designs(
+        &self,
+        filter: &impl FilterQueryBuilder<CrossDesignFieldName>,
+        facets: Vec<CrossDesignFieldName>,
+    ) -> Result<QueryResult<CrossDesign, CrossDesignFieldName>, DbError> {
+        Ok(self
+            .query::<CrossDesignDb, _>(filter, facets)
+            .await?
+            .map(CrossDesign::from))
+    }
+
+    pub async fn aggregate_cross_designs(
+        &self,
+        filter: &impl FilterQueryBuilder<CrossDesignFieldName>,
+        group_by: &CrossDesignFieldName,
+        metrics: &[Metric<CrossDesignFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("cross_designs", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn insert_cross_design(&self, cross_design: &CrossDesign) -> Result<(), DbError> {
         let editable = cross_design.editable as i32;
         match sqlx::query!(
@@ -143,10 +186,7 @@ mod test {
         let state = InnerDbState { conn_pool: pool };
         let exprs = state
             .get_filtered_cross_designs(&FilterGroup::<CrossDesignFieldName> {
-                filters: vec![vec![(
-                    CrossDesignFieldName::Id,
-                    Filter::Equal("1".to_owned()),
-                )]],
+                filters: vec![vec![(CrossDesignFieldName::Id, Filter::Equal("1".into()))]],
                 order_by: vec![],
                 limit: None,
                 offset: None,
@@ -1,10 +1,55 @@
-use super::{bulk::Bulk, DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
     expr_relation::{ExpressionRelation, ExpressionRelationDb, ExpressionRelationFieldName},
-    filter::{Count, FilterGroup, FilterQueryBuilder},
+    filter::{Count, FilterQueryBuilder},
 };
 use anyhow::Result;
+use csv::Writer;
+use serde_json::{json, Value};
 use sqlx::{QueryBuilder, Sqlite};
+use std::io::Write;
+
+impl TableRow for ExpressionRelationDb {
+    const TABLE: &'static str = "expr_relations";
+    const COLUMNS: &'static [&'static str] = &[
+        "allele_name",
+        "expressing_phenotype_name",
+        "expressing_phenotype_wild",
+        "altering_phenotype_name",
+        "altering_phenotype_wild",
+        "altering_condition",
+        "is_suppressing",
+    ];
+    const KEY_COLUMNS: &'static [&'static str] = &[
+        "allele_name",
+        "expressing_phenotype_name",
+        "expressing_phenotype_wild",
+        "altering_phenotype_name",
+        "altering_phenotype_wild",
+        "altering_condition",
+    ];
+}
+
+impl BulkInsertable for ExpressionRelationDb {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.allele_name),
+            json!(self.expressing_phenotype_name),
+            json!(self.expressing_phenotype_wild),
+            json!(self.altering_phenotype_name),
+            json!(self.altering_phenotype_wild),
+            json!(self.altering_condition),
+            json!(self.is_suppressing),
+        ]
+    }
+}
 
 impl InnerDbState {
     pub async fn get_expr_relations(&self) -> Result<Vec<ExpressionRelation>, DbError> {
@@ -43,7 +88,7 @@ impl InnerDbState {
 
     pub async fn get_filtered_expr_relations(
         &self,
-        filter: &FilterGroup<ExpressionRelationFieldName>,
+        filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
     ) -> Result<Vec<ExpressionRelation>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT
@@ -57,7 +102,7 @@ impl InnerDbState {
             FROM
                 expr_relations",
         );
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<ExpressionRelationDb>()
@@ -72,13 +117,45 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_expr_relations_page(
+        &self,
+        filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<ExpressionRelation>, DbError> {
+        Ok(self
+            .get_page::<ExpressionRelationDb, _>(filter, page)
+            .await?
+            .map(ExpressionRelation::from))
+    }
+
+    pub async fn query_expr_relations(
+        &self,
+        filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
+        facets: Vec<ExpressionRelationFieldName>,
+    ) -> Result<QueryResult<ExpressionRelation, ExpressionRelationFieldName>, DbError> {
+        Ok(self
+            .query::<ExpressionRelationDb, _>(filter, facets)
+            .await?
+            .map(ExpressionRelation::from))
+    }
+
+    pub async fn aggregate_expr_relations(
+        &self,
+        filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
+        group_by: &ExpressionRelationFieldName,
+        metrics: &[Metric<ExpressionRelationFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("expr_relations", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_expr_relations(
         &self,
-        filter: &FilterGroup<ExpressionRelationFieldName>,
+        filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT COUNT(*) as count FROM expr_relations");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -128,78 +205,46 @@ impl InnerDbState {
     pub async fn insert_expr_relations(
         &self,
         bulk: Bulk<ExpressionRelationDb>,
-    ) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.len()
-            )));
-        }
-        let bind_limit = SQLITE_BIND_LIMIT / 7;
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-                "INSERT OR IGNORE INTO expr_relations (
-                allele_name,
-                expressing_phenotype_name,
-                expressing_phenotype_wild,
-                altering_phenotype_name,
-                altering_phenotype_wild,
-                altering_condition,
-                is_suppressing
-            ) ",
-            );
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.allele_name)
-                    .push_bind(item.expressing_phenotype_name)
-                    .push_bind(item.expressing_phenotype_wild)
-                    .push_bind(item.altering_phenotype_name)
-                    .push_bind(item.altering_phenotype_wild)
-                    .push_bind(item.altering_condition)
-                    .push_bind(item.is_suppressing);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk Insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            }
-        }
-        Ok(())
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
     }
 
-    pub async fn delete_filtered_expr_relations(
+    pub async fn export_filtered_expr_relations<W: Write>(
         &self,
-        filter: &FilterGroup<ExpressionRelationFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM expr_relations");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<ExpressionRelationDb, _, _>(filter, writer)
+            .await
+    }
 
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete Expression Relation error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+    /// Counts the rows in each table that deleting the filtered expression relations would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_expr_relations(
+        &self,
+        filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("expr_relations", filter).await
+    }
+
+    pub async fn delete_filtered_expr_relations(
+        &self,
+        filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("expr_relations", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
     use std::io::BufReader;
 
-    use crate::interface::bulk::Bulk;
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
     use crate::interface::mock;
     use crate::models::expr_relation::{ExpressionRelationDb, ExpressionRelationFieldName};
     use crate::models::filter::{Filter, FilterGroup, Order};
@@ -228,11 +273,11 @@ mod test {
                 filters: vec![
                     vec![(
                         ExpressionRelationFieldName::AlteringCondition,
-                        Filter::Equal("Histamine".to_owned()),
+                        Filter::Equal("Histamine".into()),
                     )],
                     vec![(
                         ExpressionRelationFieldName::ExpressingPhenotypeName,
-                        Filter::Equal("paralyzed".to_owned()),
+                        Filter::Equal("paralyzed".into()),
                     )],
                 ],
                 order_by: vec![(ExpressionRelationFieldName::AlleleName, Order::Asc)],
@@ -252,7 +297,7 @@ mod test {
             filters: vec![
                 vec![(
                     ExpressionRelationFieldName::ExpressingPhenotypeName,
-                    Filter::Equal("paralyzed".to_owned()),
+                    Filter::Equal("paralyzed".into()),
                 )],
                 vec![(
                     ExpressionRelationFieldName::AlteringCondition,
@@ -269,7 +314,7 @@ mod test {
                 vec![(ExpressionRelationFieldName::IsSuppressing, Filter::False)],
                 vec![(
                     ExpressionRelationFieldName::AlleleName,
-                    Filter::Equal("oxEx219999".to_string()),
+                    Filter::Equal("oxEx219999".into()),
                 )],
             ],
             order_by: vec![(ExpressionRelationFieldName::AlleleName, Order::Asc)],
@@ -415,7 +460,9 @@ oxIs644,YFP(pharynx),0,Flp,1,,0"
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<ExpressionRelationDb> = Bulk::from_reader(&mut reader);
 
-        state.insert_expr_relations(bulk).await?;
+        state
+            .insert_expr_relations(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let rels = state.get_expr_relations().await?;
         assert_eq!(vec![rel], rels);
@@ -584,11 +631,11 @@ oxIs644,YFP(pharynx),0,Flp,1,,0"
             filters: vec![
                 vec![(
                     ExpressionRelationFieldName::AlleleName,
-                    Filter::Equal("eT1(III)".to_string()),
+                    Filter::Equal("eT1(III)".into()),
                 )],
                 vec![(
                     ExpressionRelationFieldName::ExpressingPhenotypeName,
-                    Filter::Equal("eT1IIIhet_aneuploid".to_string()),
+                    Filter::Equal("eT1IIIhet_aneuploid".into()),
                 )],
                 vec![(
                     ExpressionRelationFieldName::ExpressingPhenotypeWild,
@@ -596,7 +643,7 @@ oxIs644,YFP(pharynx),0,Flp,1,,0"
                 )],
                 vec![(
                     ExpressionRelationFieldName::AlteringPhenotypeName,
-                    Filter::Equal("eT1Vhet_aneuploid".to_string()),
+                    Filter::Equal("eT1Vhet_aneuploid".into()),
                 )],
                 vec![(
                     ExpressionRelationFieldName::AlteringPhenotypeWild,
@@ -609,7 +656,9 @@ oxIs644,YFP(pharynx),0,Flp,1,,0"
             offset: None,
         };
 
-        state.delete_filtered_expr_relations(delete_filter).await?;
+        state
+            .delete_filtered_expr_relations(delete_filter, DeleteMode::Restrict)
+            .await?;
 
         expr_relations = state.get_expr_relations().await?;
         assert_eq!(
@@ -645,7 +694,9 @@ oxIs644,YFP(pharynx),0,Flp,1,,0"
         let filtered_len = expr_relations.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_expr_relations(filter).await?;
+        state
+            .delete_filtered_expr_relations(filter, DeleteMode::Restrict)
+            .await?;
         expr_relations = state.get_expr_relations().await?;
 
         assert_eq!(expr_relations.len(), orig_len - filtered_len);
@@ -664,14 +715,16 @@ oxIs644,YFP(pharynx),0,Flp,1,,0"
             mock::expr_relation::get_expr_relations().len()
         );
 
-        let filter = &FilterGroup::<ExpressionRelationFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_expr_relations(filter).await?;
+        let preview = state
+            .preview_delete_all(DeletableTable::ExprRelations)
+            .await?;
+        state
+            .delete_all(
+                DeletableTable::ExprRelations,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         expr_relations = state.get_expr_relations().await?;
 
         assert_eq!(expr_relations.len(), 0);
@@ -479,6 +479,21 @@ VALUES
         1
     );
 
+INSERT INTO
+    task_dependencies (parent_id, child_id)
+VALUES
+    (1, 3),
+    (2, 3),
+    (3, 4);
+
+INSERT INTO
+    task_conditions (task_id, condition_name)
+VALUES
+    (1, "25C"),
+    (2, "15C"),
+    (2, "Histamine"),
+    (4, "25C");
+
 INSERT INTO
     strains (name, genotype, description)
 VALUES
@@ -1,10 +1,48 @@
-use super::{bulk::Bulk, DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
-    filter::{Count, FilterGroup, FilterQueryBuilder},
+    filter::{Count, FilterQueryBuilder},
     gene::{Gene, GeneDb, GeneFieldName},
 };
 use anyhow::Result;
+use csv::Writer;
+use serde_json::{json, Value};
 use sqlx::{QueryBuilder, Sqlite};
+use std::io::Write;
+
+impl TableRow for GeneDb {
+    const TABLE: &'static str = "genes";
+    const COLUMNS: &'static [&'static str] = &[
+        "systematic_name",
+        "descriptive_name",
+        "chromosome",
+        "phys_loc",
+        "gen_loc",
+        "recomb_suppressor_start",
+        "recomb_suppressor_end",
+    ];
+    const KEY_COLUMNS: &'static [&'static str] = &["systematic_name"];
+}
+
+impl BulkInsertable for GeneDb {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.systematic_name),
+            json!(self.descriptive_name),
+            json!(self.chromosome),
+            json!(self.phys_loc),
+            json!(self.gen_loc),
+            json!(self.recomb_suppressor_start),
+            json!(self.recomb_suppressor_end),
+        ]
+    }
+}
 
 impl InnerDbState {
     pub async fn get_genes(&self) -> Result<Vec<Gene>, DbError> {
@@ -27,12 +65,12 @@ impl InnerDbState {
 
     pub async fn get_filtered_genes(
         &self,
-        filter: &FilterGroup<GeneFieldName>,
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
     ) -> Result<Vec<Gene>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT systematic_name, descriptive_name, chromosome, phys_loc, gen_loc, recomb_suppressor_start, recomb_suppressor_end FROM genes",
         );
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<GeneDb>()
@@ -47,12 +85,44 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_genes_page(
+        &self,
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<Gene>, DbError> {
+        Ok(self
+            .get_page::<GeneDb, _>(filter, page)
+            .await?
+            .map(Gene::from))
+    }
+
+    pub async fn query_genes(
+        &self,
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
+        facets: Vec<GeneFieldName>,
+    ) -> Result<QueryResult<Gene, GeneFieldName>, DbError> {
+        Ok(self
+            .query::<GeneDb, _>(filter, facets)
+            .await?
+            .map(Gene::from))
+    }
+
+    pub async fn aggregate_genes(
+        &self,
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
+        group_by: &GeneFieldName,
+        metrics: &[Metric<GeneFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("genes", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_genes(
         &self,
-        filter: &FilterGroup<GeneFieldName>,
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COUNT(*) as count FROM genes");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -97,78 +167,55 @@ impl InnerDbState {
         }
     }
 
-    pub async fn insert_genes(&self, bulk: Bulk<GeneDb>) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.len()
-            )));
-        }
-        let bind_limit = SQLITE_BIND_LIMIT / 7;
-
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-                "INSERT OR IGNORE INTO genes (systematic_name, descriptive_name, chromosome, phys_loc, gen_loc, recomb_suppressor_start, recomb_suppressor_end) "
-            );
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.systematic_name)
-                    .push_bind(item.descriptive_name)
-                    .push_bind(item.chromosome)
-                    .push_bind(item.phys_loc)
-                    .push_bind(item.gen_loc)
-                    .push_bind(item.recomb_suppressor_start)
-                    .push_bind(item.recomb_suppressor_end);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk Insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            }
-        }
-        Ok(())
+    pub async fn insert_genes(
+        &self,
+        bulk: Bulk<GeneDb>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
     }
 
-    pub async fn delete_filtered_genes(
+    pub async fn export_filtered_genes<W: Write>(
         &self,
-        filter: &FilterGroup<GeneFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM genes");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<GeneDb, _, _>(filter, writer).await
+    }
 
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete Gene error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+    /// Counts the rows in each table that deleting the filtered genes would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_genes(
+        &self,
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("genes", filter).await
+    }
+
+    pub async fn delete_filtered_genes(
+        &self,
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("genes", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
     use std::io::BufReader;
 
-    use crate::interface::bulk::Bulk;
+    use crate::interface::bulk::{Bulk, BulkReport, ConflictPolicy, FieldChange, RowDiff};
     use crate::models::chromosome_name::ChromosomeName;
     use crate::models::filter::Order;
     use crate::models::gene::{Gene, GeneDb, GeneFieldName};
     use crate::InnerDbState;
     use crate::{
-        interface::mock,
-        models::filter::{Filter, FilterGroup},
+        interface::{mock, DbError},
+        models::filter::{AnyFilter, Filter, FilterExpr, FilterGroup, FilterTree, FilterValue},
     };
     use anyhow::Result;
     use pretty_assertions::assert_eq;
@@ -191,8 +238,8 @@ mod test {
         let exprs = state
             .get_filtered_genes(&FilterGroup::<GeneFieldName> {
                 filters: vec![vec![
-                    (GeneFieldName::Chromosome, Filter::Equal("X".to_string())),
-                    (GeneFieldName::Chromosome, Filter::Equal("IV".to_string())),
+                    (GeneFieldName::Chromosome, Filter::Equal("X".into())),
+                    (GeneFieldName::Chromosome, Filter::Equal("IV".into())),
                 ]],
                 order_by: vec![(GeneFieldName::DescName, Order::Asc)],
                 limit: None,
@@ -210,8 +257,8 @@ mod test {
         let exprs = state
             .get_filtered_genes(&FilterGroup::<GeneFieldName> {
                 filters: vec![vec![
-                    (GeneFieldName::Chromosome, Filter::Equal("X".to_string())),
-                    (GeneFieldName::Chromosome, Filter::Equal("IV".to_string())),
+                    (GeneFieldName::Chromosome, Filter::Equal("X".into())),
+                    (GeneFieldName::Chromosome, Filter::Equal("IV".into())),
                 ]],
                 order_by: vec![(GeneFieldName::SysName, Order::Asc)],
                 limit: None,
@@ -223,14 +270,177 @@ mod test {
         Ok(())
     }
 
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_filter_genes_type_mismatch(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let get_filter = |filter: Filter| FilterGroup::<GeneFieldName> {
+            filters: vec![vec![(GeneFieldName::GeneticLoc, filter)]],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+
+        let res = state
+            .get_filtered_genes(&get_filter(Filter::LessThan("0.5".into(), false)))
+            .await;
+        assert!(matches!(res, Err(DbError::InvalidFilter(_))));
+        let res = state
+            .get_filtered_genes(&get_filter(Filter::Like("0.5".to_string())))
+            .await;
+        assert!(matches!(res, Err(DbError::InvalidFilter(_))));
+
+        // whole numbers are fine for real columns, and come through JSON as numbers
+        let filter: Filter = serde_json::from_str(r#"{ "LessThan": [0, false] }"#)?;
+        assert_eq!(filter, Filter::LessThan(FilterValue::Int(0), false));
+        assert_eq!(
+            state.get_filtered_genes(&get_filter(filter)).await?.len(),
+            state
+                .get_filtered_genes(&get_filter(Filter::LessThan(FilterValue::Real(0.0), false)))
+                .await?
+                .len()
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_filter_genes_by_expression(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let get_names = |genes: Vec<Gene>| -> Vec<String> {
+            genes
+                .into_iter()
+                .filter_map(|gene| gene.descriptive_name)
+                .collect()
+        };
+
+        // (chromosome = X AND gen_loc < 0) OR (chromosome = IV AND gen_loc > 5)
+        let filter = FilterTree::<GeneFieldName> {
+            filter: FilterExpr::Or(vec![
+                FilterExpr::And(vec![
+                    FilterExpr::Leaf(GeneFieldName::Chromosome, Filter::Equal("X".into())),
+                    FilterExpr::Leaf(
+                        GeneFieldName::GeneticLoc,
+                        Filter::LessThan(FilterValue::Real(0.0), false),
+                    ),
+                ]),
+                FilterExpr::And(vec![
+                    FilterExpr::Leaf(GeneFieldName::Chromosome, Filter::Equal("IV".into())),
+                    FilterExpr::Leaf(
+                        GeneFieldName::GeneticLoc,
+                        Filter::GreaterThan(FilterValue::Real(5.0), false),
+                    ),
+                ]),
+            ]),
+            order_by: vec![(GeneFieldName::DescName, Order::Asc)],
+            limit: None,
+            offset: None,
+        };
+        let genes = state.get_filtered_genes(&filter).await?;
+        assert_eq!(get_names(genes), vec!["dpy-20", "unc-18"]);
+        assert_eq!(state.get_count_filtered_genes(&filter).await?, 2);
+
+        let filter = FilterTree::<GeneFieldName> {
+            filter: FilterExpr::Not(Box::new(FilterExpr::Leaf(
+                GeneFieldName::Chromosome,
+                Filter::In(vec!["IV".into(), "X".into()]),
+            ))),
+            order_by: vec![(GeneFieldName::DescName, Order::Asc)],
+            limit: None,
+            offset: None,
+        };
+        let genes = state.get_filtered_genes(&filter).await?;
+        assert_eq!(get_names(genes), vec!["dpy-10", "unc-119"]);
+
+        // empty groups don't filter anything
+        let filter = FilterTree::<GeneFieldName> {
+            filter: FilterExpr::And(vec![FilterExpr::Or(vec![])]),
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+        assert_eq!(state.get_count_filtered_genes(&filter).await?, 8);
+
+        // negating nothing is rejected rather than matching every row, even nested
+        for expr in [
+            FilterExpr::Not(Box::new(FilterExpr::And(vec![]))),
+            FilterExpr::Not(Box::new(FilterExpr::Or(vec![]))),
+            FilterExpr::Or(vec![FilterExpr::Not(Box::new(FilterExpr::And(vec![])))]),
+        ] {
+            let filter = FilterTree::<GeneFieldName> {
+                filter: expr,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+            };
+            let res = state.get_filtered_genes(&filter).await;
+            assert!(matches!(res, Err(DbError::InvalidFilter(_))));
+            let res = state.get_count_filtered_genes(&filter).await;
+            assert!(matches!(res, Err(DbError::InvalidFilter(_))));
+        }
+
+        // leaves are type checked like any other filter
+        let filter = FilterTree::<GeneFieldName> {
+            filter: FilterExpr::Not(Box::new(FilterExpr::Leaf(
+                GeneFieldName::PhysLoc,
+                Filter::Like("1".to_string()),
+            ))),
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+        let res = state.get_filtered_genes(&filter).await;
+        assert!(matches!(res, Err(DbError::InvalidFilter(_))));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_filter_genes_with_either_shape(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let group: AnyFilter<GeneFieldName> = serde_json::from_str(
+            r#"{
+                "filters": [[["Chromosome", { "Equal": "X" }]], [["PhysLoc", { "GreaterThan": [8000000, false] }]]],
+                "orderBy": []
+            }"#,
+        )?;
+        let tree: AnyFilter<GeneFieldName> = serde_json::from_str(
+            r#"{
+                "filter": { "And": [
+                    { "Leaf": ["Chromosome", { "Equal": "X" }] },
+                    { "Not": { "Leaf": ["PhysLoc", { "LessThan": [8000000, true] }] } }
+                ] },
+                "orderBy": []
+            }"#,
+        )?;
+        assert!(matches!(group, AnyFilter::Group(_)));
+        assert!(matches!(tree, AnyFilter::Tree(_)));
+
+        let expected = state
+            .get_filtered_genes(&FilterGroup::<GeneFieldName> {
+                filters: vec![vec![(
+                    GeneFieldName::SysName,
+                    Filter::Equal("ZK662.4".into()),
+                )]],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+            })
+            .await?;
+        assert_eq!(state.get_filtered_genes(&group).await?, expected);
+        assert_eq!(state.get_filtered_genes(&tree).await?, expected);
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("full_db"))]
     async fn test_get_filtered_genes_and_clause(pool: Pool<Sqlite>) -> Result<()> {
         let state = InnerDbState { conn_pool: pool };
         let exprs = state
             .get_filtered_genes(&FilterGroup::<GeneFieldName> {
                 filters: vec![
-                    vec![(GeneFieldName::Chromosome, Filter::Equal("X".to_string()))],
-                    vec![(GeneFieldName::PhysLoc, Filter::Equal("7682896".to_string()))],
+                    vec![(GeneFieldName::Chromosome, Filter::Equal("X".into()))],
+                    vec![(
+                        GeneFieldName::PhysLoc,
+                        Filter::Equal(FilterValue::Int(7682896)),
+                    )],
                 ],
                 order_by: vec![],
                 limit: None,
@@ -248,17 +458,20 @@ mod test {
             .get_filtered_genes(&FilterGroup::<GeneFieldName> {
                 filters: vec![
                     vec![
-                        (GeneFieldName::Chromosome, Filter::Equal("X".to_string())),
+                        (GeneFieldName::Chromosome, Filter::Equal("X".into())),
                         (
                             GeneFieldName::GeneticLoc,
-                            Filter::GreaterThan("5".to_string(), true),
+                            Filter::GreaterThan(FilterValue::Real(5.0), true),
                         ),
                     ],
                     vec![
-                        (GeneFieldName::PhysLoc, Filter::Equal("7682896".to_string())),
+                        (
+                            GeneFieldName::PhysLoc,
+                            Filter::Equal(FilterValue::Int(7682896)),
+                        ),
                         (
                             GeneFieldName::GeneticLoc,
-                            Filter::GreaterThan("5".to_string(), true),
+                            Filter::GreaterThan(FilterValue::Real(5.0), true),
                         ),
                     ],
                 ],
@@ -367,7 +580,9 @@ FAKE23.4,unc-new,,10902633,6.78,,"
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<GeneDb> = Bulk::from_reader(&mut reader);
 
-        state.insert_genes(bulk).await?;
+        state
+            .insert_genes(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let genes: Vec<Gene> = state.get_genes().await?;
         assert_eq!(
@@ -410,7 +625,9 @@ FAKE23.4\tunc-new\t\t10902633\t6.78\t\t"
             .from_reader(buf);
         let bulk: Bulk<GeneDb> = Bulk::from_reader(&mut reader);
 
-        state.insert_genes(bulk).await?;
+        state
+            .insert_genes(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let genes: Vec<Gene> = state.get_genes().await?;
         assert_eq!(
@@ -437,6 +654,214 @@ FAKE23.4\tunc-new\t\t10902633\t6.78\t\t"
         Ok(())
     }
 
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_genes_report(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let gene_count = state.get_genes().await?.len();
+
+        let csv_str = "sysName,descName,chromosome,physLoc,geneticLoc,recombSuppressorStart,recombSuppressorEnd
+M142.1,unc-119,III,10902641,5.59,,
+FAKE23.4,unc-new,,10902633,6.78,,
+FAKE23.4,unc-new,,10902633,6.78,,
+FAKE23.5,unc-bad,,not-a-number,6.78,,"
+            .as_bytes();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(BufReader::new(csv_str));
+        let bulk: Bulk<GeneDb> = Bulk::from_reader(&mut reader);
+
+        let report = state
+            .insert_genes(bulk, ConflictPolicy::Ignore, false)
+            .await?;
+        assert_eq!(report.inserted, vec![3]);
+        assert_eq!(report.skipped, vec![2, 4]);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line, 5);
+        assert_eq!(report.rejected[0].column, Some("physLoc".to_string()));
+        assert!(!report.committed);
+        assert_eq!(state.get_genes().await?.len(), gene_count);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_genes_dry_run(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let gene_count = state.get_genes().await?.len();
+
+        let csv_str = "sysName,descName,chromosome,physLoc,geneticLoc,recombSuppressorStart,recombSuppressorEnd
+M142.1,unc-119,III,10902641,5.59,,
+FAKE23.4,unc-new,,10902633,6.78,,"
+            .as_bytes();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(BufReader::new(csv_str));
+        let bulk: Bulk<GeneDb> = Bulk::from_reader(&mut reader);
+
+        let report = state
+            .insert_genes(bulk, ConflictPolicy::Ignore, true)
+            .await?;
+        assert_eq!(
+            report,
+            BulkReport {
+                inserted: vec![3],
+                updated: vec![],
+                skipped: vec![2],
+                rejected: vec![],
+                committed: false,
+            }
+        );
+        assert_eq!(state.get_genes().await?.len(), gene_count);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_genes_overwrite(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let csv_str = "sysName,descName,chromosome,physLoc,geneticLoc,recombSuppressorStart,recombSuppressorEnd
+M142.1,unc-119,III,10902641,6.0,,
+T14B4.7,dpy-10,II,6710149,0,,
+FAKE23.4,unc-new,,10902633,6.78,,"
+            .as_bytes();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(BufReader::new(csv_str));
+        let bulk: Bulk<GeneDb> = Bulk::from_reader(&mut reader);
+
+        let report = state
+            .insert_genes(bulk, ConflictPolicy::Overwrite, false)
+            .await?;
+        assert_eq!(
+            report,
+            BulkReport {
+                inserted: vec![4],
+                updated: vec![RowDiff {
+                    line: 2,
+                    changes: vec![FieldChange {
+                        column: "gen_loc".to_string(),
+                        old: Some("5.59".to_string()),
+                        new: Some("6.0".to_string()),
+                    }],
+                }],
+                skipped: vec![3],
+                rejected: vec![],
+                committed: true,
+            }
+        );
+        let genes = state.get_genes().await?;
+        let gene = genes.iter().find(|g| g.systematic_name == "M142.1");
+        assert_eq!(gene.and_then(|g| g.gen_loc), Some(6.0));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_genes_overwrite_null_fields(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let csv_str = "sysName,descName,chromosome,physLoc,geneticLoc,recombSuppressorStart,recombSuppressorEnd
+M142.1,unc-119,III,10902641,6.0,100,
+M142.1,,,,,,200"
+            .as_bytes();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(BufReader::new(csv_str));
+        let bulk: Bulk<GeneDb> = Bulk::from_reader(&mut reader);
+
+        let report = state
+            .insert_genes(bulk, ConflictPolicy::OverwriteNullFields, false)
+            .await?;
+        let change = |column: &str, new: &str| FieldChange {
+            column: column.to_string(),
+            old: None,
+            new: Some(new.to_string()),
+        };
+        assert_eq!(
+            report.updated,
+            vec![
+                RowDiff {
+                    line: 2,
+                    changes: vec![change("recomb_suppressor_start", "100")],
+                },
+                RowDiff {
+                    line: 3,
+                    changes: vec![change("recomb_suppressor_end", "200")],
+                },
+            ]
+        );
+        let genes = state.get_genes().await?;
+        let gene = genes
+            .iter()
+            .find(|g| g.systematic_name == "M142.1")
+            .unwrap();
+        assert_eq!(gene.gen_loc, Some(5.59));
+        assert_eq!(gene.recomb_suppressor, Some((100, 200)));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_genes_fail_on_conflict(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let gene_count = state.get_genes().await?.len();
+
+        let csv_str = "sysName,descName,chromosome,physLoc,geneticLoc,recombSuppressorStart,recombSuppressorEnd
+FAKE23.4,unc-new,,10902633,6.78,,
+M142.1,unc-119,III,10902641,6.0,,"
+            .as_bytes();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(BufReader::new(csv_str));
+        let bulk: Bulk<GeneDb> = Bulk::from_reader(&mut reader);
+
+        let report = state
+            .insert_genes(bulk, ConflictPolicy::Fail, false)
+            .await?;
+        assert_eq!(report.inserted, vec![2]);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line, 3);
+        assert!(!report.committed);
+        assert_eq!(state.get_genes().await?.len(), gene_count);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_export_filtered_genes_round_trip(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = |limit: Option<u32>| FilterGroup::<GeneFieldName> {
+            filters: vec![vec![(
+                GeneFieldName::Chromosome,
+                Filter::Equal("IV".into()),
+            )]],
+            order_by: vec![(GeneFieldName::SysName, Order::Asc)],
+            limit,
+            offset: None,
+        };
+        let genes = state.get_filtered_genes(&filter(None)).await?;
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        // the limit only applies to the table view, every matching row is exported
+        let exported = state
+            .export_filtered_genes(&filter(Some(1)), &mut writer)
+            .await?;
+        let csv_bytes = writer.into_inner()?;
+        assert_eq!(exported, genes.len());
+        assert!(csv_bytes.starts_with(
+            b"sysName,descName,chromosome,physLoc,geneticLoc,recombSuppressorStart,recombSuppressorEnd\n"
+        ));
+
+        // re-importing the file over the same rows changes nothing
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(BufReader::new(csv_bytes.as_slice()));
+        let bulk: Bulk<GeneDb> = Bulk::from_reader(&mut reader);
+        let report = state
+            .insert_genes(bulk, ConflictPolicy::Overwrite, false)
+            .await?;
+        assert_eq!(report.skipped.len(), genes.len());
+        assert!(report.updated.is_empty() && report.rejected.is_empty());
+        assert_eq!(state.get_filtered_genes(&filter(None)).await?, genes);
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn test_delete_single_gene(pool: Pool<Sqlite>) -> Result<()> {
         let state = InnerDbState { conn_pool: pool };
@@ -456,15 +881,18 @@ FAKE23.4\tunc-new\t\t10902633\t6.78\t\t"
         assert_eq!(genes.len(), 1);
 
         state
-            .delete_filtered_genes(&FilterGroup::<GeneFieldName> {
-                filters: vec![vec![(
-                    GeneFieldName::SysName,
-                    Filter::Equal("T14B4.7".to_string()),
-                )]],
-                order_by: vec![],
-                limit: None,
-                offset: None,
-            })
+            .delete_filtered_genes(
+                &FilterGroup::<GeneFieldName> {
+                    filters: vec![vec![(
+                        GeneFieldName::SysName,
+                        Filter::Equal("T14B4.7".into()),
+                    )]],
+                    order_by: vec![],
+                    limit: None,
+                    offset: None,
+                },
+                DeleteMode::Restrict,
+            )
             .await?;
 
         genes = state.get_genes().await?;
@@ -489,7 +917,7 @@ FAKE23.4\tunc-new\t\t10902633\t6.78\t\t"
         let filter = &FilterGroup::<GeneFieldName> {
             filters: vec![vec![(
                 GeneFieldName::Chromosome,
-                Filter::Equal(ChromosomeName::X.to_string()),
+                Filter::Equal(ChromosomeName::X.to_string().into()),
             )]],
             order_by: vec![],
             limit: None,
@@ -500,7 +928,9 @@ FAKE23.4\tunc-new\t\t10902633\t6.78\t\t"
         let filtered_len = genes.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_genes(filter).await?;
+        state
+            .delete_filtered_genes(filter, DeleteMode::Restrict)
+            .await?;
         genes = state.get_genes().await?;
 
         assert_eq!(genes.len(), orig_len - filtered_len);
@@ -520,14 +950,14 @@ FAKE23.4\tunc-new\t\t10902633\t6.78\t\t"
         let mut genes: Vec<Gene> = state.get_genes().await?;
         assert_eq!(genes.len(), mock::gene::get_genes().len());
 
-        let filter = &FilterGroup::<GeneFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_genes(filter).await?;
+        let preview = state.preview_delete_all(DeletableTable::Genes).await?;
+        state
+            .delete_all(
+                DeletableTable::Genes,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         genes = state.get_genes().await?;
 
         assert_eq!(genes.len(), 0);
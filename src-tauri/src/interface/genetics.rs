@@ -0,0 +1,506 @@
+use super::{DbError, InnerDbState};
+use crate::genetics::{
+    allele::{Allele, Locus},
+    allele_pair::AllelePair,
+    genetic_map::{locate, GeneticMap},
+    genotype::{parse_genotype, GenotypeMismatch},
+    offspring::Offspring,
+    strain::Strain,
+    GeneticsError,
+};
+use crate::models::{
+    chromosome_name::ChromosomeName,
+    filter::{Count, FilterQueryBuilder},
+    gene::{Gene, GeneFieldName},
+    strain_allele::StrainAllele,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr};
+use ts_rs::TS;
+
+impl From<GeneticsError> for DbError {
+    fn from(e: GeneticsError) -> DbError {
+        DbError::Genetics(e.to_string())
+    }
+}
+
+/// A strain allele together with the gene or variation it's on
+#[derive(sqlx::FromRow)]
+struct StrainAlleleLocusDb {
+    allele_name: String,
+    is_on_top: i64,
+    is_on_bot: i64,
+    systematic_gene_name: Option<String>,
+    descriptive_name: Option<String>,
+    variation_name: Option<String>,
+    chromosome: Option<String>,
+    phys_loc: Option<i32>,
+    gen_loc: Option<f64>,
+    #[sqlx(default)]
+    gen_loc_estimated: bool,
+}
+
+/// A gene placed both physically and genetically
+#[derive(sqlx::FromRow)]
+struct MappedGeneDb {
+    chromosome: String,
+    phys_loc: i32,
+    gen_loc: f64,
+}
+
+/// A gene with the genetic location crosses and the chromosome map place it at
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_LocatedGene.ts")]
+#[serde(rename = "db_LocatedGene")]
+pub struct LocatedGene {
+    pub gene: Gene,
+    /// None if the gene has neither a genetic location nor a physical one to estimate
+    /// it from
+    #[serde(rename = "geneticLoc")]
+    pub gen_loc: Option<f64>,
+    /// Whether gen_loc was estimated from the physical location, for lack of a measured one
+    pub estimated: bool,
+}
+
+impl From<StrainAlleleLocusDb> for AllelePair {
+    fn from(item: StrainAlleleLocusDb) -> AllelePair {
+        let (name, is_gene) = match (item.systematic_gene_name, item.variation_name) {
+            (Some(gene), _) => (gene, true),
+            (None, Some(variation)) => (variation, false),
+            (None, None) => (item.allele_name.clone(), false),
+        };
+        let locus = Locus {
+            name,
+            gene_name: item.descriptive_name,
+            is_gene,
+            chromosome: item
+                .chromosome
+                .and_then(|chrom| ChromosomeName::from_str(&chrom).ok()),
+            gen_loc: item.gen_loc,
+            gen_loc_estimated: item.gen_loc_estimated,
+        };
+        let allele = Allele::new(item.allele_name, locus);
+        match (item.is_on_top == 1, item.is_on_bot == 1) {
+            (true, true) => allele.to_homo(),
+            (true, false) => allele.to_top_het(),
+            (false, true) => allele.to_bot_het(),
+            (false, false) => AllelePair::new(allele.to_wild(), allele.to_wild()),
+        }
+    }
+}
+
+impl InnerDbState {
+    /// Loads the genotype of a strain from its strain_alleles rows
+    pub async fn get_genetic_strain(&self, strain_name: &str) -> Result<Strain, DbError> {
+        match sqlx::query_as::<_, Count>("SELECT COUNT(*) as count FROM strains WHERE name = ?")
+            .bind(strain_name)
+            .fetch_one(&self.conn_pool)
+            .await
+        {
+            Ok(count) if count.count == 0 => {
+                return Err(DbError::Query(format!(
+                    "Strain {strain_name} does not exist"
+                )))
+            }
+            Ok(_) => (),
+            Err(e) => {
+                eprint!("Get genetic strain error: {e}");
+                return Err(DbError::Query(e.to_string()));
+            }
+        };
+
+        let mut rows = match sqlx::query_as::<_, StrainAlleleLocusDb>(
+            "
+            SELECT
+                sa.allele_name,
+                sa.is_on_top,
+                sa.is_on_bot,
+                a.systematic_gene_name,
+                g.descriptive_name,
+                a.variation_name,
+                COALESCE(g.chromosome, v.chromosome) AS chromosome,
+                COALESCE(g.phys_loc, v.phys_loc) AS phys_loc,
+                COALESCE(g.gen_loc, v.gen_loc) AS gen_loc
+            FROM strain_alleles AS sa
+            JOIN alleles AS a ON a.name = sa.allele_name
+            LEFT JOIN genes AS g ON g.systematic_name = a.systematic_gene_name
+            LEFT JOIN variations AS v ON v.allele_name = a.variation_name
+            WHERE sa.strain_name = ?
+            ORDER BY sa.allele_name
+            ",
+        )
+        .bind(strain_name)
+        .fetch_all(&self.conn_pool)
+        .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                eprint!("Get genetic strain error: {e}");
+                return Err(DbError::Query(e.to_string()));
+            }
+        };
+
+        // without a genetic location, an allele would look unlinked to everything else
+        if rows
+            .iter()
+            .any(|row| row.gen_loc.is_none() && row.phys_loc.is_some())
+        {
+            let genetic_maps = self.get_genetic_maps().await?;
+            for row in rows.iter_mut().filter(|row| row.gen_loc.is_none()) {
+                let genetic_map = row
+                    .chromosome
+                    .as_ref()
+                    .and_then(|chrom| ChromosomeName::from_str(chrom).ok())
+                    .and_then(|chrom| genetic_maps.get(&chrom));
+                if let Some((gen_loc, estimated)) = locate(None, row.phys_loc, genetic_map) {
+                    row.gen_loc = Some(gen_loc);
+                    row.gen_loc_estimated = estimated;
+                }
+            }
+        }
+
+        // heterozygous alleles of the same locus are stored as separate rows
+        let mut pairs_by_locus: Vec<(String, Vec<AllelePair>)> = vec![];
+        for row in rows {
+            let pair = AllelePair::from(row);
+            let locus_name = pair.top.locus.name.clone();
+            match pairs_by_locus
+                .iter_mut()
+                .find(|(name, _)| *name == locus_name)
+            {
+                Some((_, pairs)) => pairs.push(pair),
+                None => pairs_by_locus.push((locus_name, vec![pair])),
+            }
+        }
+
+        let mut allele_pairs: Vec<AllelePair> = vec![];
+        for (locus_name, pairs) in pairs_by_locus {
+            match pairs.as_slice() {
+                [pair] => allele_pairs.push(pair.clone()),
+                [pair1, pair2] => allele_pairs.push(pair1.merge(pair2)?),
+                _ => return Err(GeneticsError::TooManyAlleles(locus_name).into()),
+            }
+        }
+
+        Ok(Strain::new(allele_pairs)?)
+    }
+
+    /// Fits the genetic map of each chromosome from its genes with both a physical and a
+    /// genetic location. Chromosomes without enough of them are left out.
+    pub async fn get_genetic_maps(&self) -> Result<HashMap<ChromosomeName, GeneticMap>, DbError> {
+        let genes = match sqlx::query_as::<_, MappedGeneDb>(
+            "
+            SELECT chromosome, phys_loc, gen_loc
+            FROM genes
+            WHERE chromosome IS NOT NULL AND phys_loc IS NOT NULL AND gen_loc IS NOT NULL
+            ",
+        )
+        .fetch_all(&self.conn_pool)
+        .await
+        {
+            Ok(genes) => genes,
+            Err(e) => {
+                eprint!("Get genetic maps error: {e}");
+                return Err(DbError::Query(e.to_string()));
+            }
+        };
+
+        let mut points: HashMap<ChromosomeName, Vec<(i32, f64)>> = HashMap::new();
+        for gene in genes {
+            if let Ok(chrom) = ChromosomeName::from_str(&gene.chromosome) {
+                points
+                    .entry(chrom)
+                    .or_default()
+                    .push((gene.phys_loc, gene.gen_loc));
+            }
+        }
+        Ok(points
+            .into_iter()
+            .filter_map(|(chrom, points)| Some((chrom, GeneticMap::fit(points)?)))
+            .collect())
+    }
+
+    /// Filtered genes, with missing genetic locations estimated from the genetic maps
+    pub async fn get_filtered_located_genes(
+        &self,
+        filter: &impl FilterQueryBuilder<GeneFieldName>,
+    ) -> Result<Vec<LocatedGene>, DbError> {
+        let genes = self.get_filtered_genes(filter).await?;
+        let genetic_maps = match genes.iter().any(|gene| gene.gen_loc.is_none()) {
+            true => self.get_genetic_maps().await?,
+            false => HashMap::new(),
+        };
+        Ok(genes
+            .into_iter()
+            .map(|gene| {
+                let genetic_map = gene
+                    .chromosome
+                    .as_ref()
+                    .and_then(|chrom| genetic_maps.get(chrom));
+                let located = locate(gene.gen_loc, gene.phys_loc, genetic_map);
+                LocatedGene {
+                    gen_loc: located.map(|(gen_loc, _)| gen_loc),
+                    estimated: located.map_or(false, |(_, estimated)| estimated),
+                    gene,
+                }
+            })
+            .collect())
+    }
+
+    pub async fn cross_strains(
+        &self,
+        herm_name: &str,
+        male_name: &str,
+    ) -> Result<Vec<Offspring>, DbError> {
+        let herm = self.get_genetic_strain(herm_name).await?;
+        let male = self.get_genetic_strain(male_name).await?.to_male();
+        Self::cross(herm, male).await
+    }
+
+    pub async fn self_cross_strain(&self, herm_name: &str) -> Result<Vec<Offspring>, DbError> {
+        let herm = self.get_genetic_strain(herm_name).await?;
+        Self::cross(herm.clone(), herm).await
+    }
+
+    /// Reports every strain whose genotype text doesn't describe the same alleles
+    /// as its strain_alleles rows, or that can't be parsed
+    pub async fn validate_strains(&self) -> Result<Vec<GenotypeMismatch>, DbError> {
+        let strains = self.get_strains().await?;
+        let mut stored_by_strain: HashMap<String, Vec<StrainAllele>> = HashMap::new();
+        for strain_allele in self.get_strain_alleles().await? {
+            stored_by_strain
+                .entry(strain_allele.strain_name.clone())
+                .or_default()
+                .push(strain_allele);
+        }
+
+        let mut mismatches = vec![];
+        for strain in strains {
+            let mut stored_alleles = stored_by_strain.remove(&strain.name).unwrap_or_default();
+            stored_alleles.sort_by(|a, b| a.allele_name.cmp(&b.allele_name));
+
+            let (parsed_alleles, parse_error) = match parse_genotype(&strain.name, &strain.genotype)
+            {
+                Ok(parsed_alleles) => (parsed_alleles, None),
+                Err(e) => (vec![], Some(e.to_string())),
+            };
+            if parse_error.is_none() && parsed_alleles == stored_alleles {
+                continue;
+            }
+            mismatches.push(GenotypeMismatch {
+                strain_name: strain.name,
+                genotype: strain.genotype,
+                parsed_alleles,
+                stored_alleles,
+                parse_error,
+            });
+        }
+        Ok(mismatches)
+    }
+
+    async fn cross(herm: Strain, male: Strain) -> Result<Vec<Offspring>, DbError> {
+        // the number of offspring grows exponentially with heterozygous loci,
+        // so keep the computation off the async runtime
+        match tokio::task::spawn_blocking(move || herm.cross_with(&male)).await {
+            Ok(Ok(strains)) => Ok(strains.iter().map(Offspring::from).collect()),
+            Ok(Err(e)) => Err(e.into()),
+            Err(e) => {
+                eprint!("Cross strains error: {e}");
+                Err(DbError::Genetics(e.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::genetics::genotype::GenotypeMismatch;
+    use crate::genetics::offspring::{Offspring, OffspringAllele};
+    use crate::interface::{DbError, InnerDbState};
+    use crate::models::{
+        chromosome_name::ChromosomeName,
+        filter::{Filter, FilterGroup, Order},
+        gene::GeneFieldName,
+        strain_allele::StrainAllele,
+    };
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_cross_strains(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let offspring = state.cross_strains("TN64", "CB128").await?;
+
+        assert_eq!(
+            offspring,
+            vec![Offspring {
+                genotype: "dpy-10(cn64)/dpy-10(e128) II.".to_owned(),
+                alleles: vec![
+                    OffspringAllele {
+                        allele_name: "cn64".to_owned(),
+                        is_on_top: true,
+                        is_on_bot: false,
+                    },
+                    OffspringAllele {
+                        allele_name: "e128".to_owned(),
+                        is_on_top: false,
+                        is_on_bot: true,
+                    },
+                ],
+                probability: 1.0,
+            }]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_self_cross_linked_strain(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let offspring = state.self_cross_strain("BT14").await?;
+
+        let total: f64 = offspring.iter().map(|child| child.probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(
+            offspring[0].genotype,
+            "unc-24(e138) fbl-1(hd43) dpy-20(e1282)/unc-24(e138) + + IV."
+        );
+        assert_eq!(state.self_cross_strain("BT14").await?, offspring);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_cross_missing_strain(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let res = state.cross_strains("TN64", "Fake").await;
+
+        assert!(matches!(res, Err(DbError::Query(_))));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_genetic_maps(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let genetic_maps = state.get_genetic_maps().await?;
+
+        // II and III only have one mapped gene each
+        let mut chroms: Vec<_> = genetic_maps.keys().map(|chrom| chrom.to_string()).collect();
+        chroms.sort();
+        assert_eq!(chroms, vec!["IV", "X"]);
+        // about halfway between unc-18 and lin-15B
+        let estimate = genetic_maps[&ChromosomeName::X].estimate(11704509);
+        assert!((estimate - 10.8).abs() < 1e-4);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_genetic_strain_estimates_missing_gen_loc(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        sqlx::query("UPDATE genes SET gen_loc = NULL WHERE descriptive_name = 'fbl-1'")
+            .execute(&state.conn_pool)
+            .await?;
+
+        let strain = state.get_genetic_strain("BT14").await?;
+        let allele_pairs = &strain.chrom_pair_map[&Some(ChromosomeName::Iv)].allele_pairs;
+        let gen_locs: Vec<Option<f64>> = allele_pairs
+            .iter()
+            .map(|pair| pair.top.get_gen_position())
+            .collect();
+        let estimated: Vec<bool> = allele_pairs
+            .iter()
+            .map(|pair| pair.top.locus.gen_loc_estimated)
+            .collect();
+        // fbl-1 is still between unc-24 and kin-4, and stays linked to both neighbours
+        let expected = 3.51 + (9540806.0 - 7979870.0) * (4.98 - 3.51) / (11425742.0 - 7979870.0);
+        assert_eq!(gen_locs.len(), 3);
+        assert_eq!(gen_locs[0], Some(3.51));
+        assert!((gen_locs[1].unwrap() - expected).abs() < 1e-9);
+        assert_eq!(gen_locs[2], Some(5.22));
+        assert_eq!(estimated, vec![false, true, false]);
+        assert_eq!(
+            strain.to_genotype_string(true, false),
+            "unc-24(e138) fbl-1(hd43) dpy-20(e1282)/unc-24(e138) + + IV."
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_filtered_located_genes(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        sqlx::query("UPDATE genes SET gen_loc = NULL WHERE descriptive_name = 'fbl-1'")
+            .execute(&state.conn_pool)
+            .await?;
+        let filter = FilterGroup::<GeneFieldName> {
+            filters: vec![vec![
+                (GeneFieldName::DescName, Filter::Equal("fbl-1".into())),
+                (GeneFieldName::DescName, Filter::Equal("unc-24".into())),
+            ]],
+            order_by: vec![(GeneFieldName::DescName, Order::Asc)],
+            limit: None,
+            offset: None,
+        };
+
+        let genes = state.get_filtered_located_genes(&filter).await?;
+        // estimated the same way as in test_genetic_strain_estimates_missing_gen_loc
+        let expected = 3.51 + (9540806.0 - 7979870.0) * (4.98 - 3.51) / (11425742.0 - 7979870.0);
+        assert_eq!(genes.len(), 2);
+        assert!((genes[0].gen_loc.unwrap() - expected).abs() < 1e-9);
+        assert!(genes[0].estimated);
+        assert_eq!(genes[0].gene.gen_loc, None);
+        assert_eq!(genes[1].gen_loc, Some(3.51));
+        assert!(!genes[1].estimated);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_validate_strains(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let mismatches = state.validate_strains().await?;
+
+        let strain_allele = |allele_name: &str, is_on_top: bool, is_on_bot: bool| StrainAllele {
+            strain_name: "BT14".to_owned(),
+            allele_name: allele_name.to_owned(),
+            is_on_top,
+            is_on_bot,
+        };
+        // N2 is wild type, so its description isn't a mismatch
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0],
+            GenotypeMismatch {
+                strain_name: "BT14".to_owned(),
+                genotype: "fbl-1(hd43)/dpy-20(e1282) unc-24(e138) IV.".to_owned(),
+                parsed_alleles: vec![
+                    strain_allele("e1282", false, true),
+                    strain_allele("e138", false, true),
+                    strain_allele("hd43", true, false),
+                ],
+                stored_alleles: vec![
+                    strain_allele("e1282", true, false),
+                    strain_allele("e138", true, true),
+                    strain_allele("hd43", true, false),
+                ],
+                parse_error: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_validate_strains_with_unparsable_genotype(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        // N2 has no stored alleles, but is only wild type if its genotype says so
+        sqlx::query("UPDATE strains SET genotype = 'dpy-10(cn64 II.' WHERE name = 'N2'")
+            .execute(&state.conn_pool)
+            .await?;
+
+        let mismatches = state.validate_strains().await?;
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[1].strain_name, "N2");
+        assert_eq!(mismatches[1].stored_alleles, vec![]);
+        assert!(mismatches[1].parse_error.is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,397 @@
+use super::{quote, DbError, InnerDbState};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite, Transaction};
+use ts_rs::TS;
+
+/// Changes beyond this many are forgotten, oldest first
+const MAX_CHANGES: u32 = 100;
+/// Tables that aren't journaled, besides SQLite's own
+const UNJOURNALED_TABLES: &[&str] = &[
+    "_sqlx_migrations",
+    "journal_changes",
+    "journal_entries",
+    "journal_state",
+];
+/// The full-text index, its table of row keys and the tables FTS5 keeps it in, which
+/// follow the other tables by their own triggers
+const SEARCH_INDEX_PREFIX: &str = "search_index";
+
+/// A command's worth of changes to the database
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS, sqlx::FromRow)]
+#[ts(export, export_to = "../src/models/db/db_Change.ts")]
+#[serde(rename = "db_Change")]
+pub struct Change {
+    #[ts(type = "number")]
+    pub id: i64,
+    pub description: String,
+    /// Local time of the change, as "YYYY-MM-DD HH:MM:SS"
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    /// Undone changes can be redone until a new change is made
+    pub undone: bool,
+    /// Number of rows inserted, updated or deleted
+    pub rows: u32,
+}
+
+fn journal_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Journal error: {e}");
+    DbError::Journal(e.to_string())
+}
+
+/// Builds the sql text of a statement out of a row's values, which `quote()` turns
+/// into literals when the trigger fires
+fn get_values_sql(row: &str, columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|column| format!("quote({row}.{})", quote(column)))
+        .collect::<Vec<_>>()
+        .join(" || ', ' || ")
+}
+
+fn get_assignments_sql(row: &str, columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|column| format!("'{} = ' || quote({row}.{})", quote(column), quote(column)))
+        .collect::<Vec<_>>()
+        .join(" || ', ' || ")
+}
+
+fn get_key_sql(row: &str, key_columns: &[String]) -> String {
+    key_columns
+        .iter()
+        .map(|column| format!("'{} IS ' || quote({row}.{})", quote(column), quote(column)))
+        .collect::<Vec<_>>()
+        .join(" || ' AND ' || ")
+}
+
+impl InnerDbState {
+    /// (Re)creates the triggers that write the inverse of every insert, update and
+    /// delete to the journal, so they match the tables as they are after migrating
+    pub async fn install_journal(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let tables: Vec<String> = sqlx::query(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )
+        .fetch_all(&mut tx)
+        .await?
+        .iter()
+        .map(|row| row.try_get(0))
+        .collect::<Result<_, _>>()?;
+
+        for table in tables
+            .iter()
+            .filter(|table| !UNJOURNALED_TABLES.contains(&table.as_str()))
+            .filter(|table| !table.starts_with(SEARCH_INDEX_PREFIX))
+        {
+            let columns = sqlx::query("SELECT name, pk FROM pragma_table_info(?) ORDER BY cid")
+                .bind(table)
+                .fetch_all(&mut tx)
+                .await?;
+            let mut all_columns = vec![];
+            let mut key_columns = vec![];
+            for column in columns {
+                let name: String = column.try_get(0)?;
+                if column.try_get::<i64, _>(1)? > 0 {
+                    key_columns.push(name.clone());
+                }
+                all_columns.push(name);
+            }
+            if key_columns.is_empty() {
+                key_columns = all_columns.clone();
+            }
+
+            let table_name = quote(table);
+            let column_names = all_columns
+                .iter()
+                .map(|column| quote(column))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let insert_sql = |row: &str| {
+                format!(
+                    "'INSERT INTO {table_name} ({column_names}) VALUES (' || {} || ')'",
+                    get_values_sql(row, &all_columns)
+                )
+            };
+            let delete_sql = |row: &str| {
+                format!(
+                    "'DELETE FROM {table_name} WHERE ' || {}",
+                    get_key_sql(row, &key_columns)
+                )
+            };
+            let update_sql = |values_row: &str, key_row: &str| {
+                format!(
+                    "'UPDATE {table_name} SET ' || {} || ' WHERE ' || {}",
+                    get_assignments_sql(values_row, &all_columns),
+                    get_key_sql(key_row, &key_columns)
+                )
+            };
+
+            for (event, undo_sql, redo_sql) in [
+                ("INSERT", delete_sql("NEW"), insert_sql("NEW")),
+                ("UPDATE", update_sql("OLD", "NEW"), update_sql("NEW", "OLD")),
+                ("DELETE", insert_sql("OLD"), delete_sql("OLD")),
+            ] {
+                let trigger = quote(&format!("journal_{table}_{}", event.to_lowercase()));
+                sqlx::query(&format!("DROP TRIGGER IF EXISTS {trigger}"))
+                    .execute(&mut tx)
+                    .await?;
+                sqlx::query(&format!(
+                    "CREATE TRIGGER {trigger} AFTER {event} ON {table_name}
+                    WHEN (SELECT replaying FROM journal_state) = 0
+                    BEGIN
+                        INSERT INTO journal_entries (undo_sql, redo_sql)
+                        VALUES ({undo_sql}, {redo_sql});
+                    END"
+                ))
+                .execute(&mut tx)
+                .await?;
+            }
+        }
+        tx.commit().await
+    }
+
+    /// Groups the journal entries written since the last change into a new change.
+    /// Called once a command is done, whether or not it succeeded, so a failed command
+    /// that wrote some rows can be undone too. Writing commands hold the state's write
+    /// lock until they've called this, so the entries are all their own.
+    pub async fn record_change(&self, description: &str) -> Result<(), DbError> {
+        let mut tx = self.conn_pool.begin().await.map_err(journal_error)?;
+        let pending: i64 =
+            sqlx::query("SELECT COUNT(*) FROM journal_entries WHERE change_id IS NULL")
+                .fetch_one(&mut tx)
+                .await
+                .and_then(|row| row.try_get(0))
+                .map_err(journal_error)?;
+        if pending == 0 {
+            return tx.rollback().await.map_err(journal_error);
+        }
+
+        // a new change can't be redone over, so the undone ones are dropped
+        sqlx::query("DELETE FROM journal_changes WHERE undone = 1")
+            .execute(&mut tx)
+            .await
+            .map_err(journal_error)?;
+        let change_id = sqlx::query(
+            "INSERT INTO journal_changes (description, created_at, undone)
+            VALUES (?, datetime('now', 'localtime'), 0)",
+        )
+        .bind(description)
+        .execute(&mut tx)
+        .await
+        .map_err(journal_error)?
+        .last_insert_rowid();
+        sqlx::query("UPDATE journal_entries SET change_id = ? WHERE change_id IS NULL")
+            .bind(change_id)
+            .execute(&mut tx)
+            .await
+            .map_err(journal_error)?;
+        sqlx::query(
+            "DELETE FROM journal_changes WHERE id NOT IN
+            (SELECT id FROM journal_changes ORDER BY id DESC LIMIT ?)",
+        )
+        .bind(MAX_CHANGES)
+        .execute(&mut tx)
+        .await
+        .map_err(journal_error)?;
+        tx.commit().await.map_err(journal_error)
+    }
+
+    /// Changes newest first, including undone ones
+    pub async fn list_recent_changes(&self, limit: u32) -> Result<Vec<Change>, DbError> {
+        sqlx::query_as::<_, Change>(
+            "SELECT c.id, c.description, c.created_at, c.undone, COUNT(e.id) AS rows
+            FROM journal_changes AS c LEFT JOIN journal_entries AS e ON e.change_id = c.id
+            GROUP BY c.id ORDER BY c.id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.conn_pool)
+        .await
+        .map_err(journal_error)
+    }
+
+    /// Reverts the newest change that hasn't been undone, returning None if there's
+    /// nothing left to undo
+    pub async fn undo_last_change(&self) -> Result<Option<Change>, DbError> {
+        self.replay_change(true).await
+    }
+
+    /// Reapplies the change that was undone last, returning None if there's nothing
+    /// to redo
+    pub async fn redo_change(&self) -> Result<Option<Change>, DbError> {
+        self.replay_change(false).await
+    }
+
+    async fn replay_change(&self, undo: bool) -> Result<Option<Change>, DbError> {
+        let mut tx = self.conn_pool.begin().await.map_err(journal_error)?;
+        let change = sqlx::query_as::<_, Change>(&format!(
+            "SELECT c.id, c.description, c.created_at, c.undone, COUNT(e.id) AS rows
+            FROM journal_changes AS c LEFT JOIN journal_entries AS e ON e.change_id = c.id
+            WHERE c.undone = ? GROUP BY c.id ORDER BY c.id {} LIMIT 1",
+            if undo { "DESC" } else { "ASC" }
+        ))
+        .bind(!undo)
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(journal_error)?;
+        let mut change = match change {
+            Some(change) => change,
+            None => return tx.rollback().await.map(|_| None).map_err(journal_error),
+        };
+
+        Self::replay_entries(&mut tx, change.id, undo)
+            .await
+            .map_err(journal_error)?;
+        sqlx::query("UPDATE journal_changes SET undone = ? WHERE id = ?")
+            .bind(undo)
+            .bind(change.id)
+            .execute(&mut tx)
+            .await
+            .map_err(journal_error)?;
+        tx.commit().await.map_err(journal_error)?;
+        change.undone = undo;
+        Ok(Some(change))
+    }
+
+    /// Runs the undo statements of a change newest first, or its redo statements
+    /// oldest first
+    async fn replay_entries(
+        tx: &mut Transaction<'_, Sqlite>,
+        change_id: i64,
+        undo: bool,
+    ) -> Result<(), sqlx::Error> {
+        let statements: Vec<String> = sqlx::query(match undo {
+            true => "SELECT undo_sql FROM journal_entries WHERE change_id = ? ORDER BY id DESC",
+            false => "SELECT redo_sql FROM journal_entries WHERE change_id = ? ORDER BY id ASC",
+        })
+        .bind(change_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(|row| row.try_get(0))
+        .collect::<Result<_, _>>()?;
+
+        // rows come back in whatever order they went, so only check the keys at the end
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE journal_state SET replaying = 1")
+            .execute(&mut *tx)
+            .await?;
+        for statement in statements {
+            sqlx::query(&statement).execute(&mut *tx).await?;
+        }
+        sqlx::query("UPDATE journal_state SET replaying = 0")
+            .execute(&mut *tx)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::{cascade::DeleteMode, journal::Change, InnerDbState};
+    use crate::models::{
+        filter::{Filter, FilterGroup},
+        gene::{Gene, GeneFieldName},
+        strain_allele::StrainAllele,
+    };
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    fn get_gene(systematic_name: &str) -> Gene {
+        Gene {
+            systematic_name: systematic_name.to_string(),
+            descriptive_name: Some("new-1".to_string()),
+            chromosome: None,
+            phys_loc: Some(6710149),
+            gen_loc: Some(0.25),
+            recomb_suppressor: None,
+        }
+    }
+
+    async fn get_strain_alleles(state: &InnerDbState) -> Result<Vec<StrainAllele>> {
+        let mut strain_alleles = state.get_strain_alleles().await?;
+        strain_alleles.sort_by(|a, b| {
+            (&a.strain_name, &a.allele_name).cmp(&(&b.strain_name, &b.allele_name))
+        });
+        Ok(strain_alleles)
+    }
+
+    fn get_descriptions(changes: &[Change]) -> Vec<(&str, bool, u32)> {
+        changes
+            .iter()
+            .map(|change| (change.description.as_str(), change.undone, change.rows))
+            .collect()
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_undo_redo(pool: Pool<Sqlite>) -> Result<()> {
+        InnerDbState::install_journal(&pool).await?;
+        let state = InnerDbState { conn_pool: pool };
+        let genes = state.get_genes().await?;
+        let alleles = state.get_alleles().await?;
+        let strain_alleles = get_strain_alleles(&state).await?;
+
+        state.insert_gene(&get_gene("Z1")).await?;
+        state.record_change("Insert gene").await?;
+        let genes_with_new = state.get_genes().await?;
+        state
+            .delete_filtered_genes(
+                &FilterGroup::<GeneFieldName> {
+                    filters: vec![vec![(
+                        GeneFieldName::DescName,
+                        Filter::Equal("unc-119".into()),
+                    )]],
+                    order_by: vec![],
+                    limit: None,
+                    offset: None,
+                },
+                DeleteMode::Cascade,
+            )
+            .await?;
+        state.record_change("Delete genes").await?;
+        assert_eq!(
+            get_descriptions(&state.list_recent_changes(10).await?),
+            vec![("Delete genes", false, 5), ("Insert gene", false, 1)]
+        );
+
+        let undone = state.undo_last_change().await?.unwrap();
+        assert_eq!(undone.description, "Delete genes");
+        assert!(undone.undone);
+        assert_eq!(state.get_genes().await?, genes_with_new);
+        assert_eq!(state.get_alleles().await?, alleles);
+        assert_eq!(get_strain_alleles(&state).await?, strain_alleles);
+
+        state.undo_last_change().await?;
+        assert_eq!(state.get_genes().await?, genes);
+        assert_eq!(state.undo_last_change().await?, None);
+
+        let redone = state.redo_change().await?.unwrap();
+        assert_eq!(redone.description, "Insert gene");
+        assert_eq!(state.get_genes().await?, genes_with_new);
+        assert_eq!(state.audit_database(None).await?.problems, vec![]);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_new_change_drops_redo(pool: Pool<Sqlite>) -> Result<()> {
+        InnerDbState::install_journal(&pool).await?;
+        let state = InnerDbState { conn_pool: pool };
+
+        // commands that don't write anything aren't recorded
+        state.record_change("Nothing").await?;
+        state.insert_gene(&get_gene("Z1")).await?;
+        state.record_change("Insert gene").await?;
+        state.undo_last_change().await?;
+        state.insert_gene(&get_gene("Z2")).await?;
+        state.record_change("Insert other gene").await?;
+
+        assert_eq!(
+            get_descriptions(&state.list_recent_changes(10).await?),
+            vec![("Insert other gene", false, 1)]
+        );
+        assert_eq!(state.redo_change().await?, None);
+        Ok(())
+    }
+}
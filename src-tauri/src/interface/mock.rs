@@ -19,4 +19,8 @@ pub mod strain_allele;
 #[cfg(test)]
 pub mod task;
 #[cfg(test)]
+pub mod task_condition;
+#[cfg(test)]
+pub mod task_dependency;
+#[cfg(test)]
 pub mod variation;
@@ -0,0 +1,34 @@
+use crate::models::task_condition::TaskCondition;
+pub fn get_task_conditions() -> Vec<TaskCondition> {
+    vec![
+        TaskCondition {
+            task_id: "1".to_string(),
+            condition_name: "25C".to_string(),
+        },
+        TaskCondition {
+            task_id: "2".to_string(),
+            condition_name: "15C".to_string(),
+        },
+        TaskCondition {
+            task_id: "2".to_string(),
+            condition_name: "Histamine".to_string(),
+        },
+        TaskCondition {
+            task_id: "4".to_string(),
+            condition_name: "25C".to_string(),
+        },
+    ]
+}
+
+pub fn get_filtered_task_conditions() -> Vec<TaskCondition> {
+    vec![
+        TaskCondition {
+            task_id: "1".to_string(),
+            condition_name: "25C".to_string(),
+        },
+        TaskCondition {
+            task_id: "4".to_string(),
+            condition_name: "25C".to_string(),
+        },
+    ]
+}
@@ -0,0 +1,542 @@
+use super::{DbError, InnerDbState, TableRow};
+use crate::models::{
+    filter::{Count, FilterQueryBuilder, Order},
+    FieldNameEnum,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{sqlite::SqliteRow, FromRow, QueryBuilder, Row, Sqlite, TypeInfo, ValueRef};
+use ts_rs::TS;
+
+/// Counting stops after this many rows, past which a page's total is only a lower bound
+const COUNT_LIMIT: u32 = 10_000;
+
+/// Asks for the rows after a cursor, in the order the filter gives
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_PageRequest.ts")]
+#[serde(rename = "db_PageRequest")]
+pub struct PageRequest {
+    /// The `next_cursor` of the previous page, or None for the first page
+    pub cursor: Option<String>,
+    pub limit: u32,
+    /// Whether to also count every row the filter matches
+    pub count: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_RowCount.ts")]
+#[serde(rename = "db_RowCount")]
+pub enum RowCount {
+    Exact(u32),
+    /// Too many rows to count quickly, but at least this many
+    AtLeast(u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export_to = "../src/models/db/db_Page.ts")]
+#[serde(rename = "db_Page")]
+pub struct Page<R> {
+    pub rows: Vec<R>,
+    /// Where the next page starts, or None if this is the last page
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+    pub total: Option<RowCount>,
+}
+
+impl<R> Page<R> {
+    pub fn map<S>(self, f: impl FnMut(R) -> S) -> Page<S> {
+        Page {
+            rows: self.rows.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+
+    pub(super) fn try_map<S, E>(self, f: impl FnMut(R) -> Result<S, E>) -> Result<Page<S>, E> {
+        Ok(Page {
+            rows: self.rows.into_iter().map(f).collect::<Result<_, _>>()?,
+            next_cursor: self.next_cursor,
+            total: self.total,
+        })
+    }
+}
+
+/// What a page is read from, for queries over more than a single table
+pub(super) struct PageSource<'a> {
+    /// The columns each row is read from
+    pub columns: &'a str,
+    /// FROM and any joins
+    pub from: &'a str,
+    /// Columns that tell every row apart, used to break ties
+    pub key_columns: &'a [&'a str],
+}
+
+/// A column pages are ordered by. The filter's ordering comes first, then the primary
+/// key so that no two rows tie.
+struct SortKey {
+    col_name: String,
+    desc: bool,
+    /// Whether the column compares case insensitively, like the filter's ordering does
+    nocase: bool,
+}
+
+impl SortKey {
+    fn get_col_expr(&self) -> String {
+        match self.nocase {
+            true => format!("{} COLLATE NOCASE", self.col_name),
+            false => self.col_name.clone(),
+        }
+    }
+}
+
+fn get_sort_keys<F: FieldNameEnum>(order_by: &[(F, Order)], key_columns: &[&str]) -> Vec<SortKey> {
+    order_by
+        .iter()
+        .map(|(field_name, order)| SortKey {
+            col_name: field_name.get_col_name(),
+            desc: matches!(order, Order::Desc),
+            nocase: true,
+        })
+        .chain(key_columns.iter().map(|col_name| SortKey {
+            col_name: col_name.to_string(),
+            desc: false,
+            nocase: false,
+        }))
+        .collect()
+}
+
+fn page_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Page error: {e}");
+    DbError::Query(e.to_string())
+}
+
+fn cursor_error() -> DbError {
+    DbError::InvalidFilter("the cursor doesn't belong to this query".to_string())
+}
+
+/// The sort key values of the last row of a page, as hex encoded JSON so the frontend
+/// has no reason to look inside
+fn encode_cursor(values: &[Value]) -> String {
+    Value::from(values)
+        .to_string()
+        .bytes()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn decode_cursor(cursor: &str) -> Result<Vec<Value>, DbError> {
+    let chunks = cursor.as_bytes().chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return Err(cursor_error());
+    }
+    let bytes = chunks
+        .map(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(cursor_error)?;
+    serde_json::from_slice(&bytes).map_err(|_| cursor_error())
+}
+
+/// Reads a column as whatever SQLite stored it as
+pub(super) fn get_value(row: &SqliteRow, index: usize) -> Result<Value, sqlx::Error> {
+    let raw = row.try_get_raw(index)?;
+    if raw.is_null() {
+        return Ok(Value::Null);
+    }
+    let type_name = raw.type_info().name().to_string();
+    Ok(match type_name.as_str() {
+        "INTEGER" => row.try_get::<i64, _>(index)?.into(),
+        "REAL" => row.try_get::<f64, _>(index)?.into(),
+        _ => row.try_get::<String, _>(index)?.into(),
+    })
+}
+
+fn push_value_bind(qb: &mut QueryBuilder<Sqlite>, value: &Value) -> Result<(), DbError> {
+    match value {
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => qb.push_bind(n),
+            None => qb.push_bind(n.as_f64().ok_or_else(cursor_error)?),
+        },
+        Value::String(s) => qb.push_bind(s.clone()),
+        _ => return Err(cursor_error()),
+    };
+    Ok(())
+}
+
+/// Rows that sort after the cursor: equal on some leading sort keys, then after it on
+/// the next. SQLite sorts NULL before everything else.
+fn push_after(
+    qb: &mut QueryBuilder<Sqlite>,
+    keys: &[SortKey],
+    values: &[Value],
+) -> Result<(), DbError> {
+    qb.push(" ( 0 ");
+    for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+        // nothing sorts after NULL when descending
+        if key.desc && value.is_null() {
+            continue;
+        }
+        qb.push(" OR ( ");
+        for (equal_key, equal_value) in keys.iter().zip(values).take(i) {
+            if equal_value.is_null() {
+                qb.push(format!("{} IS NULL AND ", equal_key.col_name));
+            } else {
+                qb.push(format!("{} = ", equal_key.get_col_expr()));
+                push_value_bind(qb, equal_value)?;
+                qb.push(" AND ");
+            }
+        }
+        match (key.desc, value.is_null()) {
+            (false, true) => {
+                qb.push(format!("{} IS NOT NULL", key.col_name));
+            }
+            (false, false) => {
+                qb.push(format!("{} > ", key.get_col_expr()));
+                push_value_bind(qb, value)?;
+            }
+            (true, _) => {
+                qb.push(format!("({} < ", key.get_col_expr()));
+                push_value_bind(qb, value)?;
+                qb.push(format!(" OR {} IS NULL)", key.col_name));
+            }
+        }
+        qb.push(" ) ");
+    }
+    qb.push(" ) ");
+    Ok(())
+}
+
+impl InnerDbState {
+    /// Gets a page of the rows matching the filter, in the filter's order with ties
+    /// broken by primary key. The filter's own limit and offset are ignored, and
+    /// unlike an offset, the cursor stays cheap however deep into the table it is.
+    pub async fn get_page<T, F>(
+        &self,
+        filter: &impl FilterQueryBuilder<F>,
+        page: &PageRequest,
+    ) -> Result<Page<T>, DbError>
+    where
+        T: TableRow + for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+        F: FieldNameEnum,
+    {
+        let source = PageSource {
+            columns: &T::COLUMNS.join(", "),
+            from: &format!("FROM {}", T::TABLE),
+            key_columns: T::KEY_COLUMNS,
+        };
+        self.get_row_page(
+            &source,
+            filter.get_order_by(),
+            filter.has_conditions(),
+            |qb| Ok(filter.add_filtered_query(qb, false, false)?),
+            page,
+        )
+        .await?
+        .try_map(|row| T::from_row(&row))
+        .map_err(page_error)
+    }
+
+    /// Like `get_page`, but over any source, with conditions pushed by `push_conditions`
+    /// and rows left for the caller to read
+    pub(super) async fn get_row_page<F: FieldNameEnum>(
+        &self,
+        source: &PageSource<'_>,
+        order_by: &[(F, Order)],
+        has_conditions: bool,
+        push_conditions: impl Fn(&mut QueryBuilder<Sqlite>) -> Result<(), DbError>,
+        page: &PageRequest,
+    ) -> Result<Page<SqliteRow>, DbError> {
+        let keys = get_sort_keys(order_by, source.key_columns);
+        let after = match &page.cursor {
+            Some(cursor) => Some(decode_cursor(cursor)?),
+            None => None,
+        };
+        if matches!(&after, Some(values) if values.len() != keys.len()) {
+            return Err(cursor_error());
+        }
+        let limit = page.limit.max(1);
+
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(format!("SELECT {}", source.columns));
+        for (i, key) in keys.iter().enumerate() {
+            qb.push(format!(", {} AS page_key_{i}", key.col_name));
+        }
+        qb.push(format!(" {} ", source.from));
+        if has_conditions {
+            qb.push(" WHERE ( ");
+            push_conditions(&mut qb)?;
+            qb.push(" ) ");
+        }
+        if let Some(values) = &after {
+            qb.push(match has_conditions {
+                true => " AND ",
+                false => " WHERE ",
+            });
+            push_after(&mut qb, &keys, values)?;
+        }
+        qb.push(" ORDER BY ");
+        let mut qb_separated = qb.separated(", ");
+        for key in keys.iter() {
+            qb_separated.push(format!(
+                "{} {}",
+                key.get_col_expr(),
+                if key.desc { "DESC" } else { "ASC" }
+            ));
+        }
+        // one more than asked for, to tell whether there's another page
+        qb.push(" LIMIT ");
+        qb.push_bind(limit + 1);
+
+        let mut rows = qb
+            .build()
+            .fetch_all(&self.conn_pool)
+            .await
+            .map_err(page_error)?;
+
+        let next_cursor = if rows.len() > limit as usize {
+            rows.truncate(limit as usize);
+            let last = rows.last().ok_or_else(cursor_error)?;
+            // the sort keys come after the columns
+            let first_key = last.len() - keys.len();
+            let values = (0..keys.len())
+                .map(|i| get_value(last, first_key + i))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(page_error)?;
+            Some(encode_cursor(&values))
+        } else {
+            None
+        };
+
+        let total = match page.count {
+            true => Some(
+                self.get_row_count(source.from, has_conditions, &push_conditions)
+                    .await?,
+            ),
+            false => None,
+        };
+
+        Ok(Page {
+            rows,
+            next_cursor,
+            total,
+        })
+    }
+
+    /// Counts the rows matching the conditions, stopping early on huge tables
+    async fn get_row_count(
+        &self,
+        from: &str,
+        has_conditions: bool,
+        push_conditions: &impl Fn(&mut QueryBuilder<Sqlite>) -> Result<(), DbError>,
+    ) -> Result<RowCount, DbError> {
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new(format!("SELECT COUNT(*) AS count FROM (SELECT 1 {from}"));
+        if has_conditions {
+            qb.push(" WHERE ");
+            push_conditions(&mut qb)?;
+        }
+        qb.push(" LIMIT ");
+        qb.push_bind(COUNT_LIMIT + 1);
+        qb.push(")");
+
+        let count = qb
+            .build_query_as::<Count>()
+            .fetch_one(&self.conn_pool)
+            .await
+            .map_err(page_error)?
+            .count;
+        Ok(match count > COUNT_LIMIT {
+            true => RowCount::AtLeast(COUNT_LIMIT),
+            false => RowCount::Exact(count),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::page::{PageRequest, RowCount};
+    use crate::interface::DbError;
+    use crate::models::filter::{Filter, FilterGroup, Order};
+    use crate::models::gene::{Gene, GeneFieldName};
+    use crate::InnerDbState;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    fn get_filter(
+        filters: Vec<Vec<(GeneFieldName, Filter)>>,
+        order_by: Vec<(GeneFieldName, Order)>,
+    ) -> FilterGroup<GeneFieldName> {
+        FilterGroup::<GeneFieldName> {
+            filters,
+            order_by,
+            limit: Some(1),
+            offset: Some(1),
+        }
+    }
+
+    /// The systematic names on each page, following the cursors to the end
+    async fn get_pages(
+        state: &InnerDbState,
+        filter: &FilterGroup<GeneFieldName>,
+        limit: u32,
+    ) -> Result<Vec<Vec<String>>> {
+        let mut pages = vec![];
+        let mut cursor = None;
+        loop {
+            let page = state
+                .get_filtered_genes_page(
+                    filter,
+                    &PageRequest {
+                        cursor,
+                        limit,
+                        count: false,
+                    },
+                )
+                .await?;
+            pages.push(
+                page.rows
+                    .into_iter()
+                    .map(|gene| gene.systematic_name)
+                    .collect(),
+            );
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                return Ok(pages);
+            }
+        }
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_page(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = get_filter(
+            vec![vec![(
+                GeneFieldName::Chromosome,
+                Filter::Equal("IV".into()),
+            )]],
+            vec![(GeneFieldName::GeneticLoc, Order::Desc)],
+        );
+
+        let page = state
+            .get_filtered_genes_page(
+                &filter,
+                &PageRequest {
+                    cursor: None,
+                    limit: 3,
+                    count: true,
+                },
+            )
+            .await?;
+        assert_eq!(page.total, Some(RowCount::Exact(4)));
+        assert_eq!(
+            page.rows
+                .iter()
+                .map(|gene| gene.descriptive_name.clone().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["dpy-20", "kin-4", "fbl-1"]
+        );
+
+        let page = state
+            .get_filtered_genes_page(
+                &filter,
+                &PageRequest {
+                    cursor: page.next_cursor,
+                    limit: 3,
+                    count: false,
+                },
+            )
+            .await?;
+        assert_eq!(page.total, None);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.rows[0].descriptive_name, Some("unc-24".to_string()));
+        assert_eq!(page.rows.len(), 1);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_pages_with_ties_and_nulls(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        state
+            .insert_gene(&Gene {
+                systematic_name: "Y1A1.1".to_string(),
+                descriptive_name: None,
+                chromosome: None,
+                phys_loc: None,
+                gen_loc: None,
+                recomb_suppressor: None,
+            })
+            .await?;
+
+        // ties on chromosome go by systematic name, and NULL comes first
+        let filter = get_filter(vec![], vec![(GeneFieldName::Chromosome, Order::Asc)]);
+        assert_eq!(
+            get_pages(&state, &filter, 3).await?,
+            vec![
+                vec!["Y1A1.1", "T14B4.7", "M142.1"],
+                vec!["C10C6.1", "F56H11.1", "F57H12.2"],
+                vec!["T22B3.1", "F27D9.1", "ZK662.4"],
+            ]
+        );
+
+        // and last when descending
+        let filter = get_filter(vec![], vec![(GeneFieldName::Chromosome, Order::Desc)]);
+        assert_eq!(
+            get_pages(&state, &filter, 4).await?,
+            vec![
+                vec!["F27D9.1", "ZK662.4", "C10C6.1", "F56H11.1"],
+                vec!["F57H12.2", "T22B3.1", "M142.1", "T14B4.7"],
+                vec!["Y1A1.1"],
+            ]
+        );
+
+        // no ordering at all still pages through every row once
+        let filter = get_filter(vec![], vec![]);
+        assert_eq!(
+            get_pages(&state, &filter, 5).await?,
+            vec![
+                vec!["C10C6.1", "F27D9.1", "F56H11.1", "F57H12.2", "M142.1"],
+                vec!["T14B4.7", "T22B3.1", "Y1A1.1", "ZK662.4"],
+            ]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_page_bad_cursor(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let by_name = get_filter(vec![], vec![(GeneFieldName::DescName, Order::Asc)]);
+        let page = state
+            .get_filtered_genes_page(
+                &by_name,
+                &PageRequest {
+                    cursor: None,
+                    limit: 2,
+                    count: false,
+                },
+            )
+            .await?;
+
+        for cursor in [
+            page.next_cursor.unwrap(),
+            "zz".to_string(),
+            "5b5d".to_string(),
+        ] {
+            // a cursor from a differently ordered query, or not one at all
+            let res = state
+                .get_filtered_genes_page(
+                    &get_filter(vec![], vec![]),
+                    &PageRequest {
+                        cursor: Some(cursor),
+                        limit: 2,
+                        count: false,
+                    },
+                )
+                .await;
+            assert!(matches!(res, Err(DbError::InvalidFilter(_))));
+        }
+        Ok(())
+    }
+}
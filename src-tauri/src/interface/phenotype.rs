@@ -1,11 +1,53 @@
-use super::{bulk::Bulk, DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
     expr_relation::ExpressionRelationFieldName,
-    filter::{Count, FilterGroup, FilterQueryBuilder},
+    filter::{Count, FilterQueryBuilder},
     phenotype::{Phenotype, PhenotypeDb, PhenotypeFieldName},
 };
 use anyhow::Result;
+use csv::Writer;
+use serde_json::{json, Value};
 use sqlx::{QueryBuilder, Sqlite};
+use std::io::Write;
+
+impl TableRow for PhenotypeDb {
+    const TABLE: &'static str = "phenotypes";
+    const COLUMNS: &'static [&'static str] = &[
+        "name",
+        "wild",
+        "short_name",
+        "description",
+        "male_mating",
+        "lethal",
+        "female_sterile",
+        "arrested",
+        "maturation_days",
+    ];
+    const KEY_COLUMNS: &'static [&'static str] = &["name", "wild"];
+}
+
+impl BulkInsertable for PhenotypeDb {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.name),
+            json!(self.wild),
+            json!(self.short_name),
+            json!(self.description),
+            json!(self.male_mating),
+            json!(self.lethal),
+            json!(self.female_sterile),
+            json!(self.arrested),
+            json!(self.maturation_days),
+        ]
+    }
+}
 
 impl InnerDbState {
     pub async fn get_phenotypes(&self) -> Result<Vec<Phenotype>, DbError> {
@@ -42,7 +84,7 @@ impl InnerDbState {
 
     pub async fn get_filtered_phenotypes(
         &self,
-        filter: &FilterGroup<PhenotypeFieldName>,
+        filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
     ) -> Result<Vec<Phenotype>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT
@@ -57,7 +99,7 @@ impl InnerDbState {
                 maturation_days
             FROM phenotypes",
         );
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
         match qb
             .build_query_as::<PhenotypeDb>()
             .fetch_all(&self.conn_pool)
@@ -71,13 +113,45 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_phenotypes_page(
+        &self,
+        filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<Phenotype>, DbError> {
+        Ok(self
+            .get_page::<PhenotypeDb, _>(filter, page)
+            .await?
+            .map(Phenotype::from))
+    }
+
+    pub async fn query_phenotypes(
+        &self,
+        filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
+        facets: Vec<PhenotypeFieldName>,
+    ) -> Result<QueryResult<Phenotype, PhenotypeFieldName>, DbError> {
+        Ok(self
+            .query::<PhenotypeDb, _>(filter, facets)
+            .await?
+            .map(Phenotype::from))
+    }
+
+    pub async fn aggregate_phenotypes(
+        &self,
+        filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
+        group_by: &PhenotypeFieldName,
+        metrics: &[Metric<PhenotypeFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("phenotypes", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_phenotypes(
         &self,
-        filter: &FilterGroup<PhenotypeFieldName>,
+        filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT COUNT(*) as count FROM phenotypes");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -94,8 +168,8 @@ impl InnerDbState {
 
     pub async fn get_altering_phenotypes(
         &self,
-        expr_relation_filter: &FilterGroup<ExpressionRelationFieldName>,
-        phenotype_filter: &FilterGroup<PhenotypeFieldName>,
+        expr_relation_filter: &impl FilterQueryBuilder<ExpressionRelationFieldName>,
+        phenotype_filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
     ) -> Result<Vec<Phenotype>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT DISTINCT
@@ -116,13 +190,13 @@ impl InnerDbState {
                 FROM
                     expr_relations",
         );
-        expr_relation_filter.add_filtered_query(&mut qb, true, true);
+        expr_relation_filter.add_filtered_query(&mut qb, true, true)?;
         qb.push(
             ") JOIN phenotypes AS p 
             ON p.name == pn
             AND p.wild == pw\n",
         );
-        phenotype_filter.add_filtered_query(&mut qb, true, true);
+        phenotype_filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<PhenotypeDb>()
@@ -163,75 +237,51 @@ impl InnerDbState {
         }
     }
 
-    pub async fn insert_phenotypes(&self, bulk: Bulk<PhenotypeDb>) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.len()
-            )));
-        }
-        let bind_limit = SQLITE_BIND_LIMIT / 9;
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-                "INSERT OR IGNORE INTO phenotypes (name, wild, short_name, description, male_mating, lethal, female_sterile, arrested, maturation_days) "
-            );
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.name)
-                    .push_bind(item.wild)
-                    .push_bind(item.short_name)
-                    .push_bind(item.description)
-                    .push_bind(item.male_mating)
-                    .push_bind(item.lethal)
-                    .push_bind(item.female_sterile)
-                    .push_bind(item.arrested)
-                    .push_bind(item.maturation_days);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk Insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            }
-        }
-        Ok(())
+    pub async fn insert_phenotypes(
+        &self,
+        bulk: Bulk<PhenotypeDb>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
     }
 
-    pub async fn delete_filtered_phenotypes(
+    pub async fn export_filtered_phenotypes<W: Write>(
         &self,
-        filter: &FilterGroup<PhenotypeFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM phenotypes");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<PhenotypeDb, _, _>(filter, writer).await
+    }
 
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete Gene error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+    /// Counts the rows in each table that deleting the filtered phenotypes would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_phenotypes(
+        &self,
+        filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("phenotypes", filter).await
+    }
+
+    pub async fn delete_filtered_phenotypes(
+        &self,
+        filter: &impl FilterQueryBuilder<PhenotypeFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("phenotypes", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
     use std::io::BufReader;
 
-    use crate::interface::bulk::Bulk;
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
     use crate::interface::mock;
     use crate::models::expr_relation::ExpressionRelationFieldName;
-    use crate::models::filter::{Filter, FilterGroup, Order};
+    use crate::models::filter::{Filter, FilterGroup, FilterValue, Order};
     use crate::models::phenotype::{Phenotype, PhenotypeDb, PhenotypeFieldName};
     use crate::InnerDbState;
     use anyhow::Result;
@@ -256,7 +306,7 @@ mod test {
                 filters: vec![
                     vec![(
                         PhenotypeFieldName::MaleMating,
-                        Filter::LessThan("2".to_owned(), false),
+                        Filter::LessThan(FilterValue::Int(2), false),
                     )],
                     vec![(PhenotypeFieldName::MaturationDays, Filter::Null)],
                 ],
@@ -277,7 +327,7 @@ mod test {
             .get_filtered_phenotypes(&FilterGroup::<PhenotypeFieldName> {
                 filters: vec![vec![(
                     PhenotypeFieldName::MaturationDays,
-                    Filter::LessThan("3".to_owned(), true),
+                    Filter::LessThan(FilterValue::Real(3.0), true),
                 )]],
                 order_by: vec![(PhenotypeFieldName::Name, Order::Asc)],
                 limit: None,
@@ -317,11 +367,11 @@ mod test {
             filters: vec![
                 vec![(
                     ExpressionRelationFieldName::AlleleName,
-                    Filter::Equal("oxIs644".to_owned()),
+                    Filter::Equal("oxIs644".into()),
                 )],
                 vec![(
                     ExpressionRelationFieldName::ExpressingPhenotypeName,
-                    Filter::Equal("YFP(pharynx)".to_owned()),
+                    Filter::Equal("YFP(pharynx)".into()),
                 )],
                 vec![(
                     ExpressionRelationFieldName::ExpressingPhenotypeWild,
@@ -339,7 +389,7 @@ mod test {
                 vec![(PhenotypeFieldName::Wild, Filter::True)],
                 vec![(
                     PhenotypeFieldName::MaturationDays,
-                    Filter::GreaterThan("3".to_owned(), true),
+                    Filter::GreaterThan(FilterValue::Real(3.0), true),
                 )],
             ],
             order_by: vec![],
@@ -393,7 +443,9 @@ unc-5,0,unc,,0,0,0,0,4"
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<PhenotypeDb> = Bulk::from_reader(&mut reader);
 
-        state.insert_phenotypes(bulk).await?;
+        state
+            .insert_phenotypes(bulk, ConflictPolicy::Ignore, false)
+            .await?;
         let expected = vec![
             Phenotype {
                 name: "unc-18".to_string(),
@@ -441,7 +493,7 @@ unc-5,0,unc,,0,0,0,0,4"
 
         let delete_filter = &FilterGroup::<PhenotypeFieldName> {
             filters: vec![
-                vec![(PhenotypeFieldName::Name, Filter::Equal("NeoR".to_string()))],
+                vec![(PhenotypeFieldName::Name, Filter::Equal("NeoR".into()))],
                 vec![(PhenotypeFieldName::Wild, Filter::False)],
             ],
             order_by: vec![],
@@ -449,7 +501,9 @@ unc-5,0,unc,,0,0,0,0,4"
             offset: None,
         };
 
-        state.delete_filtered_phenotypes(delete_filter).await?;
+        state
+            .delete_filtered_phenotypes(delete_filter, DeleteMode::Restrict)
+            .await?;
 
         phenotypes = state.get_phenotypes().await?;
         assert_eq!(
@@ -482,7 +536,9 @@ unc-5,0,unc,,0,0,0,0,4"
         let filtered_len = phenotypes.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_phenotypes(filter).await?;
+        state
+            .delete_filtered_phenotypes(filter, DeleteMode::Restrict)
+            .await?;
         phenotypes = state.get_phenotypes().await?;
 
         assert_eq!(phenotypes.len(), orig_len - filtered_len);
@@ -498,14 +554,14 @@ unc-5,0,unc,,0,0,0,0,4"
         let mut phenotypes: Vec<Phenotype> = state.get_phenotypes().await?;
         assert_eq!(phenotypes.len(), mock::phenotype::get_phenotypes().len());
 
-        let filter = &FilterGroup::<PhenotypeFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_phenotypes(filter).await?;
+        let preview = state.preview_delete_all(DeletableTable::Phenotypes).await?;
+        state
+            .delete_all(
+                DeletableTable::Phenotypes,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         phenotypes = state.get_phenotypes().await?;
 
         assert_eq!(phenotypes.len(), 0);
@@ -0,0 +1,282 @@
+use super::{page::get_value, DbError, InnerDbState, TableRow};
+use crate::models::{
+    filter::{Count, FilterQueryBuilder},
+    FieldNameEnum,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{sqlite::SqliteRow, FromRow, QueryBuilder, Row, Sqlite};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_FacetCount.ts")]
+#[serde(rename = "db_FacetCount")]
+pub struct FacetCount {
+    #[ts(type = "string | number | null")]
+    pub value: Value,
+    pub count: u32,
+}
+
+/// How many of the filtered rows have each value of a column
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export_to = "../src/models/db/db_Facet.ts")]
+#[serde(rename = "db_Facet")]
+pub struct Facet<F> {
+    pub field: F,
+    pub counts: Vec<FacetCount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export_to = "../src/models/db/db_QueryResult.ts")]
+#[serde(rename = "db_QueryResult")]
+pub struct QueryResult<R, F> {
+    pub rows: Vec<R>,
+    /// Every row matching the filter, ignoring its limit and offset
+    pub total: u32,
+    /// In the order they were asked for
+    pub facets: Vec<Facet<F>>,
+}
+
+impl<R, F> QueryResult<R, F> {
+    pub fn map<S>(self, f: impl FnMut(R) -> S) -> QueryResult<S, F> {
+        QueryResult {
+            rows: self.rows.into_iter().map(f).collect(),
+            total: self.total,
+            facets: self.facets,
+        }
+    }
+}
+
+fn query_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Query error: {e}");
+    DbError::Query(e.to_string())
+}
+
+impl InnerDbState {
+    /// Gets the filtered rows along with how many match in all, and how many of those
+    /// have each value of the facet columns. It's all read in one transaction, so the
+    /// counts can't disagree with the rows.
+    pub async fn query<T, F>(
+        &self,
+        filter: &impl FilterQueryBuilder<F>,
+        facets: Vec<F>,
+    ) -> Result<QueryResult<T, F>, DbError>
+    where
+        T: TableRow + for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+        F: FieldNameEnum,
+    {
+        self.query_from(
+            &T::COLUMNS.join(", "),
+            &format!("FROM {}", T::TABLE),
+            filter,
+            facets,
+        )
+        .await
+    }
+
+    /// Like `query`, but over any FROM clause, so the filter and facets can use joined
+    /// columns. The joins mustn't repeat rows, or they'd be counted more than once.
+    pub(super) async fn query_from<T, F>(
+        &self,
+        columns: &str,
+        from: &str,
+        filter: &impl FilterQueryBuilder<F>,
+        facets: Vec<F>,
+    ) -> Result<QueryResult<T, F>, DbError>
+    where
+        T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+        F: FieldNameEnum,
+    {
+        let mut tx = self.conn_pool.begin().await.map_err(query_error)?;
+
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(format!("SELECT {columns} {from}"));
+        filter.add_filtered_query(&mut qb, true, true)?;
+        let rows = qb
+            .build_query_as::<T>()
+            .fetch_all(&mut tx)
+            .await
+            .map_err(query_error)?;
+
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new(format!("SELECT COUNT(*) AS count {from}"));
+        filter.add_filtered_query(&mut qb, true, false)?;
+        let total = qb
+            .build_query_as::<Count>()
+            .fetch_one(&mut tx)
+            .await
+            .map_err(query_error)?
+            .count;
+
+        let mut facet_counts = vec![];
+        for field in facets {
+            let col_name = field.get_col_name();
+            let mut qb: QueryBuilder<Sqlite> =
+                QueryBuilder::new(format!("SELECT {col_name}, COUNT(*) AS count {from}"));
+            if filter.has_conditions() {
+                qb.push(" WHERE ");
+                filter.add_filtered_query(&mut qb, false, false)?;
+            }
+            qb.push(format!(" GROUP BY {col_name} ORDER BY {col_name}"));
+
+            let counts = qb
+                .build()
+                .fetch_all(&mut tx)
+                .await
+                .map_err(query_error)?
+                .iter()
+                .map(|row| {
+                    Ok(FacetCount {
+                        value: get_value(row, 0)?,
+                        count: row.try_get("count")?,
+                    })
+                })
+                .collect::<Result<_, sqlx::Error>>()
+                .map_err(query_error)?;
+            facet_counts.push(Facet { field, counts });
+        }
+
+        tx.commit().await.map_err(query_error)?;
+        Ok(QueryResult {
+            rows,
+            total,
+            facets: facet_counts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::query::{Facet, FacetCount};
+    use crate::models::allele::{AlleleFieldName, AlleleJoinFieldName};
+    use crate::models::filter::{Filter, FilterGroup, Order};
+    use crate::models::gene::GeneFieldName;
+    use crate::models::phenotype::PhenotypeFieldName;
+    use crate::models::variation::VariationFieldName;
+    use crate::InnerDbState;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use sqlx::{Pool, Sqlite};
+
+    fn get_counts(counts: Vec<(serde_json::Value, u32)>) -> Vec<FacetCount> {
+        counts
+            .into_iter()
+            .map(|(value, count)| FacetCount { value, count })
+            .collect()
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_query_genes(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = FilterGroup::<GeneFieldName> {
+            filters: vec![],
+            order_by: vec![(GeneFieldName::SysName, Order::Asc)],
+            limit: Some(2),
+            offset: None,
+        };
+
+        let res = state
+            .query_genes(&filter, vec![GeneFieldName::Chromosome])
+            .await?;
+        assert_eq!(
+            res.rows
+                .into_iter()
+                .map(|gene| gene.systematic_name)
+                .collect::<Vec<_>>(),
+            vec!["C10C6.1", "F27D9.1"]
+        );
+        assert_eq!(res.total, 8);
+        assert_eq!(
+            res.facets,
+            vec![Facet {
+                field: GeneFieldName::Chromosome,
+                counts: get_counts(vec![
+                    (json!("II"), 1),
+                    (json!("III"), 1),
+                    (json!("IV"), 4),
+                    (json!("X"), 2)
+                ]),
+            }]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_query_phenotypes_facets_follow_filter(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = FilterGroup::<PhenotypeFieldName> {
+            filters: vec![vec![(PhenotypeFieldName::Name, Filter::Like("GFP".into()))]],
+            order_by: vec![],
+            limit: Some(0),
+            offset: None,
+        };
+
+        let res = state
+            .query_phenotypes(&filter, vec![PhenotypeFieldName::Wild])
+            .await?;
+        // the limit only applies to the rows
+        assert_eq!(res.rows, vec![]);
+        assert_eq!(res.total, 3);
+        assert_eq!(res.facets[0].counts, get_counts(vec![(json!(0), 3)]));
+
+        let res = state.query_phenotypes(&filter, vec![]).await?;
+        assert_eq!(res.facets, vec![]);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_query_joined_alleles_facets(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = FilterGroup::<AlleleJoinFieldName> {
+            filters: vec![vec![(
+                AlleleJoinFieldName::Allele(AlleleFieldName::Name),
+                Filter::Like("ox".into()),
+            )]],
+            order_by: vec![(
+                AlleleJoinFieldName::Allele(AlleleFieldName::Name),
+                Order::Asc,
+            )],
+            limit: Some(1),
+            offset: None,
+        };
+
+        let res = state
+            .query_joined_alleles(
+                &filter,
+                vec![
+                    AlleleJoinFieldName::Gene(GeneFieldName::Chromosome),
+                    AlleleJoinFieldName::Variation(VariationFieldName::Chromosome),
+                ],
+            )
+            .await?;
+        assert_eq!(
+            res.rows
+                .into_iter()
+                .map(|allele| allele.name)
+                .collect::<Vec<_>>(),
+            vec!["ox1059"]
+        );
+        assert_eq!(res.total, 9);
+        assert_eq!(
+            res.facets,
+            vec![
+                Facet {
+                    field: AlleleJoinFieldName::Gene(GeneFieldName::Chromosome),
+                    counts: get_counts(vec![(json!(null), 8), (json!("IV"), 1)]),
+                },
+                Facet {
+                    field: AlleleJoinFieldName::Variation(VariationFieldName::Chromosome),
+                    counts: get_counts(vec![
+                        (json!(null), 2),
+                        (json!("Ex"), 2),
+                        (json!("I"), 1),
+                        (json!("II"), 2),
+                        (json!("IV"), 1),
+                        (json!("X"), 1)
+                    ]),
+                }
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use libsqlite3_sys::{
+    sqlite3_context, sqlite3_create_function_v2, sqlite3_get_auxdata, sqlite3_result_error,
+    sqlite3_result_int, sqlite3_result_null, sqlite3_set_auxdata, sqlite3_value,
+    sqlite3_value_text, SQLITE_DETERMINISTIC, SQLITE_OK, SQLITE_UTF8,
+};
+use regex::Regex;
+use sqlx::SqliteConnection;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int, c_void},
+};
+
+/// Registers the `regexp` function, which SQLite calls for `x REGEXP pattern`
+pub async fn register(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    let name = CString::new("regexp").unwrap_or_default();
+    let mut handle = conn.lock_handle().await?;
+    let res = unsafe {
+        sqlite3_create_function_v2(
+            handle.as_raw_handle().as_ptr(),
+            name.as_ptr(),
+            2,
+            SQLITE_UTF8 | SQLITE_DETERMINISTIC,
+            std::ptr::null_mut(),
+            Some(regexp),
+            None,
+            None,
+            None,
+        )
+    };
+    match res {
+        SQLITE_OK => Ok(()),
+        code => Err(sqlx::Error::Configuration(
+            format!("Failed to register regexp function: {code}").into(),
+        )),
+    }
+}
+
+unsafe fn get_text<'a>(value: *mut sqlite3_value) -> Option<&'a str> {
+    let text = sqlite3_value_text(value);
+    match text.is_null() {
+        true => None,
+        false => CStr::from_ptr(text as *const c_char).to_str().ok(),
+    }
+}
+
+unsafe extern "C" fn drop_regex(regex: *mut c_void) {
+    drop(Box::from_raw(regex as *mut Regex));
+}
+
+/// Called with the pattern first, then the value. The compiled pattern is kept as
+/// auxiliary data, so it's only compiled once per statement.
+unsafe extern "C" fn regexp(ctx: *mut sqlite3_context, argc: c_int, argv: *mut *mut sqlite3_value) {
+    let args = std::slice::from_raw_parts(argv, argc as usize);
+    let value = match get_text(args[1]) {
+        Some(value) => value,
+        None => return sqlite3_result_null(ctx),
+    };
+
+    let cached = sqlite3_get_auxdata(ctx, 0) as *const Regex;
+    if !cached.is_null() {
+        return sqlite3_result_int(ctx, (*cached).is_match(value) as c_int);
+    }
+    let regex = match get_text(args[0]).map(Regex::new) {
+        Some(Ok(regex)) => regex,
+        Some(Err(e)) => {
+            let message = e.to_string();
+            return sqlite3_result_error(
+                ctx,
+                message.as_ptr() as *const c_char,
+                message.len() as c_int,
+            );
+        }
+        None => return sqlite3_result_null(ctx),
+    };
+    sqlite3_result_int(ctx, regex.is_match(value) as c_int);
+    sqlite3_set_auxdata(
+        ctx,
+        0,
+        Box::into_raw(Box::new(regex)) as *mut c_void,
+        Some(drop_regex),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::regexp::register;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{sqlite::SqlitePoolOptions, Row};
+
+    #[tokio::test]
+    async fn test_regexp() -> Result<()> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .after_connect(|conn, _| Box::pin(register(conn)))
+            .connect("sqlite::memory:")
+            .await?;
+
+        let row = sqlx::query(
+            "SELECT 'unc-119' REGEXP '^unc-119$', 'unc-119' REGEXP '^UNC', 'unc-119' REGEXP '(?i)^UNC'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(
+            (
+                row.try_get::<bool, _>(0)?,
+                row.try_get::<bool, _>(1)?,
+                row.try_get::<bool, _>(2)?
+            ),
+            (true, false, true)
+        );
+        assert!(sqlx::query("SELECT 'unc-119' REGEXP '('")
+            .fetch_one(&pool)
+            .await
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use super::{DbError, InnerDbState};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use ts_rs::TS;
+
+/// Marks the matched terms in a hit's snippet
+const SNIPPET_START: &str = "«";
+const SNIPPET_END: &str = "»";
+/// Words of context around the matched terms
+const SNIPPET_TOKENS: u32 = 12;
+
+/// Which row a search hit is, by the kind of entity and its primary key
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/db_SearchKey.ts")]
+#[serde(rename = "db_SearchKey")]
+pub enum SearchKey {
+    /// Systematic name
+    Gene(String),
+    Allele(String),
+    Strain(String),
+    /// Name and whether it's the wild type
+    Phenotype(String, bool),
+    /// Id
+    CrossDesign(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_SearchHit.ts")]
+#[serde(rename = "db_SearchHit")]
+pub struct SearchHit {
+    pub key: SearchKey,
+    /// The best matching part of the name or description, with the matched terms
+    /// between « and »
+    pub snippet: String,
+}
+
+fn search_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Search error: {e}");
+    DbError::Query(e.to_string())
+}
+
+/// Turns what the user typed into an FTS5 query that matches rows containing every
+/// word, the last token of each as a prefix. Quoting each word keeps FTS5's operators
+/// and punctuation from meaning anything.
+fn get_match_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl InnerDbState {
+    /// Searches the names and descriptions of genes, alleles, strains, phenotypes and
+    /// cross designs, best matches first. Matches on names rank above matches on
+    /// descriptions.
+    pub async fn global_search(&self, query: &str, limit: u32) -> Result<Vec<SearchHit>, DbError> {
+        let match_query = get_match_query(query);
+        if match_query.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = sqlx::query(
+            "SELECT kind, key, wild, snippet(search_index, -1, ?, ?, '…', ?) AS snippet
+            FROM search_index
+            WHERE search_index MATCH ?
+            ORDER BY bm25(search_index, 0.0, 0.0, 0.0, 10.0, 1.0)
+            LIMIT ?",
+        )
+        .bind(SNIPPET_START)
+        .bind(SNIPPET_END)
+        .bind(SNIPPET_TOKENS)
+        .bind(match_query)
+        .bind(limit)
+        .fetch_all(&self.conn_pool)
+        .await
+        .map_err(search_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let kind: String = row.try_get("kind").map_err(search_error)?;
+                let key: String = row.try_get("key").map_err(search_error)?;
+                let key = match kind.as_str() {
+                    "gene" => SearchKey::Gene(key),
+                    "allele" => SearchKey::Allele(key),
+                    "strain" => SearchKey::Strain(key),
+                    "phenotype" => {
+                        SearchKey::Phenotype(key, row.try_get("wild").map_err(search_error)?)
+                    }
+                    "cross_design" => SearchKey::CrossDesign(key),
+                    _ => return Err(search_error(format!("unknown kind of hit: {kind}"))),
+                };
+                Ok(SearchHit {
+                    key,
+                    snippet: row.try_get("snippet").map_err(search_error)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::search::{get_match_query, SearchHit, SearchKey};
+    use crate::models::gene::Gene;
+    use crate::models::strain::Strain;
+    use crate::InnerDbState;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+    use std::time::{Duration, Instant};
+
+    fn get_keys(hits: Vec<SearchHit>) -> Vec<SearchKey> {
+        hits.into_iter().map(|hit| hit.key).collect()
+    }
+
+    async fn search_keys(state: &InnerDbState, query: &str) -> Result<Vec<SearchKey>> {
+        Ok(get_keys(state.global_search(query, 10).await?))
+    }
+
+    #[test]
+    fn test_get_match_query() {
+        assert_eq!(
+            get_match_query("  unc-119  e128 "),
+            "\"unc-119\"* \"e128\"*"
+        );
+        assert_eq!(
+            get_match_query("say \"hi\" OR"),
+            "\"say\"* \"\"\"hi\"\"\"* \"OR\"*"
+        );
+        assert_eq!(get_match_query(" "), "");
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_global_search(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        // the gene's name ranks above strains that only mention it
+        let hits = state.global_search("unc-24", 10).await?;
+        assert_eq!(
+            get_keys(hits.clone()),
+            vec![
+                SearchKey::Gene("F57H12.2".to_string()),
+                SearchKey::Strain("BT14".to_string())
+            ]
+        );
+        assert_eq!(
+            hits[1].snippet,
+            "BT14 fbl-1(hd43)/dpy-20(e1282) «unc-24»(e138) IV."
+        );
+
+        let hits = state.global_search("lethal", 10).await?;
+        assert_eq!(
+            get_keys(hits),
+            vec![SearchKey::Phenotype("lethal".to_string(), false)]
+        );
+
+        assert_eq!(state.global_search("", 10).await?, vec![]);
+        assert_eq!(state.global_search("\"", 10).await?, vec![]);
+        assert_eq!(state.global_search("unc", 3).await?.len(), 3);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_global_search_follows_changes(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        state
+            .insert_strain(&Strain {
+                name: "XY1".to_string(),
+                genotype: "zzz-1(xy1) I.".to_string(),
+                description: None,
+            })
+            .await?;
+        assert_eq!(
+            search_keys(&state, "zzz").await?,
+            vec![SearchKey::Strain("XY1".to_string())]
+        );
+
+        state
+            .update_strain(
+                "XY1".to_string(),
+                Strain {
+                    name: "XY2".to_string(),
+                    genotype: "yyy-1(xy1) I.".to_string(),
+                    description: None,
+                },
+            )
+            .await?;
+        assert_eq!(search_keys(&state, "zzz").await?, vec![]);
+        assert_eq!(
+            search_keys(&state, "yyy").await?,
+            vec![SearchKey::Strain("XY2".to_string())]
+        );
+
+        state
+            .insert_gene(&Gene {
+                systematic_name: "Y1A1.1".to_string(),
+                descriptive_name: Some("yyy-1".to_string()),
+                chromosome: None,
+                phys_loc: None,
+                gen_loc: None,
+                recomb_suppressor: None,
+            })
+            .await?;
+        sqlx::query("DELETE FROM strains WHERE name = 'XY2'")
+            .execute(&state.conn_pool)
+            .await?;
+        assert_eq!(
+            search_keys(&state, "yyy").await?,
+            vec![SearchKey::Gene("Y1A1.1".to_string())]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_global_search_bulk_delete(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        sqlx::query(
+            "WITH RECURSIVE n (i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 5000)
+            INSERT INTO alleles (name, contents) SELECT 'bulk' || i, 'bulk allele' FROM n",
+        )
+        .execute(&state.conn_pool)
+        .await?;
+        assert_eq!(state.global_search("bulk", 10).await?.len(), 10);
+
+        // each row's index entry is found by rowid, so this doesn't scan the index per row
+        let start = Instant::now();
+        sqlx::query("DELETE FROM alleles WHERE contents = 'bulk allele'")
+            .execute(&state.conn_pool)
+            .await?;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(search_keys(&state, "bulk").await?, vec![]);
+        assert_eq!(
+            search_keys(&state, "e1282").await?,
+            vec![
+                SearchKey::Allele("e1282".to_string()),
+                SearchKey::Strain("BT14".to_string())
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use super::{
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    DbError, InnerDbState,
+};
+use crate::models::{
+    allele::Allele, allele_expr::AlleleExpressionDb, condition::ConditionDb,
+    expr_relation::ExpressionRelationDb, gene::GeneDb, phenotype::PhenotypeDb, strain::Strain,
+    strain_allele::StrainAllele, variation::VariationDb,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, Transaction};
+use ts_rs::TS;
+
+// The reference set in data/ is built into the app, so seeding doesn't depend on
+// where the files end up after bundling
+const GENES: &str = include_str!("../../../data/genes.csv");
+const VARIATIONS: &str = include_str!("../../../data/variations.csv");
+const ALLELES: &str = include_str!("../../../data/alleles.csv");
+const PHENOTYPES: &str = include_str!("../../../data/phenotypes.csv");
+const CONDITIONS: &str = include_str!("../../../data/conditions.csv");
+const ALLELE_EXPRS: &str = include_str!("../../../data/allele_exprs.csv");
+const EXPR_RELATIONS: &str = include_str!("../../../data/expr_relations.csv");
+const STRAINS: &str = include_str!("../../../data/strains.csv");
+const STRAIN_ALLELES: &str = include_str!("../../../data/strain_alleles.csv");
+
+/// Outcome of seeding one of the reference files
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_SeedFileReport.ts")]
+#[serde(rename = "db_SeedFileReport")]
+pub struct SeedFileReport {
+    pub file: String,
+    pub report: BulkReport,
+}
+
+impl InnerDbState {
+    /// Loads the bundled reference data, each file after the ones its foreign keys
+    /// point to. Everything is seeded in one transaction, which is only committed if
+    /// no file had rejected rows. Rows that already exist are left as they are.
+    pub async fn seed_reference_data(&self) -> Result<Vec<SeedFileReport>, DbError> {
+        let mut tx = match self.conn_pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => {
+                eprint!("Seed error: {e}");
+                return Err(DbError::BulkInsert(e.to_string()));
+            }
+        };
+        let mut reports = vec![
+            Self::seed_file::<GeneDb>(&mut tx, "genes.csv", GENES).await?,
+            Self::seed_file::<VariationDb>(&mut tx, "variations.csv", VARIATIONS).await?,
+            Self::seed_file::<Allele>(&mut tx, "alleles.csv", ALLELES).await?,
+            Self::seed_file::<PhenotypeDb>(&mut tx, "phenotypes.csv", PHENOTYPES).await?,
+            Self::seed_file::<ConditionDb>(&mut tx, "conditions.csv", CONDITIONS).await?,
+            Self::seed_file::<AlleleExpressionDb>(&mut tx, "allele_exprs.csv", ALLELE_EXPRS)
+                .await?,
+            Self::seed_file::<ExpressionRelationDb>(&mut tx, "expr_relations.csv", EXPR_RELATIONS)
+                .await?,
+            Self::seed_file::<Strain>(&mut tx, "strains.csv", STRAINS).await?,
+            Self::seed_file::<StrainAllele>(&mut tx, "strain_alleles.csv", STRAIN_ALLELES).await?,
+        ];
+
+        let committed = reports
+            .iter()
+            .all(|seeded| seeded.report.rejected.is_empty());
+        let res = match committed {
+            true => tx.commit().await,
+            false => tx.rollback().await,
+        };
+        if let Err(e) = res {
+            eprint!("Seed error: {e}");
+            return Err(DbError::BulkInsert(e.to_string()));
+        }
+        for seeded in reports.iter_mut() {
+            seeded.report.committed = committed;
+        }
+        Ok(reports)
+    }
+
+    async fn seed_file<T: BulkInsertable>(
+        tx: &mut Transaction<'_, Sqlite>,
+        file: &str,
+        contents: &str,
+    ) -> Result<SeedFileReport, DbError> {
+        let bulk = Bulk::<T>::from_reader(&mut csv::Reader::from_reader(contents.as_bytes()));
+        Ok(SeedFileReport {
+            file: file.to_owned(),
+            report: Self::import_bulk(tx, bulk, ConflictPolicy::Ignore).await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::{seed::SeedFileReport, InnerDbState};
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    fn get_seeded_counts(reports: &[SeedFileReport]) -> Vec<(&str, usize, usize)> {
+        reports
+            .iter()
+            .map(|seeded| {
+                (
+                    seeded.file.as_str(),
+                    seeded.report.inserted.len(),
+                    seeded.report.skipped.len(),
+                )
+            })
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn test_seed_reference_data(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let expected = vec![
+            ("genes.csv", 8),
+            ("variations.csv", 12),
+            ("alleles.csv", 22),
+            ("phenotypes.csv", 29),
+            ("conditions.csv", 4),
+            ("allele_exprs.csv", 35),
+            ("expr_relations.csv", 7),
+            ("strains.csv", 7),
+            ("strain_alleles.csv", 9),
+        ];
+
+        let reports = state.seed_reference_data().await?;
+        assert!(reports
+            .iter()
+            .all(|seeded| seeded.report.committed && seeded.report.rejected.is_empty()));
+        assert_eq!(
+            get_seeded_counts(&reports),
+            expected
+                .iter()
+                .map(|&(file, rows)| (file, rows, 0))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(state.get_genes().await?.len(), 8);
+        assert_eq!(state.get_strain_alleles().await?.len(), 9);
+
+        // seeding again leaves everything as it is
+        let reports = state.seed_reference_data().await?;
+        assert_eq!(
+            get_seeded_counts(&reports),
+            expected
+                .iter()
+                .map(|&(file, rows)| (file, 0, rows))
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+}
@@ -1,11 +1,54 @@
-use super::{bulk::Bulk, DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
-    filter::{Count, FilterGroup, FilterQueryBuilder},
-    strain::{Strain, StrainFieldName},
+    filter::{Count, FilterQueryBuilder},
+    strain::{Strain, StrainFieldName, StrainJoinFieldName, StrainSearchHit, StrainSearchResults},
 };
 
 use anyhow::Result;
-use sqlx::{QueryBuilder, Sqlite};
+use csv::Writer;
+use serde_json::{json, Value};
+use sqlx::{QueryBuilder, Row, Sqlite};
+use std::io::Write;
+
+impl TableRow for Strain {
+    const TABLE: &'static str = "strains";
+    const COLUMNS: &'static [&'static str] = &["name", "genotype", "description"];
+    const KEY_COLUMNS: &'static [&'static str] = &["name"];
+}
+
+impl BulkInsertable for Strain {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.name),
+            json!(self.genotype),
+            json!(self.description),
+        ]
+    }
+}
+
+/// Every allele of each strain, with the allele's gene, variation and expressions, which the
+/// columns of StrainJoinFieldName refer to
+const STRAIN_JOIN: &str = "
+    FROM strains
+    LEFT JOIN strain_alleles ON strain_alleles.strain_name = strains.name
+    LEFT JOIN alleles ON alleles.name = strain_alleles.allele_name
+    LEFT JOIN genes ON alleles.systematic_gene_name IS genes.systematic_name
+    LEFT JOIN variations ON alleles.variation_name IS variations.allele_name
+    LEFT JOIN allele_exprs ON allele_exprs.allele_name = alleles.name
+    ";
+
+fn search_error(e: impl ToString) -> DbError {
+    let e = e.to_string();
+    eprint!("Search strains error: {e}");
+    DbError::Query(e)
+}
 
 impl InnerDbState {
     pub async fn get_strains(&self) -> Result<Vec<Strain>, DbError> {
@@ -28,11 +71,11 @@ impl InnerDbState {
 
     pub async fn get_filtered_strains(
         &self,
-        filter: &FilterGroup<StrainFieldName>,
+        filter: &impl FilterQueryBuilder<StrainFieldName>,
     ) -> Result<Vec<Strain>, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT name, genotype, description from strains");
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<Strain>()
@@ -47,13 +90,39 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_strains_page(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<Strain>, DbError> {
+        self.get_page::<Strain, _>(filter, page).await
+    }
+
+    pub async fn query_strains(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainFieldName>,
+        facets: Vec<StrainFieldName>,
+    ) -> Result<QueryResult<Strain, StrainFieldName>, DbError> {
+        self.query::<Strain, _>(filter, facets).await
+    }
+
+    pub async fn aggregate_strains(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainFieldName>,
+        group_by: &StrainFieldName,
+        metrics: &[Metric<StrainFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("strains", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_strains(
         &self,
-        filter: &FilterGroup<StrainFieldName>,
+        filter: &impl FilterQueryBuilder<StrainFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT COUNT(*) as count FROM strains");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -68,6 +137,76 @@ impl InnerDbState {
         }
     }
 
+    /**
+     * Finds the strains with an allele that, together with the allele's gene, variation
+     * and one of its expressions, matches the filter. Each strain comes with the alleles
+     * of it that matched. Ordering on anything but the strain's own columns uses one of the
+     * matching rows.
+     */
+    pub async fn search_strains(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainJoinFieldName>,
+    ) -> Result<StrainSearchResults, DbError> {
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT strains.name, strains.genotype, strains.description,
+            json_group_array(DISTINCT alleles.name) AS matching_alleles",
+        );
+        qb.push(STRAIN_JOIN);
+        if filter.has_conditions() {
+            qb.push(" WHERE ");
+        }
+        filter.add_filtered_query(&mut qb, false, false)?;
+        qb.push(" GROUP BY strains.name ");
+        filter.add_ordering(&mut qb, true);
+
+        let rows = qb
+            .build()
+            .fetch_all(&self.conn_pool)
+            .await
+            .map_err(search_error)?;
+        let hits = rows
+            .into_iter()
+            .map(|row| {
+                // strains without any alleles get a null in the array
+                let alleles: Vec<Option<String>> =
+                    serde_json::from_str(row.get("matching_alleles")).map_err(search_error)?;
+                Ok(StrainSearchHit {
+                    strain: Strain {
+                        name: row.get(0),
+                        genotype: row.get(1),
+                        description: row.get(2),
+                    },
+                    matching_alleles: alleles.into_iter().flatten().collect(),
+                })
+            })
+            .collect::<Result<Vec<StrainSearchHit>, DbError>>()?;
+
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new("SELECT COUNT(DISTINCT strains.name) as count");
+        qb.push(STRAIN_JOIN);
+        filter.add_filtered_query(&mut qb, true, false)?;
+        let total = qb
+            .build_query_as::<Count>()
+            .fetch_one(&self.conn_pool)
+            .await
+            .map_err(search_error)?
+            .count;
+
+        Ok(StrainSearchResults { hits, total })
+    }
+
+    /// Aggregates strains joined to their alleles, counting each strain once per group,
+    /// such as how many strains carry alleles on each chromosome
+    pub async fn aggregate_joined_strains(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainJoinFieldName>,
+        group_by: &StrainJoinFieldName,
+        metrics: &[Metric<StrainJoinFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate(STRAIN_JOIN, Some("strains.name"), filter, group_by, metrics)
+            .await
+    }
+
     pub async fn update_strain(&self, name: String, new_strain: Strain) -> Result<(), DbError> {
         match sqlx::query!(
             "UPDATE strains SET name = ?, genotype = ?, description = ? WHERE name = ?",
@@ -108,67 +247,53 @@ impl InnerDbState {
         }
     }
 
-    pub async fn insert_strains(&self, bulk: Bulk<Strain>) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.len()
-            )));
-        }
-        let bind_limit = SQLITE_BIND_LIMIT / 3;
-
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
-            let mut qb: QueryBuilder<Sqlite> =
-                QueryBuilder::new("INSERT OR IGNORE INTO strains (name, genotype, description)");
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.name)
-                    .push_bind(item.genotype)
-                    .push_bind(item.description);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            }
-        }
-        Ok(())
+    pub async fn insert_strains(
+        &self,
+        bulk: Bulk<Strain>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
     }
 
-    pub async fn delete_filtered_strains(
+    pub async fn export_filtered_strains<W: Write>(
         &self,
-        filter: &FilterGroup<StrainFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM strains");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter: &impl FilterQueryBuilder<StrainFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<Strain, _, _>(filter, writer).await
+    }
 
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete strain error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+    /// Counts the rows in each table that deleting the filtered strains would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_strains(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("strains", filter).await
+    }
+
+    pub async fn delete_filtered_strains(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("strains", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
     use std::io::BufReader;
 
-    use crate::interface::bulk::Bulk;
-    use crate::models::filter::{Filter, FilterGroup, Order};
-    use crate::models::strain::Strain;
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
+    use crate::models::allele_expr::AlleleExpressionFieldName;
+    use crate::models::filter::{Filter, FilterExpr, FilterGroup, FilterTree, Order};
+    use crate::models::gene::GeneFieldName;
+    use crate::models::strain::{Strain, StrainJoinFieldName, StrainSearchResults};
+    use crate::models::strain_allele::StrainAlleleFieldName;
+    use crate::models::variation::VariationFieldName;
     use crate::InnerDbState;
     use crate::{interface::mock, models::strain::StrainFieldName};
     use anyhow::Result;
@@ -184,14 +309,145 @@ mod test {
         Ok(())
     }
 
+    fn get_hit_names(results: StrainSearchResults) -> Vec<(String, Vec<String>)> {
+        results
+            .hits
+            .into_iter()
+            .map(|hit| (hit.strain.name, hit.matching_alleles))
+            .collect()
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_search_strains(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        // strains carrying any allele of dpy-10
+        let mut filter = FilterGroup::<StrainJoinFieldName> {
+            filters: vec![vec![(
+                StrainJoinFieldName::Gene(GeneFieldName::DescName),
+                Filter::Equal("dpy-10".into()),
+            )]],
+            order_by: vec![(
+                StrainJoinFieldName::Strain(StrainFieldName::Name),
+                Order::Asc,
+            )],
+            limit: None,
+            offset: None,
+        };
+        let results = state.search_strains(&filter).await?;
+        assert_eq!(results.total, 2);
+        assert_eq!(
+            get_hit_names(results),
+            vec![
+                ("CB128".to_string(), vec!["e128".to_string()]),
+                ("TN64".to_string(), vec!["cn64".to_string()]),
+            ]
+        );
+
+        filter.limit = Some(1);
+        filter.offset = Some(1);
+        let results = state.search_strains(&filter).await?;
+        assert_eq!(results.total, 2);
+        assert_eq!(
+            get_hit_names(results),
+            vec![("TN64".to_string(), vec!["cn64".to_string()])]
+        );
+
+        // strains expressing rol
+        let filter = FilterGroup::<StrainJoinFieldName> {
+            filters: vec![vec![(
+                StrainJoinFieldName::AlleleExpr(AlleleExpressionFieldName::ExpressingPhenotypeName),
+                Filter::Equal("rol".into()),
+            )]],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+        assert_eq!(
+            get_hit_names(state.search_strains(&filter).await?),
+            vec![("TN64".to_string(), vec!["cn64".to_string()])]
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_search_strains_homozygous_on_chromosome(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let on_chromosome = |chromosome: &str| {
+            FilterExpr::Or(vec![
+                FilterExpr::Leaf(
+                    StrainJoinFieldName::Gene(GeneFieldName::Chromosome),
+                    Filter::Equal(chromosome.into()),
+                ),
+                FilterExpr::Leaf(
+                    StrainJoinFieldName::Variation(VariationFieldName::Chromosome),
+                    Filter::Equal(chromosome.into()),
+                ),
+            ])
+        };
+        let homozygous = || {
+            FilterExpr::And(vec![
+                FilterExpr::Leaf(
+                    StrainJoinFieldName::StrainAllele(StrainAlleleFieldName::IsOnTop),
+                    Filter::True,
+                ),
+                FilterExpr::Leaf(
+                    StrainJoinFieldName::StrainAllele(StrainAlleleFieldName::IsOnBot),
+                    Filter::True,
+                ),
+            ])
+        };
+
+        let filter = FilterTree::<StrainJoinFieldName> {
+            filter: FilterExpr::And(vec![on_chromosome("III"), homozygous()]),
+            order_by: vec![(
+                StrainJoinFieldName::Strain(StrainFieldName::Name),
+                Order::Asc,
+            )],
+            limit: None,
+            offset: None,
+        };
+        assert_eq!(
+            get_hit_names(state.search_strains(&filter).await?),
+            vec![
+                ("EG5071".to_string(), vec!["ed3".to_string()]),
+                ("EG6207".to_string(), vec!["ed3".to_string()]),
+            ]
+        );
+
+        // only e138 of BT14's alleles on IV is homozygous
+        let filter = FilterTree::<StrainJoinFieldName> {
+            filter: FilterExpr::And(vec![on_chromosome("IV"), homozygous()]),
+            order_by: vec![(
+                StrainJoinFieldName::Strain(StrainFieldName::Name),
+                Order::Asc,
+            )],
+            limit: None,
+            offset: None,
+        };
+        let mut results = state.search_strains(&filter).await?;
+        results
+            .hits
+            .iter_mut()
+            .for_each(|hit| hit.matching_alleles.sort());
+        assert_eq!(
+            get_hit_names(results),
+            vec![
+                ("BT14".to_string(), vec!["e138".to_string()]),
+                ("EG5071".to_string(), vec!["oxIs363".to_string()]),
+            ]
+        );
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("full_db"))]
     async fn test_get_filtered_strains(pool: Pool<Sqlite>) -> Result<()> {
         let state = InnerDbState { conn_pool: pool };
         let exprs = state
             .get_filtered_strains(&FilterGroup::<StrainFieldName> {
                 filters: vec![vec![
-                    (StrainFieldName::Name, Filter::Equal("N2".to_string())),
-                    (StrainFieldName::Name, Filter::Equal("CB128".to_string())),
+                    (StrainFieldName::Name, Filter::Equal("N2".into())),
+                    (StrainFieldName::Name, Filter::Equal("CB128".into())),
                 ]],
                 order_by: vec![(StrainFieldName::Name, Order::Asc)],
                 limit: None,
@@ -209,8 +465,8 @@ mod test {
         let exprs = state
             .get_filtered_strains(&FilterGroup::<StrainFieldName> {
                 filters: vec![vec![
-                    (StrainFieldName::Name, Filter::Equal("CB128".to_string())),
-                    (StrainFieldName::Name, Filter::Equal("N2".to_string())),
+                    (StrainFieldName::Name, Filter::Equal("CB128".into())),
+                    (StrainFieldName::Name, Filter::Equal("N2".into())),
                 ]],
                 order_by: vec![(StrainFieldName::Genotype, Order::Asc)],
                 limit: None,
@@ -231,10 +487,10 @@ mod test {
         let exprs = state
             .get_filtered_strains(&FilterGroup::<StrainFieldName> {
                 filters: vec![
-                    vec![(StrainFieldName::Name, Filter::Equal("N2".to_string()))],
+                    vec![(StrainFieldName::Name, Filter::Equal("N2".into()))],
                     vec![(
                         StrainFieldName::Description,
-                        Filter::Equal( "C. elegans var Bristol. Generation time is about 3 days. Brood size is about 350. Also CGC reference 257. Isolated from mushroom compost near Bristol, England by L.N. Staniland.".to_string()),
+                        Filter::Equal( "C. elegans var Bristol. Generation time is about 3 days. Brood size is about 350. Also CGC reference 257. Isolated from mushroom compost near Bristol, England by L.N. Staniland.".into()),
                     )],
                 ],
                 order_by: vec![],
@@ -257,7 +513,7 @@ mod test {
                         Filter::Like("Dpy".to_string()),
                     )],
                     vec![
-                        (StrainFieldName::Name, Filter::Equal("EG5071".to_string())),
+                        (StrainFieldName::Name, Filter::Equal("EG5071".into())),
                         (StrainFieldName::Name, Filter::Like("BT".to_string())),
                     ],
                 ],
@@ -315,7 +571,7 @@ mod test {
         let expected = Strain {
             name: "N2".to_string(),
             genotype: "C. elegans wild isolate.".to_string(),
-            description: Some( "C. elegans var Bristol. Generation time is about 3 days. Brood size is about 350. Also CGC reference 257. Isolated from mushroom compost near Bristol, England by L.N. Staniland.".to_string()),
+            description: Some( "C. elegans var Bristol. Generation time is about 3 days. Brood size is about 350. Also CGC reference 257. Isolated from mushroom compost near Bristol, England by L.N. Staniland.".into()),
         };
 
         state.insert_strain(&expected).await?;
@@ -354,7 +610,9 @@ mod test {
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<Strain> = Bulk::from_reader(&mut reader);
 
-        state.insert_strains(bulk).await?;
+        state
+            .insert_strains(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let strains: Vec<Strain> = state.get_strains().await?;
         assert_eq!(
@@ -387,7 +645,9 @@ mod test {
             .from_reader(buf);
         let bulk: Bulk<Strain> = Bulk::from_reader(&mut reader);
 
-        state.insert_strains(bulk).await?;
+        state
+            .insert_strains(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let strains: Vec<Strain> = state.get_strains().await?;
         assert_eq!(
@@ -418,10 +678,7 @@ mod test {
         assert_eq!(orig_len, mock::strain::get_strains().len());
 
         let filter = &FilterGroup::<StrainFieldName> {
-            filters: vec![vec![(
-                StrainFieldName::Name,
-                Filter::Equal("N2".to_owned()),
-            )]],
+            filters: vec![vec![(StrainFieldName::Name, Filter::Equal("N2".into()))]],
             order_by: vec![],
             limit: None,
             offset: None,
@@ -431,7 +688,9 @@ mod test {
         let filtered_len = strains.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_strains(filter).await?;
+        state
+            .delete_filtered_strains(filter, DeleteMode::Restrict)
+            .await?;
         strains = state.get_strains().await?;
 
         assert_eq!(strains.len(), orig_len - filtered_len);
@@ -446,14 +705,14 @@ mod test {
         let mut strains: Vec<Strain> = state.get_strains().await?;
         assert_eq!(strains.len(), mock::strain::get_strains().len());
 
-        let filter = &FilterGroup::<StrainFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_strains(filter).await?;
+        let preview = state.preview_delete_all(DeletableTable::Strains).await?;
+        state
+            .delete_all(
+                DeletableTable::Strains,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         strains = state.get_strains().await?;
 
         assert_eq!(strains.len(), 0);
@@ -1,11 +1,39 @@
-use super::{bulk::Bulk, DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
-    filter::{Count, FilterGroup, FilterQueryBuilder},
+    filter::{Count, FilterQueryBuilder},
     strain_allele::{StrainAllele, StrainAlleleDb, StrainAlleleFieldName},
 };
 
 use anyhow::Result;
+use csv::Writer;
+use serde_json::{json, Value};
 use sqlx::{QueryBuilder, Sqlite};
+use std::io::Write;
+
+impl TableRow for StrainAllele {
+    const TABLE: &'static str = "strain_alleles";
+    const COLUMNS: &'static [&'static str] =
+        &["strain_name", "allele_name", "is_on_top", "is_on_bot"];
+    const KEY_COLUMNS: &'static [&'static str] = &["strain_name", "allele_name"];
+}
+
+impl BulkInsertable for StrainAllele {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.strain_name),
+            json!(self.allele_name),
+            json!(self.is_on_top as i64),
+            json!(self.is_on_bot as i64),
+        ]
+    }
+}
 
 impl InnerDbState {
     pub async fn get_strain_alleles(&self) -> Result<Vec<StrainAllele>, DbError> {
@@ -28,12 +56,12 @@ impl InnerDbState {
 
     pub async fn get_filtered_strain_alleles(
         &self,
-        filter: &FilterGroup<StrainAlleleFieldName>,
+        filter: &impl FilterQueryBuilder<StrainAlleleFieldName>,
     ) -> Result<Vec<StrainAllele>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT strain_name, allele_name, is_on_top, is_on_bot from strain_alleles",
         );
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<StrainAllele>()
@@ -48,13 +76,39 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_strain_alleles_page(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainAlleleFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<StrainAllele>, DbError> {
+        self.get_page::<StrainAllele, _>(filter, page).await
+    }
+
+    pub async fn query_strain_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainAlleleFieldName>,
+        facets: Vec<StrainAlleleFieldName>,
+    ) -> Result<QueryResult<StrainAllele, StrainAlleleFieldName>, DbError> {
+        self.query::<StrainAllele, _>(filter, facets).await
+    }
+
+    pub async fn aggregate_strain_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainAlleleFieldName>,
+        group_by: &StrainAlleleFieldName,
+        metrics: &[Metric<StrainAlleleFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("strain_alleles", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_strain_alleles(
         &self,
-        filter: &FilterGroup<StrainAlleleFieldName>,
+        filter: &impl FilterQueryBuilder<StrainAlleleFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT COUNT(*) as count FROM strain_alleles");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -91,67 +145,47 @@ impl InnerDbState {
         }
     }
 
-    pub async fn insert_strain_alleles(&self, bulk: Bulk<StrainAllele>) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.first().unwrap().1
-            )));
-        }
-        let bind_limit = SQLITE_BIND_LIMIT / 4;
-
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-                "INSERT OR IGNORE INTO strain_alleles (strain_name, allele_name, is_on_top, is_on_bot)",
-            );
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.strain_name)
-                    .push_bind(item.allele_name)
-                    .push_bind(item.is_on_top)
-                    .push_bind(item.is_on_bot);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            }
-        }
-        Ok(())
+    pub async fn insert_strain_alleles(
+        &self,
+        bulk: Bulk<StrainAllele>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
     }
 
-    pub async fn delete_filtered_strain_alleles(
+    pub async fn export_filtered_strain_alleles<W: Write>(
         &self,
-        filter: &FilterGroup<StrainAlleleFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM strain_alleles");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter: &impl FilterQueryBuilder<StrainAlleleFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<StrainAllele, _, _>(filter, writer).await
+    }
 
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete strain allele error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+    /// Counts the rows in each table that deleting the filtered strain alleles would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_strain_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainAlleleFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("strain_alleles", filter).await
+    }
+
+    pub async fn delete_filtered_strain_alleles(
+        &self,
+        filter: &impl FilterQueryBuilder<StrainAlleleFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("strain_alleles", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
     use std::io::BufReader;
 
-    use crate::interface::bulk::Bulk;
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
     use crate::models::filter::{Filter, FilterGroup, Order};
     use crate::models::strain_allele::StrainAllele;
     use crate::InnerDbState;
@@ -176,7 +210,7 @@ mod test {
             .get_filtered_strain_alleles(&FilterGroup::<StrainAlleleFieldName> {
                 filters: vec![vec![(
                     StrainAlleleFieldName::AlleleName,
-                    Filter::Equal("ed3".to_string()),
+                    Filter::Equal("ed3".into()),
                 )]],
                 order_by: vec![(StrainAlleleFieldName::StrainName, Order::Asc)],
                 limit: None,
@@ -196,19 +230,19 @@ mod test {
                 filters: vec![vec![
                     (
                         StrainAlleleFieldName::AlleleName,
-                        Filter::Equal("oxTi75".to_string()),
+                        Filter::Equal("oxTi75".into()),
                     ),
                     (
                         StrainAlleleFieldName::AlleleName,
-                        Filter::Equal("cn64".to_string()),
+                        Filter::Equal("cn64".into()),
                     ),
                     (
                         StrainAlleleFieldName::AlleleName,
-                        Filter::Equal("ox11000".to_string()),
+                        Filter::Equal("ox11000".into()),
                     ),
                     (
                         StrainAlleleFieldName::AlleleName,
-                        Filter::Equal("ed3".to_string()),
+                        Filter::Equal("ed3".into()),
                     ),
                 ]],
                 order_by: vec![],
@@ -232,7 +266,7 @@ mod test {
                 filters: vec![
                     vec![(
                         StrainAlleleFieldName::AlleleName,
-                        Filter::Equal("ed3".to_string()),
+                        Filter::Equal("ed3".into()),
                     )],
                     vec![
                         (
@@ -297,6 +331,28 @@ mod test {
         Ok(())
     }
 
+    #[sqlx::test(fixtures("allele", "strain"))]
+    async fn test_insert_strain_alleles_missing_strain(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let csv_str =
+            "strainName,alleleName,isOnTop,isOnBot\nEG6207,ed3,true,true\nFAKE1,n744,true,true"
+                .as_bytes();
+        let buf = BufReader::new(csv_str);
+        let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
+        let bulk: Bulk<StrainAllele> = Bulk::from_reader(&mut reader);
+
+        let report = state
+            .insert_strain_alleles(bulk, ConflictPolicy::Ignore, false)
+            .await?;
+        assert_eq!(report.inserted, vec![2]);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line, 3);
+        assert!(!report.committed);
+        assert_eq!(state.get_strain_alleles().await?.len(), 0);
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("allele", "strain"))]
     async fn test_insert_strain_alleles(pool: Pool<Sqlite>) -> Result<()> {
         let state = InnerDbState { conn_pool: pool };
@@ -308,7 +364,9 @@ mod test {
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<StrainAllele> = Bulk::from_reader(&mut reader);
 
-        state.insert_strain_alleles(bulk).await?;
+        state
+            .insert_strain_alleles(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let strain_alleles: Vec<StrainAllele> = state.get_strain_alleles().await?;
         assert_eq!(
@@ -351,7 +409,9 @@ mod test {
             .from_reader(buf);
         let bulk: Bulk<StrainAllele> = Bulk::from_reader(&mut reader);
 
-        state.insert_strain_alleles(bulk).await?;
+        state
+            .insert_strain_alleles(bulk, ConflictPolicy::Ignore, false)
+            .await?;
 
         let strain_alleles: Vec<StrainAllele> = state.get_strain_alleles().await?;
         assert_eq!(
@@ -380,6 +440,59 @@ mod test {
         Ok(())
     }
 
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_export_filtered_strain_alleles_tsv(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = &FilterGroup::<StrainAlleleFieldName> {
+            filters: vec![vec![(
+                StrainAlleleFieldName::StrainName,
+                Filter::Equal("BT14".into()),
+            )]],
+            order_by: vec![(StrainAlleleFieldName::AlleleName, Order::Asc)],
+            limit: None,
+            offset: None,
+        };
+        let strain_alleles = state.get_filtered_strain_alleles(filter).await?;
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_writer(vec![]);
+        state
+            .export_filtered_strain_alleles(filter, &mut writer)
+            .await?;
+        let tsv_bytes = writer.into_inner()?;
+
+        state
+            .delete_filtered_strain_alleles(
+                &FilterGroup {
+                    filters: vec![vec![(
+                        StrainAlleleFieldName::StrainName,
+                        Filter::Equal("BT14".into()),
+                    )]],
+                    order_by: vec![],
+                    limit: None,
+                    offset: None,
+                },
+                DeleteMode::Restrict,
+            )
+            .await?;
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(true)
+            .from_reader(BufReader::new(tsv_bytes.as_slice()));
+        let bulk: Bulk<StrainAllele> = Bulk::from_reader(&mut reader);
+        assert!(bulk.errors.is_empty());
+        state
+            .insert_strain_alleles(bulk, ConflictPolicy::Fail, false)
+            .await?;
+
+        assert_eq!(
+            state.get_filtered_strain_alleles(filter).await?,
+            strain_alleles
+        );
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("full_db"))]
 
     async fn test_delete_filtered_strain_alleles(pool: Pool<Sqlite>) -> Result<()> {
@@ -392,7 +505,7 @@ mod test {
         let filter = &FilterGroup::<StrainAlleleFieldName> {
             filters: vec![vec![(
                 StrainAlleleFieldName::AlleleName,
-                Filter::Equal("ed3".to_string()),
+                Filter::Equal("ed3".into()),
             )]],
             order_by: vec![],
             limit: None,
@@ -403,7 +516,9 @@ mod test {
         let filtered_len = strain_alleles.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_strain_alleles(filter).await?;
+        state
+            .delete_filtered_strain_alleles(filter, DeleteMode::Restrict)
+            .await?;
         strain_alleles = state.get_strain_alleles().await?;
 
         assert_eq!(strain_alleles.len(), orig_len - filtered_len);
@@ -421,14 +536,16 @@ mod test {
             mock::strain_allele::get_strain_alleles().len()
         );
 
-        let filter = &FilterGroup::<StrainAlleleFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_strain_alleles(filter).await?;
+        let preview = state
+            .preview_delete_all(DeletableTable::StrainAlleles)
+            .await?;
+        state
+            .delete_all(
+                DeletableTable::StrainAlleles,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         strain_alleles = state.get_strain_alleles().await?;
 
         assert_eq!(strain_alleles.len(), 0);
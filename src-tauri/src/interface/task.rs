@@ -1,11 +1,33 @@
-use super::{DbError, InnerDbState};
+use super::{
+    aggregate::{AggregateRow, Metric},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
 use crate::models::{
-    filter::{FilterGroup, FilterQueryBuilder},
+    filter::FilterQueryBuilder,
     task::{Task, TaskDb, TaskFieldName},
 };
 use anyhow::Result;
 use sqlx::{QueryBuilder, Sqlite};
 
+impl TableRow for TaskDb {
+    const TABLE: &'static str = "tasks";
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "due_date",
+        "action",
+        "herm_strain",
+        "male_strain",
+        "result_strain",
+        "notes",
+        "cross_design_id",
+        "child_task_id",
+        "completed",
+    ];
+    const KEY_COLUMNS: &'static [&'static str] = &["id"];
+}
+
 impl InnerDbState {
     pub async fn get_tasks(&self) -> Result<Vec<Task>, DbError> {
         match sqlx::query_as!(
@@ -27,12 +49,12 @@ impl InnerDbState {
 
     pub async fn get_filtered_tasks(
         &self,
-        filter: &FilterGroup<TaskFieldName>,
+        filter: &impl FilterQueryBuilder<TaskFieldName>,
     ) -> Result<Vec<Task>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT id, due_date, action, herm_strain, male_strain, result_strain, notes, cross_design_id, child_task_id, completed FROM tasks",
         );
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
 
         match qb
             .build_query_as::<TaskDb>()
@@ -47,6 +69,38 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_tasks_page(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<Task>, DbError> {
+        Ok(self
+            .get_page::<TaskDb, _>(filter, page)
+            .await?
+            .map(Task::from))
+    }
+
+    pub async fn query_tasks(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskFieldName>,
+        facets: Vec<TaskFieldName>,
+    ) -> Result<QueryResult<Task, TaskFieldName>, DbError> {
+        Ok(self
+            .query::<TaskDb, _>(filter, facets)
+            .await?
+            .map(Task::from))
+    }
+
+    pub async fn aggregate_tasks(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskFieldName>,
+        group_by: &TaskFieldName,
+        metrics: &[Metric<TaskFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("tasks", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn insert_task(&self, task: &Task) -> Result<(), DbError> {
         let action_val: i32 = (task.action as u8).into();
         match sqlx::query!(
@@ -189,7 +243,7 @@ mod test {
         let state = InnerDbState { conn_pool: pool };
         let exprs = state
             .get_filtered_tasks(&FilterGroup::<TaskFieldName> {
-                filters: vec![vec![(TaskFieldName::Id, Filter::Equal("1".to_owned()))]],
+                filters: vec![vec![(TaskFieldName::Id, Filter::Equal("1".into()))]],
                 order_by: vec![],
                 limit: None,
                 offset: None,
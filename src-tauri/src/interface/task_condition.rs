@@ -0,0 +1,301 @@
+use super::{
+    aggregate::{AggregateRow, Metric},
+    bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
+use crate::models::{
+    filter::{Count, FilterQueryBuilder},
+    task_condition::{TaskCondition, TaskConditionFieldName},
+};
+use anyhow::Result;
+use csv::Writer;
+use serde_json::{json, Value};
+use sqlx::{QueryBuilder, Sqlite};
+use std::io::Write;
+
+impl TableRow for TaskCondition {
+    const TABLE: &'static str = "task_conditions";
+    const COLUMNS: &'static [&'static str] = &["task_id", "condition_name"];
+    const KEY_COLUMNS: &'static [&'static str] = &["task_id", "condition_name"];
+}
+
+impl BulkInsertable for TaskCondition {
+    fn values(&self) -> Vec<Value> {
+        vec![json!(self.task_id), json!(self.condition_name)]
+    }
+}
+
+impl InnerDbState {
+    pub async fn get_task_conditions(&self) -> Result<Vec<TaskCondition>, DbError> {
+        match sqlx::query_as::<_, TaskCondition>(
+            "
+            SELECT task_id, condition_name FROM task_conditions ORDER BY task_id, condition_name
+            ",
+        )
+        .fetch_all(&self.conn_pool)
+        .await
+        {
+            Ok(conds) => Ok(conds),
+            Err(e) => {
+                eprint!("Get task conditions error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn get_filtered_task_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskConditionFieldName>,
+    ) -> Result<Vec<TaskCondition>, DbError> {
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new("SELECT task_id, condition_name FROM task_conditions");
+        filter.add_filtered_query(&mut qb, true, true)?;
+
+        match qb
+            .build_query_as::<TaskCondition>()
+            .fetch_all(&self.conn_pool)
+            .await
+        {
+            Ok(conds) => Ok(conds),
+            Err(e) => {
+                eprint!("Get Filtered Task Condition error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn get_filtered_task_conditions_page(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskConditionFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<TaskCondition>, DbError> {
+        self.get_page::<TaskCondition, _>(filter, page).await
+    }
+
+    pub async fn query_task_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskConditionFieldName>,
+        facets: Vec<TaskConditionFieldName>,
+    ) -> Result<QueryResult<TaskCondition, TaskConditionFieldName>, DbError> {
+        self.query::<TaskCondition, _>(filter, facets).await
+    }
+
+    pub async fn aggregate_task_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskConditionFieldName>,
+        group_by: &TaskConditionFieldName,
+        metrics: &[Metric<TaskConditionFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("task_conditions", filter, group_by, metrics)
+            .await
+    }
+
+    pub async fn get_count_filtered_task_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskConditionFieldName>,
+    ) -> Result<u32, DbError> {
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new("SELECT COUNT(*) as count FROM task_conditions");
+        filter.add_filtered_query(&mut qb, true, false)?;
+
+        match qb
+            .build_query_as::<Count>()
+            .fetch_one(&self.conn_pool)
+            .await
+        {
+            Ok(count) => Ok(count.count),
+            Err(e) => {
+                eprint!("Get Filtered Task Condition Count error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn insert_task_condition(&self, cond: &TaskCondition) -> Result<(), DbError> {
+        match sqlx::query(
+            "INSERT INTO task_conditions (task_id, condition_name)
+            VALUES(?, ?)
+            ",
+        )
+        .bind(&cond.task_id)
+        .bind(&cond.condition_name)
+        .execute(&self.conn_pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                eprint!("Insert Task Condition error: {e}");
+                Err(DbError::Insert(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn insert_task_conditions(
+        &self,
+        bulk: Bulk<TaskCondition>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
+    }
+
+    pub async fn export_filtered_task_conditions<W: Write>(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskConditionFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<TaskCondition, _, _>(filter, writer)
+            .await
+    }
+
+    /// Counts the rows in each table that deleting the filtered task conditions would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_task_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskConditionFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("task_conditions", filter).await
+    }
+
+    pub async fn delete_filtered_task_conditions(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskConditionFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("task_conditions", filter, mode).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
+    use std::io::BufReader;
+
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
+    use crate::interface::mock;
+    use crate::models::filter::{Filter, FilterGroup, Order};
+    use crate::models::task_condition::{TaskCondition, TaskConditionFieldName};
+    use crate::InnerDbState;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_task_conditions(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let conds = state.get_task_conditions().await?;
+
+        assert_eq!(conds, mock::task_condition::get_task_conditions());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_filtered_task_conditions(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = FilterGroup::<TaskConditionFieldName> {
+            filters: vec![vec![(
+                TaskConditionFieldName::Name,
+                Filter::Equal("25C".into()),
+            )]],
+            order_by: vec![(TaskConditionFieldName::Id, Order::Asc)],
+            limit: None,
+            offset: None,
+        };
+
+        let conds = state.get_filtered_task_conditions(&filter).await?;
+        assert_eq!(conds, mock::task_condition::get_filtered_task_conditions());
+        assert_eq!(state.get_count_filtered_task_conditions(&filter).await?, 2);
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_task_condition(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let orig_len = state.get_task_conditions().await?.len();
+
+        let expected = TaskCondition {
+            task_id: "5".to_string(),
+            condition_name: "Tetracycline".to_string(),
+        };
+        state.insert_task_condition(&expected).await?;
+
+        let conds = state.get_task_conditions().await?;
+        assert_eq!(conds.len(), orig_len + 1);
+        assert!(conds.contains(&expected));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_task_condition_unknown_condition(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let cond = TaskCondition {
+            task_id: "5".to_string(),
+            condition_name: "not a condition".to_string(),
+        };
+        assert!(state.insert_task_condition(&cond).await.is_err());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_task_conditions(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let preview = state
+            .preview_delete_all(DeletableTable::TaskConditions)
+            .await?;
+        state
+            .delete_all(
+                DeletableTable::TaskConditions,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
+
+        let csv_str = "parentId,name
+1,25C
+2,15C
+2,Histamine
+4,25C"
+            .as_bytes();
+        let buf = BufReader::new(csv_str);
+        let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
+        let bulk: Bulk<TaskCondition> = Bulk::from_reader(&mut reader);
+
+        state
+            .insert_task_conditions(bulk, ConflictPolicy::Ignore, false)
+            .await?;
+
+        assert_eq!(
+            state.get_task_conditions().await?,
+            mock::task_condition::get_task_conditions()
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_filtered_task_conditions(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let orig_len = state.get_task_conditions().await?.len();
+
+        let filter = FilterGroup::<TaskConditionFieldName> {
+            filters: vec![vec![(
+                TaskConditionFieldName::Id,
+                Filter::Equal("2".into()),
+            )]],
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+        state
+            .delete_filtered_task_conditions(&filter, DeleteMode::Restrict)
+            .await?;
+
+        assert_eq!(state.get_task_conditions().await?.len(), orig_len - 2);
+        assert_eq!(state.get_filtered_task_conditions(&filter).await?.len(), 0);
+        Ok(())
+    }
+}
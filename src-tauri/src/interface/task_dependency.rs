@@ -0,0 +1,273 @@
+use super::{
+    aggregate::{AggregateRow, Metric},
+    cascade::{DeleteMode, DeleteReport},
+    page::{Page, PageRequest},
+    query::QueryResult,
+    DbError, InnerDbState, TableRow,
+};
+use crate::models::{
+    filter::{Count, FilterQueryBuilder},
+    task_dependency::{TaskDependency, TaskDependencyFieldName},
+};
+use anyhow::Result;
+use sqlx::{QueryBuilder, Sqlite};
+
+impl TableRow for TaskDependency {
+    const TABLE: &'static str = "task_dependencies";
+    const COLUMNS: &'static [&'static str] = &["parent_id", "child_id"];
+    const KEY_COLUMNS: &'static [&'static str] = &["parent_id", "child_id"];
+}
+
+impl InnerDbState {
+    pub async fn get_task_dependencies(&self) -> Result<Vec<TaskDependency>, DbError> {
+        match sqlx::query_as::<_, TaskDependency>(
+            "
+            SELECT parent_id, child_id FROM task_dependencies ORDER BY parent_id, child_id
+            ",
+        )
+        .fetch_all(&self.conn_pool)
+        .await
+        {
+            Ok(deps) => Ok(deps),
+            Err(e) => {
+                eprint!("Get task dependencies error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn get_filtered_task_dependencies(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskDependencyFieldName>,
+    ) -> Result<Vec<TaskDependency>, DbError> {
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new("SELECT parent_id, child_id FROM task_dependencies");
+        filter.add_filtered_query(&mut qb, true, true)?;
+
+        match qb
+            .build_query_as::<TaskDependency>()
+            .fetch_all(&self.conn_pool)
+            .await
+        {
+            Ok(deps) => Ok(deps),
+            Err(e) => {
+                eprint!("Get Filtered Task Dependency error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn get_filtered_task_dependencies_page(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskDependencyFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<TaskDependency>, DbError> {
+        self.get_page::<TaskDependency, _>(filter, page).await
+    }
+
+    pub async fn query_task_dependencies(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskDependencyFieldName>,
+        facets: Vec<TaskDependencyFieldName>,
+    ) -> Result<QueryResult<TaskDependency, TaskDependencyFieldName>, DbError> {
+        self.query::<TaskDependency, _>(filter, facets).await
+    }
+
+    pub async fn aggregate_task_dependencies(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskDependencyFieldName>,
+        group_by: &TaskDependencyFieldName,
+        metrics: &[Metric<TaskDependencyFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("task_dependencies", filter, group_by, metrics)
+            .await
+    }
+
+    pub async fn get_count_filtered_task_dependencies(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskDependencyFieldName>,
+    ) -> Result<u32, DbError> {
+        let mut qb: QueryBuilder<Sqlite> =
+            QueryBuilder::new("SELECT COUNT(*) as count FROM task_dependencies");
+        filter.add_filtered_query(&mut qb, true, false)?;
+
+        match qb
+            .build_query_as::<Count>()
+            .fetch_one(&self.conn_pool)
+            .await
+        {
+            Ok(count) => Ok(count.count),
+            Err(e) => {
+                eprint!("Get Filtered Task Dependency Count error: {e}");
+                Err(DbError::Query(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn insert_task_dependency(&self, dep: &TaskDependency) -> Result<(), DbError> {
+        match sqlx::query(
+            "INSERT INTO task_dependencies (parent_id, child_id)
+            VALUES(?, ?)
+            ",
+        )
+        .bind(&dep.parent_id)
+        .bind(&dep.child_id)
+        .execute(&self.conn_pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                eprint!("Insert Task Dependency error: {e}");
+                Err(DbError::Insert(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn delete_task_dependency(&self, dep: &TaskDependency) -> Result<(), DbError> {
+        match sqlx::query(
+            "DELETE FROM task_dependencies
+            WHERE parent_id = ? AND child_id = ?",
+        )
+        .bind(&dep.parent_id)
+        .bind(&dep.child_id)
+        .execute(&self.conn_pool)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                eprint!("Delete Task Dependency error: {e}");
+                Err(DbError::Delete(e.to_string()))
+            }
+        }
+    }
+
+    /// Counts the rows in each table that deleting the filtered task dependencies would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_task_dependencies(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskDependencyFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("task_dependencies", filter).await
+    }
+
+    pub async fn delete_filtered_task_dependencies(
+        &self,
+        filter: &impl FilterQueryBuilder<TaskDependencyFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("task_dependencies", filter, mode).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::mock;
+    use crate::models::filter::{Filter, FilterGroup, Order};
+    use crate::models::task_dependency::{TaskDependency, TaskDependencyFieldName};
+    use crate::InnerDbState;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use sqlx::{Pool, Sqlite};
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_task_dependencies(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let deps = state.get_task_dependencies().await?;
+
+        assert_eq!(deps, mock::task_dependency::get_task_dependencies());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_get_filtered_task_dependencies(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let filter = FilterGroup::<TaskDependencyFieldName> {
+            filters: vec![vec![(
+                TaskDependencyFieldName::ChildId,
+                Filter::Equal("3".into()),
+            )]],
+            order_by: vec![(TaskDependencyFieldName::ParentId, Order::Asc)],
+            limit: None,
+            offset: None,
+        };
+
+        let deps = state.get_filtered_task_dependencies(&filter).await?;
+        assert_eq!(
+            deps,
+            mock::task_dependency::get_filtered_task_dependencies()
+        );
+        assert_eq!(
+            state.get_count_filtered_task_dependencies(&filter).await?,
+            2
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_task_dependency(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let orig_len = state.get_task_dependencies().await?.len();
+
+        let expected = TaskDependency {
+            parent_id: "4".to_string(),
+            child_id: "5".to_string(),
+        };
+        state.insert_task_dependency(&expected).await?;
+
+        let deps = state.get_task_dependencies().await?;
+        assert_eq!(deps.len(), orig_len + 1);
+        assert!(deps.contains(&expected));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_insert_task_dependency_unknown_task(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        let dep = TaskDependency {
+            parent_id: "1".to_string(),
+            child_id: "does-not-exist".to_string(),
+        };
+        assert!(state.insert_task_dependency(&dep).await.is_err());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_task_dependency(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+        let orig_len = state.get_task_dependencies().await?.len();
+
+        let dep = TaskDependency {
+            parent_id: "1".to_string(),
+            child_id: "3".to_string(),
+        };
+        state.delete_task_dependency(&dep).await?;
+
+        let deps = state.get_task_dependencies().await?;
+        assert_eq!(deps.len(), orig_len - 1);
+        assert!(!deps.contains(&dep));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("full_db"))]
+    async fn test_delete_task_cascades_to_dependencies(pool: Pool<Sqlite>) -> Result<()> {
+        let state = InnerDbState { conn_pool: pool };
+
+        state.delete_task("3".to_string()).await?;
+
+        let deps = state
+            .get_filtered_task_dependencies(&FilterGroup::<TaskDependencyFieldName> {
+                filters: vec![vec![
+                    (TaskDependencyFieldName::ParentId, Filter::Equal("3".into())),
+                    (TaskDependencyFieldName::ChildId, Filter::Equal("3".into())),
+                ]],
+                order_by: vec![],
+                limit: None,
+                offset: None,
+            })
+            .await?;
+        assert_eq!(deps.len(), 0);
+        Ok(())
+    }
+}
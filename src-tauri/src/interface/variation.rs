@@ -1,13 +1,43 @@
-use super::bulk::Bulk;
-use super::{DbError, InnerDbState, SQLITE_BIND_LIMIT};
+use super::aggregate::{AggregateRow, Metric};
+use super::bulk::{Bulk, BulkInsertable, BulkReport, ConflictPolicy};
+use super::cascade::{DeleteMode, DeleteReport};
+use super::page::{Page, PageRequest};
+use super::query::QueryResult;
+use super::{DbError, InnerDbState, TableRow};
 use crate::models::filter::{Count, FilterQueryBuilder};
 use crate::models::variation::VariationDb;
-use crate::models::{
-    filter::FilterGroup,
-    variation::{Variation, VariationFieldName},
-};
+use crate::models::variation::{Variation, VariationFieldName};
 use anyhow::Result;
+use csv::Writer;
+use serde_json::{json, Value};
 use sqlx::{QueryBuilder, Sqlite};
+use std::io::Write;
+
+impl TableRow for VariationDb {
+    const TABLE: &'static str = "variations";
+    const COLUMNS: &'static [&'static str] = &[
+        "allele_name",
+        "chromosome",
+        "phys_loc",
+        "gen_loc",
+        "recomb_suppressor_start",
+        "recomb_suppressor_end",
+    ];
+    const KEY_COLUMNS: &'static [&'static str] = &["allele_name"];
+}
+
+impl BulkInsertable for VariationDb {
+    fn values(&self) -> Vec<Value> {
+        vec![
+            json!(self.allele_name),
+            json!(self.chromosome),
+            json!(self.phys_loc),
+            json!(self.gen_loc),
+            json!(self.recomb_suppressor_start),
+            json!(self.recomb_suppressor_end),
+        ]
+    }
+}
 
 impl InnerDbState {
     pub async fn get_variations(&self) -> Result<Vec<Variation>, DbError> {
@@ -29,12 +59,12 @@ impl InnerDbState {
     }
     pub async fn get_filtered_variations(
         &self,
-        filter: &FilterGroup<VariationFieldName>,
+        filter: &impl FilterQueryBuilder<VariationFieldName>,
     ) -> Result<Vec<Variation>, DbError> {
         let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
             "SELECT allele_name, chromosome, phys_loc, gen_loc, recomb_suppressor_start, recomb_suppressor_end FROM variations",
         );
-        filter.add_filtered_query(&mut qb, true, true);
+        filter.add_filtered_query(&mut qb, true, true)?;
         match qb
             .build_query_as::<VariationDb>()
             .fetch_all(&self.conn_pool)
@@ -48,13 +78,45 @@ impl InnerDbState {
         }
     }
 
+    pub async fn get_filtered_variations_page(
+        &self,
+        filter: &impl FilterQueryBuilder<VariationFieldName>,
+        page: &PageRequest,
+    ) -> Result<Page<Variation>, DbError> {
+        Ok(self
+            .get_page::<VariationDb, _>(filter, page)
+            .await?
+            .map(Variation::from))
+    }
+
+    pub async fn query_variations(
+        &self,
+        filter: &impl FilterQueryBuilder<VariationFieldName>,
+        facets: Vec<VariationFieldName>,
+    ) -> Result<QueryResult<Variation, VariationFieldName>, DbError> {
+        Ok(self
+            .query::<VariationDb, _>(filter, facets)
+            .await?
+            .map(Variation::from))
+    }
+
+    pub async fn aggregate_variations(
+        &self,
+        filter: &impl FilterQueryBuilder<VariationFieldName>,
+        group_by: &VariationFieldName,
+        metrics: &[Metric<VariationFieldName>],
+    ) -> Result<Vec<AggregateRow>, DbError> {
+        self.aggregate_table("variations", filter, group_by, metrics)
+            .await
+    }
+
     pub async fn get_count_filtered_variations(
         &self,
-        filter: &FilterGroup<VariationFieldName>,
+        filter: &impl FilterQueryBuilder<VariationFieldName>,
     ) -> Result<u32, DbError> {
         let mut qb: QueryBuilder<Sqlite> =
             QueryBuilder::new("SELECT COUNT(*) as count FROM variations");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter.add_filtered_query(&mut qb, true, false)?;
 
         match qb
             .build_query_as::<Count>()
@@ -97,69 +159,48 @@ impl InnerDbState {
         }
     }
 
-    pub async fn insert_variations(&self, bulk: Bulk<VariationDb>) -> Result<(), DbError> {
-        if !bulk.errors.is_empty() {
-            return Err(DbError::BulkInsert(format!(
-                "Found errors on {} lines",
-                bulk.errors.len()
-            )));
-        }
-        let bind_limit = SQLITE_BIND_LIMIT / 6;
-        let mut data = bulk.data.into_iter().peekable();
-        while data.peek().is_some() {
-            let chunk = data.by_ref().take(bind_limit - 1).collect::<Vec<_>>();
-            if chunk.len() > bind_limit {
-                return Err(DbError::BulkInsert(format!(
-                    "Row count exceeds max: {}",
-                    bind_limit
-                )));
-            }
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-                "INSERT OR IGNORE INTO variations (allele_name, chromosome, phys_loc, gen_loc, recomb_suppressor_start, recomb_suppressor_end) "
-            );
-            qb.push_values(chunk, |mut b, item| {
-                b.push_bind(item.allele_name)
-                    .push_bind(item.chromosome)
-                    .push_bind(item.phys_loc)
-                    .push_bind(item.gen_loc)
-                    .push_bind(item.recomb_suppressor_start)
-                    .push_bind(item.recomb_suppressor_end);
-            });
-
-            match qb.build().execute(&self.conn_pool).await {
-                Ok(_) => {}
-                Err(e) => {
-                    eprint!("Bulk insert error: {e}");
-                    return Err(DbError::BulkInsert(e.to_string()));
-                }
-            }
-        }
-        Ok(())
+    pub async fn insert_variations(
+        &self,
+        bulk: Bulk<VariationDb>,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<BulkReport, DbError> {
+        self.insert_bulk(bulk, policy, dry_run).await
     }
 
-    pub async fn delete_filtered_variations(
+    pub async fn export_filtered_variations<W: Write>(
         &self,
-        filter: &FilterGroup<VariationFieldName>,
-    ) -> Result<(), DbError> {
-        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("DELETE FROM variations");
-        filter.add_filtered_query(&mut qb, true, false);
+        filter: &impl FilterQueryBuilder<VariationFieldName>,
+        writer: &mut Writer<W>,
+    ) -> Result<usize, DbError> {
+        self.export_bulk::<VariationDb, _, _>(filter, writer).await
+    }
 
-        match qb.build().execute(&self.conn_pool).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                eprint!("Delete variations error: {e}");
-                Err(DbError::Delete(e.to_string()))
-            }
-        }
+    /// Counts the rows in each table that deleting the filtered variations would
+    /// cascade to, without deleting anything
+    pub async fn preview_delete_variations(
+        &self,
+        filter: &impl FilterQueryBuilder<VariationFieldName>,
+    ) -> Result<DeleteReport, DbError> {
+        self.preview_delete("variations", filter).await
+    }
+
+    pub async fn delete_filtered_variations(
+        &self,
+        filter: &impl FilterQueryBuilder<VariationFieldName>,
+        mode: DeleteMode,
+    ) -> Result<DeleteReport, DbError> {
+        self.delete_filtered("variations", filter, mode).await
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    use crate::interface::cascade::{DeletableTable, DeleteMode};
     use std::io::BufReader;
 
-    use crate::interface::bulk::Bulk;
+    use crate::interface::bulk::{Bulk, ConflictPolicy};
     use crate::interface::mock;
     use crate::models::chromosome_name::ChromosomeName;
     use crate::models::filter::{Filter, FilterGroup, Order};
@@ -205,7 +246,7 @@ mod test {
         let filter = FilterGroup::<VariationFieldName> {
             filters: vec![vec![(
                 VariationFieldName::GenLoc,
-                Filter::Range("-1.46".to_string(), false, "4.72".to_string(), true),
+                Filter::Range((-1.46).into(), false, 4.72.into(), true),
             )]],
             order_by: vec![(VariationFieldName::AlleleName, Order::Asc)],
             limit: None,
@@ -322,7 +363,9 @@ oxIs13,X,,,,"
         let mut reader = csv::ReaderBuilder::new().has_headers(true).from_reader(buf);
         let bulk: Bulk<VariationDb> = Bulk::from_reader(&mut reader);
 
-        state.insert_variations(bulk).await?;
+        state
+            .insert_variations(bulk, ConflictPolicy::Ignore, false)
+            .await?;
         let vis: Vec<Variation> = state.get_variations().await?;
 
         assert_eq!(expected, vis);
@@ -338,14 +381,16 @@ oxIs13,X,,,,"
         let delete_filter = &FilterGroup::<VariationFieldName> {
             filters: vec![vec![(
                 VariationFieldName::AlleleName,
-                Filter::Equal("oxEx219999".to_string()),
+                Filter::Equal("oxEx219999".into()),
             )]],
             order_by: vec![],
             limit: None,
             offset: None,
         };
 
-        state.delete_filtered_variations(delete_filter).await?;
+        state
+            .delete_filtered_variations(delete_filter, DeleteMode::Restrict)
+            .await?;
 
         variation = state.get_variations().await?;
         assert_eq!(variation.len(), mock::variation::get_variations().len() - 1);
@@ -375,7 +420,9 @@ oxIs13,X,,,,"
         let filtered_len = variation.len();
         assert!(filtered_len > 0);
 
-        state.delete_filtered_variations(filter).await?;
+        state
+            .delete_filtered_variations(filter, DeleteMode::Restrict)
+            .await?;
         variation = state.get_variations().await?;
 
         assert_eq!(variation.len(), orig_len - filtered_len);
@@ -391,14 +438,14 @@ oxIs13,X,,,,"
         let mut variation: Vec<Variation> = state.get_variations().await?;
         assert_eq!(variation.len(), mock::variation::get_variations().len());
 
-        let filter = &FilterGroup::<VariationFieldName> {
-            filters: vec![],
-            order_by: vec![],
-            limit: None,
-            offset: None,
-        };
-
-        state.delete_filtered_variations(filter).await?;
+        let preview = state.preview_delete_all(DeletableTable::Variations).await?;
+        state
+            .delete_all(
+                DeletableTable::Variations,
+                DeleteMode::Restrict,
+                &preview.confirmation,
+            )
+            .await?;
         variation = state.get_variations().await?;
 
         assert_eq!(variation.len(), 0);
@@ -0,0 +1,236 @@
+use super::{DbError, InnerDbState};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use ts_rs::TS;
+
+/// A database file that has been opened in the app
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_Workspace.ts")]
+#[serde(rename = "db_Workspace")]
+pub struct Workspace {
+    pub name: String,
+    pub path: String,
+    #[serde(rename = "isActive")]
+    pub is_active: bool,
+    /// False if the file was moved or deleted since it was last opened
+    pub exists: bool,
+}
+
+impl Workspace {
+    fn new(path: &Path, active_path: Option<&Path>) -> Workspace {
+        Workspace {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            is_active: active_path == Some(path),
+            exists: path.exists(),
+        }
+    }
+}
+
+/// The workspaces the app knows about, saved as json so the last used one can be
+/// reopened on the next launch
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct WorkspaceList {
+    #[serde(rename = "lastUsed")]
+    pub last_used: Option<PathBuf>,
+    pub paths: Vec<PathBuf>,
+}
+
+fn workspace_error(e: impl std::fmt::Display) -> DbError {
+    eprint!("Workspace error: {e}");
+    DbError::Workspace(e.to_string())
+}
+
+impl WorkspaceList {
+    /// Reads the list at given path, starting over if it's missing or unreadable
+    pub fn load(list_path: &Path) -> WorkspaceList {
+        fs::read_to_string(list_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, list_path: &Path) -> Result<(), DbError> {
+        let json = serde_json::to_string_pretty(self).map_err(workspace_error)?;
+        fs::write(list_path, json).map_err(workspace_error)
+    }
+
+    /// Adds the workspace if it's new and marks it as last used
+    pub fn set_last_used(&mut self, workspace_path: &Path) {
+        if !self.paths.iter().any(|path| path == workspace_path) {
+            self.paths.push(workspace_path.to_owned());
+        }
+        self.last_used = Some(workspace_path.to_owned());
+    }
+
+    pub fn get_workspaces(&self, active_path: Option<&Path>) -> Vec<Workspace> {
+        self.paths
+            .iter()
+            .map(|path| Workspace::new(path, active_path))
+            .collect()
+    }
+}
+
+impl InnerDbState {
+    /// Creates a new, empty database at given path and switches to it
+    pub async fn create_workspace(
+        &mut self,
+        path: &Path,
+        list_path: &Path,
+    ) -> Result<Workspace, DbError> {
+        if path.exists() {
+            return Err(DbError::Workspace(format!(
+                "{} already exists",
+                path.display()
+            )));
+        }
+        self.switch_workspace(path, list_path).await
+    }
+
+    /// Switches to an existing database
+    pub async fn open_workspace(
+        &mut self,
+        path: &Path,
+        list_path: &Path,
+    ) -> Result<Workspace, DbError> {
+        if !path.is_file() {
+            return Err(DbError::Workspace(format!(
+                "{} does not exist",
+                path.display()
+            )));
+        }
+        self.switch_workspace(path, list_path).await
+    }
+
+    pub async fn list_workspaces(&self, list_path: &Path) -> Result<Vec<Workspace>, DbError> {
+        let active_path = self.get_database_path().await?;
+        Ok(WorkspaceList::load(list_path).get_workspaces(active_path.as_deref()))
+    }
+
+    /// Forgets a workspace without deleting its file. Closing the open workspace
+    /// switches back to the default one, which can't be closed.
+    pub async fn close_workspace(
+        &mut self,
+        path: &Path,
+        list_path: &Path,
+        default_path: &Path,
+    ) -> Result<(), DbError> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+        let default_path = default_path
+            .canonicalize()
+            .unwrap_or_else(|_| default_path.to_owned());
+        if path == default_path {
+            return Err(DbError::Workspace(
+                "The default workspace cannot be closed".to_owned(),
+            ));
+        }
+        if self.get_database_path().await?.as_deref() == Some(path.as_path()) {
+            self.switch_workspace(&default_path, list_path).await?;
+        }
+
+        let mut list = WorkspaceList::load(list_path);
+        list.paths.retain(|known_path| *known_path != path);
+        list.save(list_path)
+    }
+
+    /// Opens the database at given path in place of the current one, which stays open
+    /// if the new one can't be opened
+    async fn switch_workspace(
+        &mut self,
+        path: &Path,
+        list_path: &Path,
+    ) -> Result<Workspace, DbError> {
+        let conn_pool = Self::connect(path).await.map_err(workspace_error)?;
+        let old_pool = std::mem::replace(&mut self.conn_pool, conn_pool);
+        old_pool.close().await;
+
+        let path = path.canonicalize().map_err(workspace_error)?;
+        let mut list = WorkspaceList::load(list_path);
+        list.set_last_used(&path);
+        list.save(list_path)?;
+        Ok(Workspace::new(&path, Some(&path)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interface::{
+        workspace::{Workspace, WorkspaceList},
+        DbError, InnerDbState,
+    };
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use std::{fs, path::Path};
+
+    fn workspace(path: &Path, is_active: bool) -> Workspace {
+        Workspace {
+            name: path.file_stem().unwrap().to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            is_active,
+            exists: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_switch_workspaces() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("ww-workspace-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir)?;
+        let list_path = dir.join("workspaces.json");
+        let default_path = dir.join("worm.sqlite");
+        let mut state = InnerDbState {
+            conn_pool: InnerDbState::connect(&default_path).await?,
+        };
+        let mut list = WorkspaceList::default();
+        list.set_last_used(&default_path.canonicalize()?);
+        list.save(&list_path)?;
+
+        let teaching = state
+            .create_workspace(&dir.join("teaching.sqlite"), &list_path)
+            .await?;
+        assert_eq!(teaching.name, "teaching");
+        assert_eq!(state.get_genes().await?, vec![]);
+        let res = state
+            .create_workspace(&dir.join("teaching.sqlite"), &list_path)
+            .await;
+        assert!(matches!(res, Err(DbError::Workspace(_))));
+
+        state.open_workspace(&default_path, &list_path).await?;
+        let default_path = default_path.canonicalize()?;
+        let teaching_path = Path::new(&teaching.path);
+        assert_eq!(
+            state.list_workspaces(&list_path).await?,
+            vec![
+                workspace(&default_path, true),
+                workspace(teaching_path, false)
+            ]
+        );
+        assert_eq!(
+            WorkspaceList::load(&list_path).last_used,
+            Some(default_path.clone())
+        );
+
+        state.open_workspace(teaching_path, &list_path).await?;
+        state
+            .close_workspace(teaching_path, &list_path, &default_path)
+            .await?;
+        assert_eq!(
+            state.list_workspaces(&list_path).await?,
+            vec![workspace(&default_path, true)]
+        );
+        let res = state
+            .close_workspace(&default_path, &list_path, &default_path)
+            .await;
+        assert!(matches!(res, Err(DbError::Workspace(_))));
+
+        state.conn_pool.close().await;
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -4,30 +4,48 @@
 )]
 use anyhow::Result;
 use directories::ProjectDirs;
-use sqlx::{
-    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
-    Pool, Sqlite,
-};
-use std::{path::Path, str::FromStr, time::Duration};
+use sqlx::{Pool, Sqlite};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 use tokio::sync::RwLock;
 
 mod interface;
-use interface::{bulk::Bulk, DbError, InnerDbState};
+use interface::{
+    aggregate::{AggregateRow, Metric},
+    audit::{AuditReport, RepairMode},
+    backup::Snapshot,
+    bulk::{Bulk, BulkReport, ConflictPolicy, ExportFormat},
+    cascade::{DeletableTable, DeleteAllPreview, DeleteMode, DeleteReport},
+    chromosome_map::ChromosomeMap,
+    genetics::LocatedGene,
+    journal::Change,
+    page::{Page, PageRequest},
+    query::QueryResult,
+    search::SearchHit,
+    seed::SeedFileReport,
+    workspace::{Workspace, WorkspaceList},
+    DbError, InnerDbState,
+};
+
+mod genetics;
+use genetics::{genotype::GenotypeMismatch, offspring::Offspring};
 
 mod models;
 use models::{
-    allele::{Allele, AlleleFieldName},
+    allele::{Allele, AlleleFieldName, AlleleJoinFieldName},
     allele_expr::{AlleleExpression, AlleleExpressionDb, AlleleExpressionFieldName},
+    chromosome_name::ChromosomeName,
     condition::{Condition, ConditionDb, ConditionFieldName},
     cross_design::{CrossDesign, CrossDesignFieldName},
     expr_relation::{ExpressionRelation, ExpressionRelationDb, ExpressionRelationFieldName},
-    filter::FilterGroup,
+    filter::{AnyFilter, Conjunction},
     gene::{Gene, GeneDb, GeneFieldName},
     phenotype::{Phenotype, PhenotypeDb, PhenotypeFieldName},
-    strain::{Strain, StrainFieldName},
+    strain::{Strain, StrainFieldName, StrainJoinFieldName, StrainSearchResults},
     strain_allele::{StrainAllele, StrainAlleleFieldName},
     task::{Task, TaskFieldName},
+    task_condition::{TaskCondition, TaskConditionFieldName},
+    task_dependency::{TaskDependency, TaskDependencyFieldName},
     variation::{Variation, VariationDb, VariationFieldName},
 };
 
@@ -36,96 +54,216 @@ async fn main() {
     let pool = sqlite_setup()
         .await
         .expect("Failed to set up sqlite3 database.");
+    let state = InnerDbState { conn_pool: pool };
+
+    // automatic snapshot on launch, at most once a day
+    if let Err(e) = state.create_startup_snapshot().await {
+        eprintln!("Failed to take startup snapshot: {e}");
+    }
 
     tauri::Builder::default()
-        .manage(DbState(RwLock::new(InnerDbState { conn_pool: pool })))
+        .manage(DbState(RwLock::new(state)))
         .invoke_handler(tauri::generate_handler![
             // genes
             get_genes,
             get_filtered_genes,
+            get_filtered_genes_page,
+            get_filtered_located_genes,
+            query_genes,
+            aggregate_genes,
             get_count_filtered_genes,
             insert_gene,
             insert_genes_from_file,
+            export_filtered_genes,
+            preview_delete_genes,
             delete_filtered_genes,
             // conditions
             get_conditions,
             get_filtered_conditions,
+            get_filtered_conditions_page,
+            query_conditions,
+            aggregate_conditions,
             get_count_filtered_conditions,
             get_altering_conditions,
             insert_condition,
             insert_conditions_from_file,
+            export_filtered_conditions,
+            preview_delete_conditions,
             delete_filtered_conditions,
             // phenotypes
             get_phenotypes,
             get_filtered_phenotypes,
+            get_filtered_phenotypes_page,
+            query_phenotypes,
+            aggregate_phenotypes,
             get_count_filtered_phenotypes,
             get_altering_phenotypes,
             insert_phenotype,
             insert_phenotypes_from_file,
+            export_filtered_phenotypes,
+            preview_delete_phenotypes,
             delete_filtered_phenotypes,
             // variations
             get_variations,
             get_filtered_variations,
+            get_filtered_variations_page,
+            query_variations,
+            aggregate_variations,
             get_count_filtered_variations,
             insert_variation,
             insert_variations_from_file,
+            export_filtered_variations,
+            preview_delete_variations,
             delete_filtered_variations,
             // allele_exprs
             get_allele_exprs,
             get_filtered_allele_exprs,
+            get_filtered_allele_exprs_page,
+            query_allele_exprs,
+            aggregate_allele_exprs,
             get_count_filtered_allele_exprs,
             insert_allele_expr,
             insert_allele_exprs_from_file,
+            export_filtered_allele_exprs,
+            preview_delete_allele_exprs,
             delete_filtered_allele_exprs,
             // alleles
             get_alleles,
             get_filtered_alleles,
+            get_filtered_alleles_page,
+            query_alleles,
+            query_joined_alleles,
+            aggregate_alleles,
             get_count_filtered_alleles,
             get_filtered_alleles_with_gene_filter,
+            get_filtered_alleles_with_gene_filter_page,
+            get_filtered_joined_alleles,
+            get_filtered_joined_alleles_page,
+            get_count_filtered_joined_alleles,
+            aggregate_joined_alleles,
             insert_allele,
             insert_alleles_from_file,
+            export_filtered_alleles,
+            preview_delete_alleles,
             delete_filtered_alleles,
             // expr_relations
             get_expr_relations,
             get_filtered_expr_relations,
+            get_filtered_expr_relations_page,
+            query_expr_relations,
+            aggregate_expr_relations,
             get_count_filtered_expr_relations,
             insert_expr_relation,
             insert_expr_relations_from_file,
+            export_filtered_expr_relations,
+            preview_delete_expr_relations,
             delete_filtered_expr_relations,
             // tasks
             get_tasks,
             get_filtered_tasks,
+            get_filtered_tasks_page,
+            query_tasks,
+            aggregate_tasks,
             insert_task,
             update_task,
             delete_task,
             delete_tasks,
             delete_all_tasks,
+            // task_dependencies
+            get_task_dependencies,
+            get_filtered_task_dependency,
+            get_filtered_task_dependencies_page,
+            query_task_dependencies,
+            aggregate_task_dependencies,
+            get_count_filtered_task_dependencies,
+            insert_task_dependency,
+            delete_task_dependency,
+            preview_delete_task_dependencies,
+            delete_filtered_task_dependencies,
+            // task_conditions
+            get_task_conditions,
+            get_filtered_task_conditions,
+            get_filtered_task_conditions_page,
+            query_task_conditions,
+            aggregate_task_conditions,
+            get_count_filtered_task_conditions,
+            insert_task_condition,
+            insert_task_conditions_from_file,
+            export_filtered_task_conditions,
+            preview_delete_task_conditions,
+            delete_filtered_task_conditions,
             // cross_designs
             get_cross_designs,
             get_filtered_cross_designs,
+            get_filtered_cross_designs_page,
+            query_cross_designs,
+            aggregate_cross_designs,
             insert_cross_design,
             update_cross_design,
             delete_cross_design,
             // strains
             get_strains,
             get_filtered_strains,
+            get_filtered_strains_page,
+            query_strains,
+            aggregate_strains,
             get_count_filtered_strains,
+            search_strains,
+            aggregate_joined_strains,
             insert_strain,
             insert_strains_from_file,
+            export_filtered_strains,
             update_strain,
+            preview_delete_strains,
             delete_filtered_strains,
             // strain_alleles,
             get_strain_alleles,
             get_filtered_strain_alleles,
+            get_filtered_strain_alleles_page,
+            query_strain_alleles,
+            aggregate_strain_alleles,
             get_count_filtered_strain_alleles,
             insert_strain_allele,
             insert_strain_alleles_from_file,
+            export_filtered_strain_alleles,
+            preview_delete_strain_alleles,
             delete_filtered_strain_alleles,
+            // genetics
+            cross_strains,
+            self_cross_strain,
+            validate_strains,
+            // backups
+            backup_database,
+            create_snapshot,
+            list_snapshots,
+            restore_snapshot,
+            // workspaces
+            create_workspace,
+            open_workspace,
+            list_workspaces,
+            close_workspace,
+            // seeding
+            seed_reference_data,
+            // audit
+            audit_database,
+            // journal
+            list_recent_changes,
+            undo_last_change,
+            redo_change,
+            // deleting everything
+            preview_delete_all,
+            delete_all,
+            // search
+            global_search,
+            // chromosome map
+            get_chromosome_map,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
+/// Commands that write to the database take the write lock, so no other command's
+/// writes end up in their change in the journal
 pub struct DbState(pub RwLock<InnerDbState>);
 
 #[derive(Error, Debug)]
@@ -134,32 +272,48 @@ pub enum SqlSetupError {
     NoProjectDir,
 }
 
-async fn sqlite_setup() -> Result<Pool<Sqlite>> {
+fn get_data_dir() -> Result<PathBuf> {
     let proj_dirs =
         ProjectDirs::from("edu", "UofUBiology", "WormWorld").ok_or(SqlSetupError::NoProjectDir)?;
-    let database_dir = proj_dirs.data_dir().join("db");
-    std::fs::create_dir_all(database_dir.clone())?;
-
-    let database_url = format!(
-        "sqlite:///{}",
-        database_dir.join("worm.sqlite").to_str().unwrap()
-    );
-    println!("{}", database_url);
-
-    let pool_timeout = Duration::from_secs(30);
-    let connection_options = SqliteConnectOptions::from_str(&database_url)?
-        .create_if_missing(true)
-        .journal_mode(SqliteJournalMode::Wal)
-        .synchronous(SqliteSynchronous::Normal)
-        .busy_timeout(pool_timeout);
-
-    let sqlite_pool = SqlitePoolOptions::new()
-        .max_connections(100)
-        .acquire_timeout(pool_timeout)
-        .connect_with(connection_options)
-        .await?;
+    Ok(proj_dirs.data_dir().to_owned())
+}
 
-    sqlx::migrate!().run(&sqlite_pool).await?;
+/// The default workspace
+fn get_database_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("db").join("worm.sqlite"))
+}
+
+fn get_workspace_list_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("workspaces.json"))
+}
+
+/// Opens the last used workspace, or the default one if that's gone
+async fn sqlite_setup() -> Result<Pool<Sqlite>> {
+    let default_path = get_database_path()?;
+    std::fs::create_dir_all(default_path.parent().unwrap())?;
+
+    let list_path = get_workspace_list_path()?;
+    let mut list = WorkspaceList::load(&list_path);
+    let last_used = list.last_used.clone().filter(|path| path.is_file());
+
+    let (database_path, sqlite_pool) = match last_used {
+        Some(path) => match InnerDbState::connect(&path).await {
+            Ok(sqlite_pool) => (path, sqlite_pool),
+            Err(e) => {
+                eprintln!("Failed to open last used workspace: {e}");
+                let sqlite_pool = InnerDbState::connect(&default_path).await?;
+                (default_path, sqlite_pool)
+            }
+        },
+        None => {
+            let sqlite_pool = InnerDbState::connect(&default_path).await?;
+            (default_path, sqlite_pool)
+        }
+    };
+    println!("sqlite:///{}", database_path.to_str().unwrap());
+
+    list.set_last_used(&database_path.canonicalize()?);
+    list.save(&list_path)?;
 
     Ok(sqlite_pool)
 }
@@ -174,16 +328,58 @@ async fn get_genes(state: tauri::State<'_, DbState>) -> Result<Vec<Gene>, DbErro
 #[tauri::command]
 async fn get_filtered_genes(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<GeneFieldName>,
+    filter: AnyFilter<GeneFieldName>,
 ) -> Result<Vec<Gene>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_genes(&filter).await
 }
 
+#[tauri::command]
+async fn get_filtered_genes_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<GeneFieldName>,
+    page: PageRequest,
+) -> Result<Page<Gene>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_filtered_genes_page(&filter, &page).await
+}
+
+#[tauri::command]
+async fn get_filtered_located_genes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<GeneFieldName>,
+) -> Result<Vec<LocatedGene>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_filtered_located_genes(&filter).await
+}
+
+#[tauri::command]
+async fn query_genes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<GeneFieldName>,
+    facets: Vec<GeneFieldName>,
+) -> Result<QueryResult<Gene, GeneFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_genes(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_genes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<GeneFieldName>,
+    group_by: GeneFieldName,
+    metrics: Vec<Metric<GeneFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_genes(&filter, &group_by, &metrics)
+        .await
+}
+
 #[tauri::command]
 async fn get_count_filtered_genes(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<GeneFieldName>,
+    filter: AnyFilter<GeneFieldName>,
 ) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_count_filtered_genes(&filter).await
@@ -191,29 +387,65 @@ async fn get_count_filtered_genes(
 
 #[tauri::command]
 async fn insert_gene(state: tauri::State<'_, DbState>, gene: Gene) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.insert_gene(&gene).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_gene(&gene).await;
+    state_guard.record_change("Insert gene").await?;
+    res
 }
 
 #[tauri::command]
 async fn insert_genes_from_file(
     state: tauri::State<'_, DbState>,
     path: String,
-) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    match Bulk::<GeneDb>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_genes(bulk).await,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<GeneDb>::new(Path::new(&path)) {
+        Ok(bulk) => state_guard.insert_genes(bulk, policy, dry_run).await,
         Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard.record_change("Import genes").await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_genes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<GeneFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_genes(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
     }
 }
 
 #[tauri::command]
-async fn delete_filtered_genes(
+async fn preview_delete_genes(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<GeneFieldName>,
-) -> Result<(), DbError> {
+    filter: AnyFilter<GeneFieldName>,
+) -> Result<DeleteReport, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_filtered_genes(&filter).await
+    state_guard.preview_delete_genes(&filter).await
+}
+
+#[tauri::command]
+async fn delete_filtered_genes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<GeneFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_filtered_genes(&filter, mode).await;
+    state_guard.record_change("Delete genes").await?;
+    res
 }
 /* #endregion genes */
 
@@ -227,16 +459,51 @@ async fn get_conditions(state: tauri::State<'_, DbState>) -> Result<Vec<Conditio
 #[tauri::command]
 async fn get_filtered_conditions(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<ConditionFieldName>,
+    filter: AnyFilter<ConditionFieldName>,
 ) -> Result<Vec<Condition>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_conditions(&filter).await
 }
 
+#[tauri::command]
+async fn get_filtered_conditions_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ConditionFieldName>,
+    page: PageRequest,
+) -> Result<Page<Condition>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_conditions_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ConditionFieldName>,
+    facets: Vec<ConditionFieldName>,
+) -> Result<QueryResult<Condition, ConditionFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_conditions(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ConditionFieldName>,
+    group_by: ConditionFieldName,
+    metrics: Vec<Metric<ConditionFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_conditions(&filter, &group_by, &metrics)
+        .await
+}
+
 #[tauri::command]
 async fn get_count_filtered_conditions(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<ConditionFieldName>,
+    filter: AnyFilter<ConditionFieldName>,
 ) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_count_filtered_conditions(&filter).await
@@ -245,8 +512,8 @@ async fn get_count_filtered_conditions(
 #[tauri::command]
 async fn get_altering_conditions(
     state: tauri::State<'_, DbState>,
-    expr_relation_filter: FilterGroup<ExpressionRelationFieldName>,
-    condition_filter: FilterGroup<ConditionFieldName>,
+    expr_relation_filter: AnyFilter<ExpressionRelationFieldName>,
+    condition_filter: AnyFilter<ConditionFieldName>,
 ) -> Result<Vec<Condition>, DbError> {
     let state_guard = state.0.read().await;
     state_guard
@@ -259,29 +526,65 @@ async fn insert_condition(
     state: tauri::State<'_, DbState>,
     condition: Condition,
 ) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.insert_condition(&condition).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_condition(&condition).await;
+    state_guard.record_change("Insert condition").await?;
+    res
 }
 
 #[tauri::command]
 async fn insert_conditions_from_file(
     state: tauri::State<'_, DbState>,
     path: String,
-) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    match Bulk::<ConditionDb>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_conditions(bulk).await,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<ConditionDb>::new(Path::new(&path)) {
+        Ok(bulk) => state_guard.insert_conditions(bulk, policy, dry_run).await,
         Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard.record_change("Import conditions").await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ConditionFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_conditions(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
     }
 }
 
 #[tauri::command]
-async fn delete_filtered_conditions(
+async fn preview_delete_conditions(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<ConditionFieldName>,
-) -> Result<(), DbError> {
+    filter: AnyFilter<ConditionFieldName>,
+) -> Result<DeleteReport, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_filtered_conditions(&filter).await
+    state_guard.preview_delete_conditions(&filter).await
+}
+
+#[tauri::command]
+async fn delete_filtered_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ConditionFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_filtered_conditions(&filter, mode).await;
+    state_guard.record_change("Delete conditions").await?;
+    res
 }
 /* #endregion conditions */
 
@@ -295,16 +598,51 @@ async fn get_phenotypes(state: tauri::State<'_, DbState>) -> Result<Vec<Phenotyp
 #[tauri::command]
 async fn get_filtered_phenotypes(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<PhenotypeFieldName>,
+    filter: AnyFilter<PhenotypeFieldName>,
 ) -> Result<Vec<Phenotype>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_phenotypes(&filter).await
 }
 
+#[tauri::command]
+async fn get_filtered_phenotypes_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<PhenotypeFieldName>,
+    page: PageRequest,
+) -> Result<Page<Phenotype>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_phenotypes_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_phenotypes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<PhenotypeFieldName>,
+    facets: Vec<PhenotypeFieldName>,
+) -> Result<QueryResult<Phenotype, PhenotypeFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_phenotypes(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_phenotypes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<PhenotypeFieldName>,
+    group_by: PhenotypeFieldName,
+    metrics: Vec<Metric<PhenotypeFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_phenotypes(&filter, &group_by, &metrics)
+        .await
+}
+
 #[tauri::command]
 async fn get_count_filtered_phenotypes(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<PhenotypeFieldName>,
+    filter: AnyFilter<PhenotypeFieldName>,
 ) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_count_filtered_phenotypes(&filter).await
@@ -313,8 +651,8 @@ async fn get_count_filtered_phenotypes(
 #[tauri::command]
 async fn get_altering_phenotypes(
     state: tauri::State<'_, DbState>,
-    expr_relation_filter: FilterGroup<ExpressionRelationFieldName>,
-    phenotype_filter: FilterGroup<PhenotypeFieldName>,
+    expr_relation_filter: AnyFilter<ExpressionRelationFieldName>,
+    phenotype_filter: AnyFilter<PhenotypeFieldName>,
 ) -> Result<Vec<Phenotype>, DbError> {
     let state_guard = state.0.read().await;
     state_guard
@@ -327,28 +665,64 @@ async fn insert_phenotype(
     state: tauri::State<'_, DbState>,
     phenotype: Phenotype,
 ) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.insert_phenotype(&phenotype).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_phenotype(&phenotype).await;
+    state_guard.record_change("Insert phenotype").await?;
+    res
 }
 
 #[tauri::command]
 async fn insert_phenotypes_from_file(
     state: tauri::State<'_, DbState>,
     path: String,
-) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    match Bulk::<PhenotypeDb>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_phenotypes(bulk).await,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<PhenotypeDb>::new(Path::new(&path)) {
+        Ok(bulk) => state_guard.insert_phenotypes(bulk, policy, dry_run).await,
         Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard.record_change("Import phenotypes").await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_phenotypes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<PhenotypeFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_phenotypes(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
     }
 }
 #[tauri::command]
-async fn delete_filtered_phenotypes(
+async fn preview_delete_phenotypes(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<PhenotypeFieldName>,
-) -> Result<(), DbError> {
+    filter: AnyFilter<PhenotypeFieldName>,
+) -> Result<DeleteReport, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_filtered_phenotypes(&filter).await
+    state_guard.preview_delete_phenotypes(&filter).await
+}
+
+#[tauri::command]
+async fn delete_filtered_phenotypes(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<PhenotypeFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_filtered_phenotypes(&filter, mode).await;
+    state_guard.record_change("Delete phenotypes").await?;
+    res
 }
 /* #endregion phenotypes */
 
@@ -362,16 +736,51 @@ async fn get_variations(state: tauri::State<'_, DbState>) -> Result<Vec<Variatio
 #[tauri::command]
 async fn get_filtered_variations(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<VariationFieldName>,
+    filter: AnyFilter<VariationFieldName>,
 ) -> Result<Vec<Variation>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_variations(&filter).await
 }
 
+#[tauri::command]
+async fn get_filtered_variations_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<VariationFieldName>,
+    page: PageRequest,
+) -> Result<Page<Variation>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_variations_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_variations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<VariationFieldName>,
+    facets: Vec<VariationFieldName>,
+) -> Result<QueryResult<Variation, VariationFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_variations(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_variations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<VariationFieldName>,
+    group_by: VariationFieldName,
+    metrics: Vec<Metric<VariationFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_variations(&filter, &group_by, &metrics)
+        .await
+}
+
 #[tauri::command]
 async fn get_count_filtered_variations(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<VariationFieldName>,
+    filter: AnyFilter<VariationFieldName>,
 ) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_count_filtered_variations(&filter).await
@@ -382,29 +791,65 @@ async fn insert_variation(
     state: tauri::State<'_, DbState>,
     variation: Variation,
 ) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.insert_variation(&variation).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_variation(&variation).await;
+    state_guard.record_change("Insert variation").await?;
+    res
 }
 
 #[tauri::command]
 async fn insert_variations_from_file(
     state: tauri::State<'_, DbState>,
     path: String,
-) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    match Bulk::<VariationDb>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_variations(bulk).await,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<VariationDb>::new(Path::new(&path)) {
+        Ok(bulk) => state_guard.insert_variations(bulk, policy, dry_run).await,
         Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard.record_change("Import variations").await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_variations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<VariationFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_variations(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
     }
 }
 
 #[tauri::command]
-async fn delete_filtered_variations(
+async fn preview_delete_variations(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<VariationFieldName>,
-) -> Result<(), DbError> {
+    filter: AnyFilter<VariationFieldName>,
+) -> Result<DeleteReport, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_filtered_variations(&filter).await
+    state_guard.preview_delete_variations(&filter).await
+}
+
+#[tauri::command]
+async fn delete_filtered_variations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<VariationFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_filtered_variations(&filter, mode).await;
+    state_guard.record_change("Delete variations").await?;
+    res
 }
 /* #endregion variations */
 
@@ -420,16 +865,51 @@ async fn get_allele_exprs(
 #[tauri::command]
 async fn get_filtered_allele_exprs(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<AlleleExpressionFieldName>,
+    filter: AnyFilter<AlleleExpressionFieldName>,
 ) -> Result<Vec<AlleleExpression>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_allele_exprs(&filter).await
 }
 
+#[tauri::command]
+async fn get_filtered_allele_exprs_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleExpressionFieldName>,
+    page: PageRequest,
+) -> Result<Page<AlleleExpression>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_allele_exprs_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_allele_exprs(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleExpressionFieldName>,
+    facets: Vec<AlleleExpressionFieldName>,
+) -> Result<QueryResult<AlleleExpression, AlleleExpressionFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_allele_exprs(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_allele_exprs(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleExpressionFieldName>,
+    group_by: AlleleExpressionFieldName,
+    metrics: Vec<Metric<AlleleExpressionFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_allele_exprs(&filter, &group_by, &metrics)
+        .await
+}
+
 #[tauri::command]
 async fn get_count_filtered_allele_exprs(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<AlleleExpressionFieldName>,
+    filter: AnyFilter<AlleleExpressionFieldName>,
 ) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_count_filtered_allele_exprs(&filter).await
@@ -440,29 +920,73 @@ async fn insert_allele_expr(
     state: tauri::State<'_, DbState>,
     allele_expr: AlleleExpression,
 ) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.insert_allele_expr(&allele_expr).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_allele_expr(&allele_expr).await;
+    state_guard
+        .record_change("Insert allele expression")
+        .await?;
+    res
 }
 
 #[tauri::command]
 async fn insert_allele_exprs_from_file(
     state: tauri::State<'_, DbState>,
     path: String,
-) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    match Bulk::<AlleleExpressionDb>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_allele_exprs(bulk).await,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<AlleleExpressionDb>::new(Path::new(&path)) {
+        Ok(bulk) => state_guard.insert_allele_exprs(bulk, policy, dry_run).await,
         Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard
+        .record_change("Import allele expressions")
+        .await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_allele_exprs(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleExpressionFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_allele_exprs(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
     }
 }
 
 #[tauri::command]
-async fn delete_filtered_allele_exprs(
+async fn preview_delete_allele_exprs(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<AlleleExpressionFieldName>,
-) -> Result<(), DbError> {
+    filter: AnyFilter<AlleleExpressionFieldName>,
+) -> Result<DeleteReport, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_filtered_allele_exprs(&filter).await
+    state_guard.preview_delete_allele_exprs(&filter).await
+}
+
+#[tauri::command]
+async fn delete_filtered_allele_exprs(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleExpressionFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard
+        .delete_filtered_allele_exprs(&filter, mode)
+        .await;
+    state_guard
+        .record_change("Delete allele expressions")
+        .await?;
+    res
 }
 
 #[tauri::command]
@@ -474,145 +998,409 @@ async fn get_alleles(state: tauri::State<'_, DbState>) -> Result<Vec<Allele>, Db
 #[tauri::command]
 async fn get_filtered_alleles(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<AlleleFieldName>,
+    filter: AnyFilter<AlleleFieldName>,
 ) -> Result<Vec<Allele>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_alleles(&filter).await
 }
 
 #[tauri::command]
-async fn get_count_filtered_alleles(
+async fn get_filtered_alleles_page(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<AlleleFieldName>,
-) -> Result<u32, DbError> {
+    filter: AnyFilter<AlleleFieldName>,
+    page: PageRequest,
+) -> Result<Page<Allele>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.get_count_filtered_alleles(&filter).await
+    state_guard.get_filtered_alleles_page(&filter, &page).await
 }
 
 #[tauri::command]
-async fn get_filtered_alleles_with_gene_filter(
+async fn query_alleles(
     state: tauri::State<'_, DbState>,
-    allele_filter: FilterGroup<AlleleFieldName>,
-    gene_filter: FilterGroup<GeneFieldName>,
-) -> Result<Vec<(Allele, Gene)>, DbError> {
+    filter: AnyFilter<AlleleFieldName>,
+    facets: Vec<AlleleFieldName>,
+) -> Result<QueryResult<Allele, AlleleFieldName>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard
-        .get_filtered_alleles_with_gene_filter(&allele_filter, &gene_filter)
-        .await
+    state_guard.query_alleles(&filter, facets).await
 }
 
 #[tauri::command]
-async fn insert_allele(state: tauri::State<'_, DbState>, allele: Allele) -> Result<(), DbError> {
+async fn query_joined_alleles(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleJoinFieldName>,
+    facets: Vec<AlleleJoinFieldName>,
+) -> Result<QueryResult<Allele, AlleleJoinFieldName>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.insert_allele(&allele).await
+    state_guard.query_joined_alleles(&filter, facets).await
 }
 
 #[tauri::command]
-async fn insert_alleles_from_file(
+async fn aggregate_alleles(
     state: tauri::State<'_, DbState>,
-    path: String,
-) -> Result<(), DbError> {
+    filter: AnyFilter<AlleleFieldName>,
+    group_by: AlleleFieldName,
+    metrics: Vec<Metric<AlleleFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
     let state_guard = state.0.read().await;
-    match Bulk::<Allele>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_alleles(bulk).await,
-        Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
-    }
+    state_guard
+        .aggregate_alleles(&filter, &group_by, &metrics)
+        .await
 }
 
 #[tauri::command]
-async fn delete_filtered_alleles(
+async fn get_count_filtered_alleles(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<AlleleFieldName>,
-) -> Result<(), DbError> {
+    filter: AnyFilter<AlleleFieldName>,
+) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_filtered_alleles(&filter).await
+    state_guard.get_count_filtered_alleles(&filter).await
 }
 
 #[tauri::command]
-async fn get_expr_relations(
+async fn get_filtered_alleles_with_gene_filter(
     state: tauri::State<'_, DbState>,
-) -> Result<Vec<ExpressionRelation>, DbError> {
+    allele_filter: AnyFilter<AlleleFieldName>,
+    gene_filter: AnyFilter<GeneFieldName>,
+    conjunction: Conjunction,
+) -> Result<Vec<(Allele, Gene)>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.get_expr_relations().await
+    state_guard
+        .get_filtered_alleles_with_gene_filter(&allele_filter, &gene_filter, conjunction)
+        .await
 }
 
 #[tauri::command]
-async fn get_filtered_expr_relations(
+async fn get_filtered_alleles_with_gene_filter_page(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<ExpressionRelationFieldName>,
-) -> Result<Vec<ExpressionRelation>, DbError> {
+    allele_filter: AnyFilter<AlleleFieldName>,
+    gene_filter: AnyFilter<GeneFieldName>,
+    conjunction: Conjunction,
+    page: PageRequest,
+) -> Result<Page<(Allele, Gene)>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.get_filtered_expr_relations(&filter).await
+    state_guard
+        .get_filtered_alleles_with_gene_filter_page(
+            &allele_filter,
+            &gene_filter,
+            conjunction,
+            &page,
+        )
+        .await
 }
 
 #[tauri::command]
-async fn get_count_filtered_expr_relations(
+async fn get_filtered_joined_alleles(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<ExpressionRelationFieldName>,
-) -> Result<u32, DbError> {
+    filter: AnyFilter<AlleleJoinFieldName>,
+) -> Result<Vec<Allele>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.get_count_filtered_expr_relations(&filter).await
+    state_guard.get_filtered_joined_alleles(&filter).await
 }
 
 #[tauri::command]
-async fn insert_expr_relation(
+async fn get_filtered_joined_alleles_page(
     state: tauri::State<'_, DbState>,
-    expr_relation: ExpressionRelation,
-) -> Result<(), DbError> {
+    filter: AnyFilter<AlleleJoinFieldName>,
+    page: PageRequest,
+) -> Result<Page<Allele>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.insert_expr_relation(&expr_relation).await
+    state_guard
+        .get_filtered_joined_alleles_page(&filter, &page)
+        .await
 }
 
 #[tauri::command]
-async fn insert_expr_relations_from_file(
+async fn get_count_filtered_joined_alleles(
     state: tauri::State<'_, DbState>,
-    path: String,
-) -> Result<(), DbError> {
+    filter: AnyFilter<AlleleJoinFieldName>,
+) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
-    match Bulk::<ExpressionRelationDb>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_expr_relations(bulk).await,
-        Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
-    }
+    state_guard.get_count_filtered_joined_alleles(&filter).await
 }
+
 #[tauri::command]
-async fn delete_filtered_expr_relations(
+async fn aggregate_joined_alleles(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<ExpressionRelationFieldName>,
-) -> Result<(), DbError> {
+    filter: AnyFilter<AlleleJoinFieldName>,
+    group_by: AlleleJoinFieldName,
+    metrics: Vec<Metric<AlleleJoinFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_filtered_expr_relations(&filter).await
+    state_guard
+        .aggregate_joined_alleles(&filter, &group_by, &metrics)
+        .await
 }
 
 #[tauri::command]
-async fn get_tasks(state: tauri::State<'_, DbState>) -> Result<Vec<Task>, DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.get_tasks().await
+async fn insert_allele(state: tauri::State<'_, DbState>, allele: Allele) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_allele(&allele).await;
+    state_guard.record_change("Insert allele").await?;
+    res
 }
 
 #[tauri::command]
-async fn get_filtered_tasks(
+async fn insert_alleles_from_file(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<TaskFieldName>,
-) -> Result<Vec<Task>, DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.get_filtered_tasks(&filter).await
+    path: String,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<Allele>::new(Path::new(&path)) {
+        Ok(bulk) => state_guard.insert_alleles(bulk, policy, dry_run).await,
+        Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard.record_change("Import alleles").await?;
+    res
 }
+
 #[tauri::command]
-async fn insert_task(state: tauri::State<'_, DbState>, task: Task) -> Result<(), DbError> {
+async fn export_filtered_alleles(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_alleles(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
+    }
+}
+
+#[tauri::command]
+async fn preview_delete_alleles(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleFieldName>,
+) -> Result<DeleteReport, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.insert_task(&task).await
+    state_guard.preview_delete_alleles(&filter).await
 }
 
 #[tauri::command]
-async fn update_task(state: tauri::State<'_, DbState>, task: Task) -> Result<(), DbError> {
+async fn delete_filtered_alleles(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<AlleleFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_filtered_alleles(&filter, mode).await;
+    state_guard.record_change("Delete alleles").await?;
+    res
+}
+
+#[tauri::command]
+async fn get_expr_relations(
+    state: tauri::State<'_, DbState>,
+) -> Result<Vec<ExpressionRelation>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.update_task(&task).await
+    state_guard.get_expr_relations().await
 }
 
 #[tauri::command]
-async fn delete_task(state: tauri::State<'_, DbState>, id: String) -> Result<(), DbError> {
+async fn get_filtered_expr_relations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ExpressionRelationFieldName>,
+) -> Result<Vec<ExpressionRelation>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_filtered_expr_relations(&filter).await
+}
+
+#[tauri::command]
+async fn get_filtered_expr_relations_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ExpressionRelationFieldName>,
+    page: PageRequest,
+) -> Result<Page<ExpressionRelation>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_expr_relations_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_expr_relations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ExpressionRelationFieldName>,
+    facets: Vec<ExpressionRelationFieldName>,
+) -> Result<QueryResult<ExpressionRelation, ExpressionRelationFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_expr_relations(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_expr_relations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ExpressionRelationFieldName>,
+    group_by: ExpressionRelationFieldName,
+    metrics: Vec<Metric<ExpressionRelationFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_expr_relations(&filter, &group_by, &metrics)
+        .await
+}
+
+#[tauri::command]
+async fn get_count_filtered_expr_relations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ExpressionRelationFieldName>,
+) -> Result<u32, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_count_filtered_expr_relations(&filter).await
+}
+
+#[tauri::command]
+async fn insert_expr_relation(
+    state: tauri::State<'_, DbState>,
+    expr_relation: ExpressionRelation,
+) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_expr_relation(&expr_relation).await;
+    state_guard
+        .record_change("Insert expression relation")
+        .await?;
+    res
+}
+
+#[tauri::command]
+async fn insert_expr_relations_from_file(
+    state: tauri::State<'_, DbState>,
+    path: String,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<ExpressionRelationDb>::new(Path::new(&path)) {
+        Ok(bulk) => {
+            state_guard
+                .insert_expr_relations(bulk, policy, dry_run)
+                .await
+        }
+        Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard
+        .record_change("Import expression relations")
+        .await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_expr_relations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ExpressionRelationFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_expr_relations(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
+    }
+}
+#[tauri::command]
+async fn preview_delete_expr_relations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ExpressionRelationFieldName>,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.preview_delete_expr_relations(&filter).await
+}
+
+#[tauri::command]
+async fn delete_filtered_expr_relations(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<ExpressionRelationFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard
+        .delete_filtered_expr_relations(&filter, mode)
+        .await;
+    state_guard
+        .record_change("Delete expression relations")
+        .await?;
+    res
+}
+
+#[tauri::command]
+async fn get_tasks(state: tauri::State<'_, DbState>) -> Result<Vec<Task>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_tasks().await
+}
+
+#[tauri::command]
+async fn get_filtered_tasks(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskFieldName>,
+) -> Result<Vec<Task>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_filtered_tasks(&filter).await
+}
+
+#[tauri::command]
+async fn get_filtered_tasks_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskFieldName>,
+    page: PageRequest,
+) -> Result<Page<Task>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_filtered_tasks_page(&filter, &page).await
+}
+
+#[tauri::command]
+async fn query_tasks(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskFieldName>,
+    facets: Vec<TaskFieldName>,
+) -> Result<QueryResult<Task, TaskFieldName>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_task(id).await
+    state_guard.query_tasks(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_tasks(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskFieldName>,
+    group_by: TaskFieldName,
+    metrics: Vec<Metric<TaskFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_tasks(&filter, &group_by, &metrics)
+        .await
+}
+#[tauri::command]
+async fn insert_task(state: tauri::State<'_, DbState>, task: Task) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_task(&task).await;
+    state_guard.record_change("Insert task").await?;
+    res
+}
+
+#[tauri::command]
+async fn update_task(state: tauri::State<'_, DbState>, task: Task) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.update_task(&task).await;
+    state_guard.record_change("Update task").await?;
+    res
+}
+
+#[tauri::command]
+async fn delete_task(state: tauri::State<'_, DbState>, id: String) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_task(id).await;
+    state_guard.record_change("Delete task").await?;
+    res
 }
 
 #[tauri::command]
@@ -620,14 +1408,263 @@ async fn delete_tasks(
     state: tauri::State<'_, DbState>,
     cross_design: String,
 ) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.delete_tasks(cross_design).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_tasks(cross_design).await;
+    state_guard.record_change("Delete tasks").await?;
+    res
 }
 
 #[tauri::command]
 async fn delete_all_tasks(state: tauri::State<'_, DbState>) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_all_tasks().await;
+    state_guard.record_change("Delete all tasks").await?;
+    res
+}
+
+#[tauri::command]
+async fn get_task_dependencies(
+    state: tauri::State<'_, DbState>,
+) -> Result<Vec<TaskDependency>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_task_dependencies().await
+}
+
+#[tauri::command]
+async fn get_filtered_task_dependency(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskDependencyFieldName>,
+) -> Result<Vec<TaskDependency>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_filtered_task_dependencies(&filter).await
+}
+
+#[tauri::command]
+async fn get_filtered_task_dependencies_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskDependencyFieldName>,
+    page: PageRequest,
+) -> Result<Page<TaskDependency>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_task_dependencies_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_task_dependencies(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskDependencyFieldName>,
+    facets: Vec<TaskDependencyFieldName>,
+) -> Result<QueryResult<TaskDependency, TaskDependencyFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_task_dependencies(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_task_dependencies(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskDependencyFieldName>,
+    group_by: TaskDependencyFieldName,
+    metrics: Vec<Metric<TaskDependencyFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_task_dependencies(&filter, &group_by, &metrics)
+        .await
+}
+
+#[tauri::command]
+async fn get_count_filtered_task_dependencies(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskDependencyFieldName>,
+) -> Result<u32, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_count_filtered_task_dependencies(&filter)
+        .await
+}
+
+#[tauri::command]
+async fn insert_task_dependency(
+    state: tauri::State<'_, DbState>,
+    task: TaskDependency,
+) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_task_dependency(&task).await;
+    state_guard.record_change("Insert task dependency").await?;
+    res
+}
+
+#[tauri::command]
+async fn delete_task_dependency(
+    state: tauri::State<'_, DbState>,
+    task: TaskDependency,
+) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_task_dependency(&task).await;
+    state_guard.record_change("Delete task dependency").await?;
+    res
+}
+
+#[tauri::command]
+async fn preview_delete_task_dependencies(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskDependencyFieldName>,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.preview_delete_task_dependencies(&filter).await
+}
+
+#[tauri::command]
+async fn delete_filtered_task_dependencies(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskDependencyFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard
+        .delete_filtered_task_dependencies(&filter, mode)
+        .await;
+    state_guard
+        .record_change("Delete task dependencies")
+        .await?;
+    res
+}
+
+#[tauri::command]
+async fn get_task_conditions(
+    state: tauri::State<'_, DbState>,
+) -> Result<Vec<TaskCondition>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_task_conditions().await
+}
+
+#[tauri::command]
+async fn get_filtered_task_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskConditionFieldName>,
+) -> Result<Vec<TaskCondition>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_filtered_task_conditions(&filter).await
+}
+
+#[tauri::command]
+async fn get_filtered_task_conditions_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskConditionFieldName>,
+    page: PageRequest,
+) -> Result<Page<TaskCondition>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_task_conditions_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_task_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskConditionFieldName>,
+    facets: Vec<TaskConditionFieldName>,
+) -> Result<QueryResult<TaskCondition, TaskConditionFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_task_conditions(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_task_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskConditionFieldName>,
+    group_by: TaskConditionFieldName,
+    metrics: Vec<Metric<TaskConditionFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_task_conditions(&filter, &group_by, &metrics)
+        .await
+}
+
+#[tauri::command]
+async fn get_count_filtered_task_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskConditionFieldName>,
+) -> Result<u32, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_count_filtered_task_conditions(&filter)
+        .await
+}
+
+#[tauri::command]
+async fn insert_task_condition(
+    state: tauri::State<'_, DbState>,
+    task: TaskCondition,
+) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_task_condition(&task).await;
+    state_guard.record_change("Insert task condition").await?;
+    res
+}
+
+#[tauri::command]
+async fn insert_task_conditions_from_file(
+    state: tauri::State<'_, DbState>,
+    path: String,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<TaskCondition>::new(Path::new(&path)) {
+        Ok(bulk) => {
+            state_guard
+                .insert_task_conditions(bulk, policy, dry_run)
+                .await
+        }
+        Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard.record_change("Import task conditions").await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_task_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskConditionFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_task_conditions(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
+    }
+}
+
+#[tauri::command]
+async fn preview_delete_task_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskConditionFieldName>,
+) -> Result<DeleteReport, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_all_tasks().await
+    state_guard.preview_delete_task_conditions(&filter).await
+}
+
+#[tauri::command]
+async fn delete_filtered_task_conditions(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<TaskConditionFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard
+        .delete_filtered_task_conditions(&filter, mode)
+        .await;
+    state_guard.record_change("Delete task conditions").await?;
+    res
 }
 
 #[tauri::command]
@@ -639,19 +1676,56 @@ async fn get_cross_designs(state: tauri::State<'_, DbState>) -> Result<Vec<Cross
 #[tauri::command]
 async fn get_filtered_cross_designs(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<CrossDesignFieldName>,
+    filter: AnyFilter<CrossDesignFieldName>,
 ) -> Result<Vec<CrossDesign>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_cross_designs(&filter).await
 }
 
+#[tauri::command]
+async fn get_filtered_cross_designs_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<CrossDesignFieldName>,
+    page: PageRequest,
+) -> Result<Page<CrossDesign>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_cross_designs_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_cross_designs(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<CrossDesignFieldName>,
+    facets: Vec<CrossDesignFieldName>,
+) -> Result<QueryResult<CrossDesign, CrossDesignFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_cross_designs(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_cross_designs(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<CrossDesignFieldName>,
+    group_by: CrossDesignFieldName,
+    metrics: Vec<Metric<CrossDesignFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_cross_designs(&filter, &group_by, &metrics)
+        .await
+}
+
 #[tauri::command]
 async fn insert_cross_design(
     state: tauri::State<'_, DbState>,
     cross_design: CrossDesign,
 ) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.insert_cross_design(&cross_design).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_cross_design(&cross_design).await;
+    state_guard.record_change("Insert cross design").await?;
+    res
 }
 
 #[tauri::command]
@@ -659,14 +1733,18 @@ async fn update_cross_design(
     state: tauri::State<'_, DbState>,
     cross_design: CrossDesign,
 ) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.update_cross_design(&cross_design).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.update_cross_design(&cross_design).await;
+    state_guard.record_change("Update cross design").await?;
+    res
 }
 
 #[tauri::command]
 async fn delete_cross_design(state: tauri::State<'_, DbState>, id: String) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.delete_cross_design(id).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_cross_design(id).await;
+    state_guard.record_change("Delete cross design").await?;
+    res
 }
 
 #[tauri::command]
@@ -678,36 +1756,115 @@ async fn get_strains(state: tauri::State<'_, DbState>) -> Result<Vec<Strain>, Db
 #[tauri::command]
 async fn get_count_filtered_strains(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<StrainFieldName>,
+    filter: AnyFilter<StrainFieldName>,
 ) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_count_filtered_strains(&filter).await
 }
 
+#[tauri::command]
+async fn search_strains(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainJoinFieldName>,
+) -> Result<StrainSearchResults, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.search_strains(&filter).await
+}
+
+#[tauri::command]
+async fn aggregate_joined_strains(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainJoinFieldName>,
+    group_by: StrainJoinFieldName,
+    metrics: Vec<Metric<StrainJoinFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_joined_strains(&filter, &group_by, &metrics)
+        .await
+}
+
 #[tauri::command]
 async fn get_filtered_strains(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<StrainFieldName>,
+    filter: AnyFilter<StrainFieldName>,
 ) -> Result<Vec<Strain>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_strains(&filter).await
 }
 
 #[tauri::command]
-async fn insert_strain(state: tauri::State<'_, DbState>, strain: Strain) -> Result<(), DbError> {
+async fn get_filtered_strains_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainFieldName>,
+    page: PageRequest,
+) -> Result<Page<Strain>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.get_filtered_strains_page(&filter, &page).await
+}
+
+#[tauri::command]
+async fn query_strains(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainFieldName>,
+    facets: Vec<StrainFieldName>,
+) -> Result<QueryResult<Strain, StrainFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_strains(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_strains(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainFieldName>,
+    group_by: StrainFieldName,
+    metrics: Vec<Metric<StrainFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.insert_strain(&strain).await
+    state_guard
+        .aggregate_strains(&filter, &group_by, &metrics)
+        .await
+}
+
+#[tauri::command]
+async fn insert_strain(state: tauri::State<'_, DbState>, strain: Strain) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_strain(&strain).await;
+    state_guard.record_change("Insert strain").await?;
+    res
 }
 
 #[tauri::command]
 async fn insert_strains_from_file(
     state: tauri::State<'_, DbState>,
     path: String,
-) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    match Bulk::<Strain>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_strains(bulk).await,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<Strain>::new(Path::new(&path)) {
+        Ok(bulk) => state_guard.insert_strains(bulk, policy, dry_run).await,
         Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard.record_change("Import strains").await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_strains(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_strains(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
     }
 }
 
@@ -717,17 +1874,31 @@ async fn update_strain(
     name: String,
     new_strain: Strain,
 ) -> Result<(), DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.update_strain(name, new_strain).await;
+    state_guard.record_change("Update strain").await?;
+    res
+}
+
+#[tauri::command]
+async fn preview_delete_strains(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainFieldName>,
+) -> Result<DeleteReport, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.update_strain(name, new_strain).await
+    state_guard.preview_delete_strains(&filter).await
 }
 
 #[tauri::command]
 async fn delete_filtered_strains(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<StrainFieldName>,
-) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.delete_filtered_strains(&filter).await
+    filter: AnyFilter<StrainFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_filtered_strains(&filter, mode).await;
+    state_guard.record_change("Delete strains").await?;
+    res
 }
 /* #endregion strains */
 
@@ -743,7 +1914,7 @@ async fn get_strain_alleles(
 #[tauri::command]
 async fn get_count_filtered_strain_alleles(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<StrainAlleleFieldName>,
+    filter: AnyFilter<StrainAlleleFieldName>,
 ) -> Result<u32, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_count_filtered_strain_alleles(&filter).await
@@ -752,38 +1923,335 @@ async fn get_count_filtered_strain_alleles(
 #[tauri::command]
 async fn get_filtered_strain_alleles(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<StrainAlleleFieldName>,
+    filter: AnyFilter<StrainAlleleFieldName>,
 ) -> Result<Vec<StrainAllele>, DbError> {
     let state_guard = state.0.read().await;
     state_guard.get_filtered_strain_alleles(&filter).await
 }
 
+#[tauri::command]
+async fn get_filtered_strain_alleles_page(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainAlleleFieldName>,
+    page: PageRequest,
+) -> Result<Page<StrainAllele>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .get_filtered_strain_alleles_page(&filter, &page)
+        .await
+}
+
+#[tauri::command]
+async fn query_strain_alleles(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainAlleleFieldName>,
+    facets: Vec<StrainAlleleFieldName>,
+) -> Result<QueryResult<StrainAllele, StrainAlleleFieldName>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.query_strain_alleles(&filter, facets).await
+}
+
+#[tauri::command]
+async fn aggregate_strain_alleles(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainAlleleFieldName>,
+    group_by: StrainAlleleFieldName,
+    metrics: Vec<Metric<StrainAlleleFieldName>>,
+) -> Result<Vec<AggregateRow>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard
+        .aggregate_strain_alleles(&filter, &group_by, &metrics)
+        .await
+}
+
 #[tauri::command]
 async fn insert_strain_allele(
     state: tauri::State<'_, DbState>,
     strain_allele: StrainAllele,
 ) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    state_guard.insert_strain_allele(&strain_allele).await
+    let state_guard = state.0.write().await;
+    let res = state_guard.insert_strain_allele(&strain_allele).await;
+    state_guard.record_change("Insert strain allele").await?;
+    res
 }
 
 #[tauri::command]
 async fn insert_strain_alleles_from_file(
     state: tauri::State<'_, DbState>,
     path: String,
-) -> Result<(), DbError> {
-    let state_guard = state.0.read().await;
-    match Bulk::<StrainAllele>::new(Path::new(&path)) {
-        Ok(bulk) => state_guard.insert_strain_alleles(bulk).await,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<BulkReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = match Bulk::<StrainAllele>::new(Path::new(&path)) {
+        Ok(bulk) => {
+            state_guard
+                .insert_strain_alleles(bulk, policy, dry_run)
+                .await
+        }
         Err(_) => Err(DbError::BulkInsert("Unable to open file".to_owned())),
+    };
+    state_guard.record_change("Import strain alleles").await?;
+    res
+}
+
+#[tauri::command]
+async fn export_filtered_strain_alleles(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainAlleleFieldName>,
+    path: String,
+    format: ExportFormat,
+) -> Result<usize, DbError> {
+    let state_guard = state.0.read().await;
+    match format.writer(Path::new(&path)) {
+        Ok(mut writer) => {
+            state_guard
+                .export_filtered_strain_alleles(&filter, &mut writer)
+                .await
+        }
+        Err(_) => Err(DbError::Export("Unable to create file".to_owned())),
     }
 }
 
+#[tauri::command]
+async fn preview_delete_strain_alleles(
+    state: tauri::State<'_, DbState>,
+    filter: AnyFilter<StrainAlleleFieldName>,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.preview_delete_strain_alleles(&filter).await
+}
+
 #[tauri::command]
 async fn delete_filtered_strain_alleles(
     state: tauri::State<'_, DbState>,
-    filter: FilterGroup<StrainAlleleFieldName>,
-) -> Result<(), DbError> {
+    filter: AnyFilter<StrainAlleleFieldName>,
+    mode: DeleteMode,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard
+        .delete_filtered_strain_alleles(&filter, mode)
+        .await;
+    state_guard.record_change("Delete strain alleles").await?;
+    res
+}
+
+/* #endregion strain_alleles */
+
+/* #region genetics */
+#[tauri::command]
+async fn cross_strains(
+    state: tauri::State<'_, DbState>,
+    herm: String,
+    male: String,
+) -> Result<Vec<Offspring>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.cross_strains(&herm, &male).await
+}
+
+#[tauri::command]
+async fn self_cross_strain(
+    state: tauri::State<'_, DbState>,
+    herm: String,
+) -> Result<Vec<Offspring>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.self_cross_strain(&herm).await
+}
+
+#[tauri::command]
+async fn validate_strains(
+    state: tauri::State<'_, DbState>,
+) -> Result<Vec<GenotypeMismatch>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.validate_strains().await
+}
+/* #endregion genetics */
+
+/* #region backups */
+#[tauri::command]
+async fn backup_database(state: tauri::State<'_, DbState>, path: String) -> Result<(), DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.backup_to(Path::new(&path)).await
+}
+
+#[tauri::command]
+async fn create_snapshot(state: tauri::State<'_, DbState>) -> Result<Snapshot, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.create_snapshot().await
+}
+
+#[tauri::command]
+async fn list_snapshots(state: tauri::State<'_, DbState>) -> Result<Vec<Snapshot>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.list_snapshots().await
+}
+
+#[tauri::command]
+async fn restore_snapshot(state: tauri::State<'_, DbState>, name: String) -> Result<(), DbError> {
+    // the write lock waits for running commands and holds off new ones during the swap
+    let mut state_guard = state.0.write().await;
+    state_guard.restore_snapshot(&name).await
+}
+/* #endregion backups */
+
+/* #region workspaces */
+#[tauri::command]
+async fn create_workspace(
+    state: tauri::State<'_, DbState>,
+    path: String,
+) -> Result<Workspace, DbError> {
+    let mut state_guard = state.0.write().await;
+    match get_workspace_list_path() {
+        Ok(list_path) => {
+            state_guard
+                .create_workspace(Path::new(&path), &list_path)
+                .await
+        }
+        Err(_) => Err(DbError::Workspace(
+            "Unable to find app data directory".to_owned(),
+        )),
+    }
+}
+
+#[tauri::command]
+async fn open_workspace(
+    state: tauri::State<'_, DbState>,
+    path: String,
+) -> Result<Workspace, DbError> {
+    let mut state_guard = state.0.write().await;
+    match get_workspace_list_path() {
+        Ok(list_path) => {
+            state_guard
+                .open_workspace(Path::new(&path), &list_path)
+                .await
+        }
+        Err(_) => Err(DbError::Workspace(
+            "Unable to find app data directory".to_owned(),
+        )),
+    }
+}
+
+#[tauri::command]
+async fn list_workspaces(state: tauri::State<'_, DbState>) -> Result<Vec<Workspace>, DbError> {
+    let state_guard = state.0.read().await;
+    match get_workspace_list_path() {
+        Ok(list_path) => state_guard.list_workspaces(&list_path).await,
+        Err(_) => Err(DbError::Workspace(
+            "Unable to find app data directory".to_owned(),
+        )),
+    }
+}
+
+#[tauri::command]
+async fn close_workspace(state: tauri::State<'_, DbState>, path: String) -> Result<(), DbError> {
+    let mut state_guard = state.0.write().await;
+    match (get_workspace_list_path(), get_database_path()) {
+        (Ok(list_path), Ok(default_path)) => {
+            state_guard
+                .close_workspace(Path::new(&path), &list_path, &default_path)
+                .await
+        }
+        _ => Err(DbError::Workspace(
+            "Unable to find app data directory".to_owned(),
+        )),
+    }
+}
+/* #endregion workspaces */
+
+/* #region seeding */
+#[tauri::command]
+async fn seed_reference_data(
+    state: tauri::State<'_, DbState>,
+) -> Result<Vec<SeedFileReport>, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.seed_reference_data().await;
+    state_guard.record_change("Seed reference data").await?;
+    res
+}
+/* #endregion seeding */
+
+/* #region audit */
+#[tauri::command]
+async fn audit_database(
+    state: tauri::State<'_, DbState>,
+    repair: Option<RepairMode>,
+) -> Result<AuditReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.audit_database(repair).await;
+    state_guard.record_change("Repair database").await?;
+    res
+}
+/* #endregion audit */
+
+/* #region journal */
+#[tauri::command]
+async fn list_recent_changes(
+    state: tauri::State<'_, DbState>,
+    limit: u32,
+) -> Result<Vec<Change>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.list_recent_changes(limit).await
+}
+
+#[tauri::command]
+async fn undo_last_change(state: tauri::State<'_, DbState>) -> Result<Option<Change>, DbError> {
+    let state_guard = state.0.write().await;
+    state_guard.undo_last_change().await
+}
+
+#[tauri::command]
+async fn redo_change(state: tauri::State<'_, DbState>) -> Result<Option<Change>, DbError> {
+    let state_guard = state.0.write().await;
+    state_guard.redo_change().await
+}
+/* #endregion journal */
+
+/* #region delete_all */
+#[tauri::command]
+async fn preview_delete_all(
+    state: tauri::State<'_, DbState>,
+    table: DeletableTable,
+) -> Result<DeleteAllPreview, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.preview_delete_all(table).await
+}
+
+#[tauri::command]
+async fn delete_all(
+    state: tauri::State<'_, DbState>,
+    table: DeletableTable,
+    mode: DeleteMode,
+    confirmation: String,
+) -> Result<DeleteReport, DbError> {
+    let state_guard = state.0.write().await;
+    let res = state_guard.delete_all(table, mode, &confirmation).await;
+    state_guard
+        .record_change(&format!("Delete all {}", table.get_table_name()))
+        .await?;
+    res
+}
+/* #endregion delete_all */
+
+/* #region search */
+#[tauri::command]
+async fn global_search(
+    state: tauri::State<'_, DbState>,
+    query: String,
+    limit: u32,
+) -> Result<Vec<SearchHit>, DbError> {
+    let state_guard = state.0.read().await;
+    state_guard.global_search(&query, limit).await
+}
+/* #endregion search */
+
+/* #region chromosome_map */
+#[tauri::command]
+async fn get_chromosome_map(
+    state: tauri::State<'_, DbState>,
+    chromosome: ChromosomeName,
+    range: Option<(f64, f64)>,
+) -> Result<ChromosomeMap, DbError> {
     let state_guard = state.0.read().await;
-    state_guard.delete_filtered_strain_alleles(&filter).await
+    state_guard.get_chromosome_map(chromosome, range).await
 }
+/* #endregion chromosome_map */
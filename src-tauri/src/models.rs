@@ -1,6 +1,9 @@
+use filter::ColumnType;
 use ts_rs::TS;
 pub trait FieldNameEnum: TS + std::hash::Hash + std::cmp::Eq {
     fn get_col_name(&self) -> String;
+    /// Filters on the column are checked against this before running
+    fn get_col_type(&self) -> ColumnType;
 }
 pub mod allele;
 pub mod allele_expr;
@@ -14,4 +17,6 @@ pub mod phenotype;
 pub mod strain;
 pub mod strain_allele;
 pub mod task;
+pub mod task_condition;
+pub mod task_dependency;
 pub mod variation;
@@ -1,4 +1,6 @@
-use super::FieldNameEnum;
+use super::{
+    filter::ColumnType, gene::GeneFieldName, variation::VariationFieldName, FieldNameEnum,
+};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -32,4 +34,45 @@ impl FieldNameEnum for AlleleFieldName {
             AlleleFieldName::VariationName => "variation_name".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            AlleleFieldName::Name => ColumnType::Text,
+            AlleleFieldName::Contents => ColumnType::Text,
+            AlleleFieldName::SysGeneName => ColumnType::Text,
+            AlleleFieldName::VariationName => ColumnType::Text,
+        }
+    }
+}
+
+/// A column of an allele, its gene or its variation, for querying alleles across all three
+#[derive(Serialize, Deserialize, Debug, Hash, PartialEq, Eq, TS)]
+#[ts(
+    export,
+    export_to = "../src/models/db/filter/db_AlleleJoinFieldName.ts"
+)]
+pub enum AlleleJoinFieldName {
+    Allele(AlleleFieldName),
+    Gene(GeneFieldName),
+    Variation(VariationFieldName),
+}
+
+impl FieldNameEnum for AlleleJoinFieldName {
+    fn get_col_name(&self) -> String {
+        match self {
+            AlleleJoinFieldName::Allele(field) => format!("alleles.{}", field.get_col_name()),
+            AlleleJoinFieldName::Gene(field) => format!("genes.{}", field.get_col_name()),
+            AlleleJoinFieldName::Variation(field) => {
+                format!("variations.{}", field.get_col_name())
+            }
+        }
+    }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            AlleleJoinFieldName::Allele(field) => field.get_col_type(),
+            AlleleJoinFieldName::Gene(field) => field.get_col_type(),
+            AlleleJoinFieldName::Variation(field) => field.get_col_type(),
+        }
+    }
 }
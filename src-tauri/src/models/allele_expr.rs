@@ -1,4 +1,4 @@
-use super::FieldNameEnum;
+use super::{filter::ColumnType, FieldNameEnum};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -62,4 +62,13 @@ impl FieldNameEnum for AlleleExpressionFieldName {
             AlleleExpressionFieldName::Dominance => "dominance".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            AlleleExpressionFieldName::AlleleName => ColumnType::Text,
+            AlleleExpressionFieldName::ExpressingPhenotypeName => ColumnType::Text,
+            AlleleExpressionFieldName::ExpressingPhenotypeWild => ColumnType::Bool,
+            AlleleExpressionFieldName::Dominance => ColumnType::Int,
+        }
+    }
 }
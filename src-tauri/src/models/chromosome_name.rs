@@ -4,7 +4,9 @@ use strum_macros::Display;
 use strum_macros::EnumString;
 use ts_rs::TS;
 
-#[derive(Serialize, Deserialize, Debug, Hash, PartialEq, Eq, TS, EnumString, Display)]
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq, TS, EnumString, Display,
+)]
 #[ts(export, export_to = "../src/models/db/filter/db_ChromosomeName.ts")]
 pub enum ChromosomeName {
     #[strum(serialize = "I")]
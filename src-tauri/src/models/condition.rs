@@ -1,4 +1,4 @@
-use super::FieldNameEnum;
+use super::{filter::ColumnType, FieldNameEnum};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -73,4 +73,16 @@ impl FieldNameEnum for ConditionFieldName {
             ConditionFieldName::MaturationDays => "maturation_days".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            ConditionFieldName::Name => ColumnType::Text,
+            ConditionFieldName::Description => ColumnType::Text,
+            ConditionFieldName::MaleMating => ColumnType::Int,
+            ConditionFieldName::Lethal => ColumnType::Bool,
+            ConditionFieldName::FemaleSterile => ColumnType::Bool,
+            ConditionFieldName::Arrested => ColumnType::Bool,
+            ConditionFieldName::MaturationDays => ColumnType::Real,
+        }
+    }
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
-use super::FieldNameEnum;
+use super::{filter::ColumnType, FieldNameEnum};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, TS)]
 #[ts(export, export_to = "../src/models/db/db_CrossDesign.ts")]
@@ -60,4 +60,14 @@ impl FieldNameEnum for CrossDesignFieldName {
             CrossDesignFieldName::Editable => "editable".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            CrossDesignFieldName::Id => ColumnType::Text,
+            CrossDesignFieldName::Name => ColumnType::Text,
+            CrossDesignFieldName::LastEdited => ColumnType::Date,
+            CrossDesignFieldName::Data => ColumnType::Text,
+            CrossDesignFieldName::Editable => ColumnType::Bool,
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use super::FieldNameEnum;
+use super::{filter::ColumnType, FieldNameEnum};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -82,4 +82,16 @@ impl FieldNameEnum for ExpressionRelationFieldName {
             ExpressionRelationFieldName::IsSuppressing => "is_suppressing".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            ExpressionRelationFieldName::AlleleName => ColumnType::Text,
+            ExpressionRelationFieldName::ExpressingPhenotypeName => ColumnType::Text,
+            ExpressionRelationFieldName::ExpressingPhenotypeWild => ColumnType::Bool,
+            ExpressionRelationFieldName::AlteringPhenotypeName => ColumnType::Text,
+            ExpressionRelationFieldName::AlteringPhenotypeWild => ColumnType::Bool,
+            ExpressionRelationFieldName::AlteringCondition => ColumnType::Text,
+            ExpressionRelationFieldName::IsSuppressing => ColumnType::Bool,
+        }
+    }
 }
@@ -1,15 +1,110 @@
 use super::FieldNameEnum;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, QueryBuilder, Sqlite};
+use thiserror::Error;
 use ts_rs::TS;
 
-pub trait FilterQueryBuilder {
+pub trait FilterQueryBuilder<T: FieldNameEnum> {
+    /// Fails without adding anything if a filter's values don't fit its column
     fn add_filtered_query(
         &self,
         qb: &mut QueryBuilder<Sqlite>,
         supply_where: bool,
         paginated: bool,
-    );
+    ) -> Result<(), FilterTypeError>;
+    /// Just the ORDER BY, LIMIT and OFFSET, for queries that add the conditions themselves
+    fn add_ordering(&self, qb: &mut QueryBuilder<Sqlite>, paginated: bool);
+    /// Whether the filter would leave out any rows at all
+    fn has_conditions(&self) -> bool;
+    /// Just the columns to order by, for queries that page through the rows themselves
+    fn get_order_by(&self) -> &[(T, Order)];
+}
+
+#[derive(Error, Debug)]
+#[error("{0}")]
+pub struct FilterTypeError(pub String);
+
+/// The type of the values stored in a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Int,
+    Real,
+    Text,
+    Bool,
+    /// ISO 8601 dates stored as text
+    Date,
+}
+
+impl ColumnType {
+    fn accepts(&self, value: &FilterValue) -> bool {
+        match (self, value) {
+            (ColumnType::Int, FilterValue::Int(_))
+            | (ColumnType::Real, FilterValue::Int(_) | FilterValue::Real(_))
+            | (ColumnType::Text, FilterValue::Text(_))
+            | (ColumnType::Bool, FilterValue::Bool(_))
+            | (ColumnType::Date, FilterValue::Date(_)) => true,
+            (ColumnType::Date, FilterValue::Text(text)) => {
+                NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+/**
+ * A value to compare a column to. JSON has no dates, so from the frontend dates
+ * come in as text, which date columns accept if it's an ISO 8601 date.
+ */
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, TS)]
+#[ts(export, export_to = "../src/models/db/filter/FilterValue.ts")]
+#[serde(untagged)]
+pub enum FilterValue {
+    Int(i64),
+    Real(f64),
+    Bool(bool),
+    Text(String),
+    Date(#[ts(type = "string")] NaiveDate),
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        FilterValue::Text(value.to_owned())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        FilterValue::Text(value)
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(value: i64) -> Self {
+        FilterValue::Int(value)
+    }
+}
+
+impl From<f64> for FilterValue {
+    fn from(value: f64) -> Self {
+        FilterValue::Real(value)
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(value: bool) -> Self {
+        FilterValue::Bool(value)
+    }
+}
+
+fn push_value(qb: &mut QueryBuilder<Sqlite>, value: &FilterValue) {
+    match value {
+        FilterValue::Int(value) => qb.push_bind(*value),
+        FilterValue::Real(value) => qb.push_bind(*value),
+        FilterValue::Bool(value) => qb.push_bind(*value),
+        FilterValue::Text(value) => qb.push_bind(value.to_owned()),
+        FilterValue::Date(value) => qb.push_bind(*value),
+    };
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, TS)]
@@ -19,87 +114,279 @@ pub enum Order {
     Desc,
 }
 
+/// How to combine the filters on different tables of a join
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, TS)]
+#[ts(export, export_to = "../src/models/db/filter/Conjunction.ts")]
+pub enum Conjunction {
+    And,
+    Or,
+}
+
+impl Conjunction {
+    pub fn get_sql(&self) -> &'static str {
+        match self {
+            Conjunction::And => " AND ",
+            Conjunction::Or => " OR ",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, FromRow)]
 pub struct Count {
     pub count: u32,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, TS)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, TS)]
 #[ts(export, export_to = "../src/models/db/filter/Filter.ts")]
 pub enum Filter {
     /// the left value to compare to, bool is whether it's inclusive, right is the same
-    Range(String, bool, String, bool),
+    Range(FilterValue, bool, FilterValue, bool),
     /// the value to compare to, bool is whether it's inclusive
-    LessThan(String, bool),
+    LessThan(FilterValue, bool),
     /// the value to compare to, bool is whether it's inclusive
-    GreaterThan(String, bool),
-    Equal(String),
-    NotEqual(String),
-    /// searches all entries that contain the string
+    GreaterThan(FilterValue, bool),
+    Equal(FilterValue),
+    NotEqual(FilterValue),
+    /// matches any of the values, however many there are
+    In(Vec<FilterValue>),
+    NotIn(Vec<FilterValue>),
+    /// searches all entries that contain the string, only on text and date columns.
+    /// Like the other LIKE filters, % and _ in the string match themselves.
     Like(String),
+    /// searches all entries that don't contain the string
+    NotLike(String),
+    StartsWith(String),
+    EndsWith(String),
+    /// a SQLite glob pattern, where * and ? are wildcards
+    Glob(String),
+    /// a regular expression, only available with the `regex` feature and an invalid
+    /// filter without it
+    Regex(String),
     Null,
     NotNull,
     True,
     False,
+    /**
+     * The filter matching case when the bool is true and ignoring it otherwise.
+     * Without it, comparisons match case, while Like, NotLike, StartsWith and
+     * EndsWith ignore it.
+     */
+    CaseSensitive(bool, Box<Filter>),
 }
+
+/// Escapes the LIKE wildcards, for use with `ESCAPE '\'`
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Escapes the GLOB wildcards by putting them in brackets
+fn escape_glob(value: &str) -> String {
+    value
+        .replace('[', "[[]")
+        .replace('*', "[*]")
+        .replace('?', "[?]")
+}
+
 impl Filter {
+    /// Checks that the filter's values fit a column of the given type
+    pub fn check_type(&self, col_name: &str, col_type: ColumnType) -> Result<(), FilterTypeError> {
+        // SQLite only has a regexp function when the feature registers one
+        #[cfg(not(feature = "regex"))]
+        if let Self::Regex(_) = self {
+            return Err(FilterTypeError(
+                "Regex filters are not enabled in this build".to_owned(),
+            ));
+        }
+        let values: Vec<&FilterValue> = match self {
+            Self::Range(a, _, b, _) => vec![a, b],
+            Self::LessThan(a, _) | Self::GreaterThan(a, _) | Self::Equal(a) | Self::NotEqual(a) => {
+                vec![a]
+            }
+            Self::In(values) | Self::NotIn(values) => values.iter().collect(),
+            Self::Like(_)
+            | Self::NotLike(_)
+            | Self::StartsWith(_)
+            | Self::EndsWith(_)
+            | Self::Glob(_)
+            | Self::Regex(_) => match col_type {
+                ColumnType::Text | ColumnType::Date => vec![],
+                _ => {
+                    return Err(FilterTypeError(format!(
+                        "{col_name} holds {col_type:?} values, so it can't be matched against text"
+                    )))
+                }
+            },
+            Self::True | Self::False => match col_type {
+                ColumnType::Bool => vec![],
+                _ => {
+                    return Err(FilterTypeError(format!(
+                        "{col_name} holds {col_type:?} values, so it can't be true or false"
+                    )))
+                }
+            },
+            Self::Null | Self::NotNull => vec![],
+            Self::CaseSensitive(_, filter) => return filter.check_type(col_name, col_type),
+        };
+        match values.into_iter().find(|value| !col_type.accepts(value)) {
+            Some(value) => Err(FilterTypeError(format!(
+                "{col_name} holds {col_type:?} values, so it can't be compared to {value:?}"
+            ))),
+            None => Ok(()),
+        }
+    }
+
     pub fn add_to_query(&self, col_name: &String, qb: &mut QueryBuilder<Sqlite>) {
-        qb.push(col_name.to_owned());
+        self.add_with_case(col_name, qb, None);
+        qb.push("\n");
+    }
+
+    fn add_with_case(
+        &self,
+        col_name: &String,
+        qb: &mut QueryBuilder<Sqlite>,
+        case_sensitive: Option<bool>,
+    ) {
+        // comparisons only change collation when asked to
+        let collated = match case_sensitive {
+            Some(true) => format!("{col_name} COLLATE BINARY"),
+            Some(false) => format!("{col_name} COLLATE NOCASE"),
+            None => col_name.to_owned(),
+        };
         match self {
             Self::Range(a, a_inc, b, b_inc) => {
+                qb.push(collated.to_owned());
                 qb.push(match a_inc.to_owned() {
                     true => " >= ",
                     false => " > ",
                 });
-                qb.push_bind(a.to_owned());
+                push_value(qb, a);
                 qb.push(" AND ");
-                qb.push(col_name.to_owned());
+                qb.push(collated);
                 qb.push(match b_inc.to_owned() {
                     true => " <= ",
                     false => " < ",
                 });
-                qb.push_bind(b.to_owned());
+                push_value(qb, b);
             }
             Self::GreaterThan(a, a_inc) => {
+                qb.push(collated);
                 qb.push(match a_inc.to_owned() {
                     true => " >= ",
                     false => " > ",
                 });
-                qb.push_bind(a.to_owned());
+                push_value(qb, a);
             }
             Self::LessThan(b, b_inc) => {
+                qb.push(collated);
                 qb.push(match b_inc.to_owned() {
                     true => " <= ",
                     false => " < ",
                 });
-                qb.push_bind(b.to_owned());
+                push_value(qb, b);
             }
             Self::Equal(a) => {
+                qb.push(collated);
                 qb.push(" = ");
-                qb.push_bind(a.to_owned());
+                push_value(qb, a);
             }
             Self::NotEqual(a) => {
+                qb.push(collated);
                 qb.push(" != ");
-                qb.push_bind(a.to_owned());
+                push_value(qb, a);
             }
-            Self::Like(a) => {
-                qb.push(" LIKE ");
-                qb.push_bind(format!("%{}%", a.to_owned()));
+            // the values go in as one JSON array, so long lists don't run into the bind limit
+            Self::In(values) | Self::NotIn(values) => {
+                qb.push(collated);
+                qb.push(match self {
+                    Self::In(_) => " IN ",
+                    _ => " NOT IN ",
+                });
+                qb.push("(SELECT value FROM json_each(");
+                qb.push_bind(serde_json::to_string(values).unwrap_or_default());
+                qb.push("))");
+            }
+            Self::Like(a) | Self::NotLike(a) => {
+                let negated = matches!(self, Self::NotLike(_));
+                qb.push(col_name.to_owned());
+                match case_sensitive {
+                    Some(true) => {
+                        qb.push(if negated { " NOT GLOB " } else { " GLOB " });
+                        qb.push_bind(format!("*{}*", escape_glob(a)));
+                    }
+                    _ => {
+                        qb.push(if negated { " NOT LIKE " } else { " LIKE " });
+                        qb.push_bind(format!("%{}%", escape_like(a)));
+                        qb.push(" ESCAPE '\\'");
+                    }
+                }
+            }
+            Self::StartsWith(a) | Self::EndsWith(a) => {
+                let (glob, like) = match self {
+                    Self::StartsWith(_) => (
+                        format!("{}*", escape_glob(a)),
+                        format!("{}%", escape_like(a)),
+                    ),
+                    _ => (
+                        format!("*{}", escape_glob(a)),
+                        format!("%{}", escape_like(a)),
+                    ),
+                };
+                qb.push(col_name.to_owned());
+                match case_sensitive {
+                    Some(true) => {
+                        qb.push(" GLOB ");
+                        qb.push_bind(glob);
+                    }
+                    _ => {
+                        qb.push(" LIKE ");
+                        qb.push_bind(like);
+                        qb.push(" ESCAPE '\\'");
+                    }
+                }
+            }
+            Self::Glob(a) => match case_sensitive {
+                Some(false) => {
+                    qb.push(format!("lower({col_name}) GLOB lower("));
+                    qb.push_bind(a.to_owned());
+                    qb.push(")");
+                }
+                _ => {
+                    qb.push(col_name.to_owned());
+                    qb.push(" GLOB ");
+                    qb.push_bind(a.to_owned());
+                }
+            },
+            Self::Regex(a) => {
+                qb.push(col_name.to_owned());
+                qb.push(" REGEXP ");
+                qb.push_bind(match case_sensitive {
+                    Some(false) => format!("(?i){a}"),
+                    _ => a.to_owned(),
+                });
             }
             Self::Null => {
+                qb.push(col_name.to_owned());
                 qb.push(" IS NULL");
             }
             Self::NotNull => {
+                qb.push(col_name.to_owned());
                 qb.push(" IS NOT NULL");
             }
             Self::True => {
+                qb.push(col_name.to_owned());
                 qb.push(" = 1");
             }
             Self::False => {
+                qb.push(col_name.to_owned());
                 qb.push(" = 0");
             }
+            Self::CaseSensitive(case_sensitive, filter) => {
+                filter.add_with_case(col_name, qb, Some(*case_sensitive));
+            }
         };
-        qb.push("\n");
     }
 }
 
@@ -155,13 +442,17 @@ where
     pub offset: Option<u32>,
 }
 
-impl<T: FieldNameEnum> FilterQueryBuilder for FilterGroup<T> {
+impl<T: FieldNameEnum> FilterQueryBuilder<T> for FilterGroup<T> {
     fn add_filtered_query(
         &self,
         qb: &mut QueryBuilder<Sqlite>,
         supply_where: bool,
         paginated: bool,
-    ) {
+    ) -> Result<(), FilterTypeError> {
+        for (field_name, filter) in self.filters.iter().flatten() {
+            filter.check_type(&field_name.get_col_name(), field_name.get_col_type())?;
+        }
+
         if !self.filters.is_empty() {
             // WHERE
             if supply_where {
@@ -189,36 +480,272 @@ impl<T: FieldNameEnum> FilterQueryBuilder for FilterGroup<T> {
             qb.push(" ) \n");
         }
 
-        // ORDER BY
-        if !self.order_by.is_empty() && supply_where {
-            qb.push(" ORDER BY ");
-            let mut qb_separated = qb.separated(", ");
-            for (order_field, order_dir) in self.order_by.iter() {
-                let order_dir_str = match order_dir {
-                    Order::Asc => "ASC",
-                    Order::Desc => "DESC",
-                };
-                qb_separated.push(format!(
-                    "{} COLLATE NOCASE {}",
-                    order_field.get_col_name(),
-                    order_dir_str
-                ));
+        if supply_where {
+            self.add_ordering(qb, paginated);
+        }
+        // DEBUG (uncomment line below)
+        // println!("{}", qb.sql());
+        Ok(())
+    }
+
+    fn add_ordering(&self, qb: &mut QueryBuilder<Sqlite>, paginated: bool) {
+        push_ordering(qb, &self.order_by, paginated, self.limit, self.offset);
+    }
+
+    fn has_conditions(&self) -> bool {
+        !self.filters.is_empty()
+    }
+
+    fn get_order_by(&self) -> &[(T, Order)] {
+        &self.order_by
+    }
+}
+
+/// ORDER BY, then LIMIT and OFFSET if the query is paginated
+fn push_ordering<T: FieldNameEnum>(
+    qb: &mut QueryBuilder<Sqlite>,
+    order_by: &[(T, Order)],
+    paginated: bool,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) {
+    // ORDER BY
+    if !order_by.is_empty() {
+        qb.push(" ORDER BY ");
+        let mut qb_separated = qb.separated(", ");
+        for (order_field, order_dir) in order_by.iter() {
+            let order_dir_str = match order_dir {
+                Order::Asc => "ASC",
+                Order::Desc => "DESC",
+            };
+            qb_separated.push(format!(
+                "{} COLLATE NOCASE {}",
+                order_field.get_col_name(),
+                order_dir_str
+            ));
+        }
+        qb_separated.push_unseparated(" ");
+    }
+    // LIMIT
+    if paginated {
+        if let Some(limit) = limit {
+            qb.push(" LIMIT ");
+            qb.push_bind(limit);
+        }
+        // OFFSET
+        if let Some(offset) = offset {
+            qb.push(" OFFSET ");
+            qb.push_bind(offset);
+        }
+    }
+}
+
+/**
+ * A boolean expression over filters, for conditions a FilterGroup can't express.
+ *
+ * For example:
+ * Or([
+ *   And([Leaf(col1, val1), Leaf(col2, val2)]),
+ *   Not(Leaf(col3, val3)),
+ * ])
+ *
+ * ^ would generate: WHERE ((col1 == val1 AND col2 == val2) OR NOT (col3 == val3))
+ *
+ * An And or Or with nothing in it doesn't filter anything, and is left out. Negating
+ * one would be ambiguous, so a Not over an empty expression is an invalid filter.
+ */
+#[derive(Serialize, Deserialize, Debug, TS)]
+#[ts(export_to = "../src/models/db/filter/FilterExpr.ts")]
+pub enum FilterExpr<T>
+where
+    T: TS + std::cmp::Eq + std::hash::Hash,
+{
+    And(Vec<FilterExpr<T>>),
+    Or(Vec<FilterExpr<T>>),
+    Not(Box<FilterExpr<T>>),
+    Leaf(T, Filter),
+}
+
+impl<T: FieldNameEnum> FilterExpr<T> {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            FilterExpr::And(exprs) | FilterExpr::Or(exprs) => exprs.iter().all(|e| e.is_empty()),
+            // never left out, so check_type gets to reject a Not over nothing
+            FilterExpr::Not(_) | FilterExpr::Leaf(_, _) => false,
+        }
+    }
+
+    pub fn check_type(&self) -> Result<(), FilterTypeError> {
+        match self {
+            FilterExpr::And(exprs) | FilterExpr::Or(exprs) => {
+                exprs.iter().try_for_each(|e| e.check_type())
+            }
+            FilterExpr::Not(expr) if expr.is_empty() => Err(FilterTypeError(
+                "Not needs a filter to negate, but it was given an empty And or Or".to_owned(),
+            )),
+            FilterExpr::Not(expr) => expr.check_type(),
+            FilterExpr::Leaf(field_name, filter) => {
+                filter.check_type(&field_name.get_col_name(), field_name.get_col_type())
             }
-            qb_separated.push_unseparated(" ");
         }
-        // LIMIT
-        if supply_where && paginated {
-            if let Some(limit) = self.limit {
-                qb.push(" LIMIT ");
-                qb.push_bind(limit);
+    }
+
+    /// Adds the expression in parentheses, which must not be empty
+    pub fn add_to_query(&self, qb: &mut QueryBuilder<Sqlite>) {
+        match self {
+            FilterExpr::And(exprs) | FilterExpr::Or(exprs) => {
+                let conjunction = match self {
+                    FilterExpr::And(_) => " AND ",
+                    _ => " OR ",
+                };
+                qb.push(" ( ");
+                for (i, expr) in exprs.iter().filter(|e| !e.is_empty()).enumerate() {
+                    if i > 0 {
+                        qb.push(conjunction);
+                    }
+                    expr.add_to_query(qb);
+                }
+                qb.push(" ) ");
             }
-            // OFFSET
-            if let Some(offset) = self.offset {
-                qb.push(" OFFSET ");
-                qb.push_bind(offset);
+            FilterExpr::Not(expr) => {
+                qb.push(" ( NOT ");
+                expr.add_to_query(qb);
+                qb.push(" ) ");
+            }
+            FilterExpr::Leaf(field_name, filter) => {
+                qb.push(" ( ");
+                filter.add_to_query(&field_name.get_col_name(), qb);
+                qb.push(" ) ");
             }
         }
-        // DEBUG (uncomment line below)
-        // println!("{}", qb.sql());
+    }
+}
+
+/// A FilterGroup is an AND of ORs
+impl<T: FieldNameEnum> From<Vec<Vec<(T, Filter)>>> for FilterExpr<T> {
+    fn from(filters: Vec<Vec<(T, Filter)>>) -> Self {
+        FilterExpr::And(
+            filters
+                .into_iter()
+                .map(|inner_filters| {
+                    FilterExpr::Or(
+                        inner_filters
+                            .into_iter()
+                            .map(|(field_name, filter)| FilterExpr::Leaf(field_name, filter))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Like a FilterGroup, but filtering on any FilterExpr
+#[derive(Serialize, Deserialize, Debug, TS)]
+#[ts(export_to = "../src/models/db/filter/FilterTree.ts")]
+pub struct FilterTree<T>
+where
+    T: TS + std::cmp::Eq + std::hash::Hash,
+{
+    pub filter: FilterExpr<T>,
+    #[serde(rename = "orderBy")]
+    pub order_by: Vec<(T, Order)>,
+    #[serde(rename = "limit")]
+    pub limit: Option<u32>,
+    #[serde(rename = "offset")]
+    pub offset: Option<u32>,
+}
+
+impl<T: FieldNameEnum> From<FilterGroup<T>> for FilterTree<T> {
+    fn from(group: FilterGroup<T>) -> Self {
+        FilterTree {
+            filter: group.filters.into(),
+            order_by: group.order_by,
+            limit: group.limit,
+            offset: group.offset,
+        }
+    }
+}
+
+impl<T: FieldNameEnum> FilterQueryBuilder<T> for FilterTree<T> {
+    fn add_filtered_query(
+        &self,
+        qb: &mut QueryBuilder<Sqlite>,
+        supply_where: bool,
+        paginated: bool,
+    ) -> Result<(), FilterTypeError> {
+        self.filter.check_type()?;
+
+        if !self.filter.is_empty() {
+            if supply_where {
+                qb.push(" WHERE \n");
+            }
+            self.filter.add_to_query(qb);
+            qb.push(" \n");
+        }
+
+        if supply_where {
+            self.add_ordering(qb, paginated);
+        }
+        Ok(())
+    }
+
+    fn add_ordering(&self, qb: &mut QueryBuilder<Sqlite>, paginated: bool) {
+        push_ordering(qb, &self.order_by, paginated, self.limit, self.offset);
+    }
+
+    fn has_conditions(&self) -> bool {
+        !self.filter.is_empty()
+    }
+
+    fn get_order_by(&self) -> &[(T, Order)] {
+        &self.order_by
+    }
+}
+
+/// What commands take as a filter, so the frontend can send either shape
+#[derive(Serialize, Deserialize, Debug, TS)]
+#[ts(export_to = "../src/models/db/filter/AnyFilter.ts")]
+#[serde(untagged)]
+pub enum AnyFilter<T>
+where
+    T: TS + std::cmp::Eq + std::hash::Hash,
+{
+    Group(FilterGroup<T>),
+    Tree(FilterTree<T>),
+}
+
+impl<T: FieldNameEnum> FilterQueryBuilder<T> for AnyFilter<T> {
+    fn add_filtered_query(
+        &self,
+        qb: &mut QueryBuilder<Sqlite>,
+        supply_where: bool,
+        paginated: bool,
+    ) -> Result<(), FilterTypeError> {
+        match self {
+            AnyFilter::Group(group) => group.add_filtered_query(qb, supply_where, paginated),
+            AnyFilter::Tree(tree) => tree.add_filtered_query(qb, supply_where, paginated),
+        }
+    }
+
+    fn add_ordering(&self, qb: &mut QueryBuilder<Sqlite>, paginated: bool) {
+        match self {
+            AnyFilter::Group(group) => group.add_ordering(qb, paginated),
+            AnyFilter::Tree(tree) => tree.add_ordering(qb, paginated),
+        }
+    }
+
+    fn has_conditions(&self) -> bool {
+        match self {
+            AnyFilter::Group(group) => group.has_conditions(),
+            AnyFilter::Tree(tree) => tree.has_conditions(),
+        }
+    }
+
+    fn get_order_by(&self) -> &[(T, Order)] {
+        match self {
+            AnyFilter::Group(group) => group.get_order_by(),
+            AnyFilter::Tree(tree) => tree.get_order_by(),
+        }
     }
 }
@@ -1,4 +1,4 @@
-use super::{chromosome_name::ChromosomeName, FieldNameEnum};
+use super::{chromosome_name::ChromosomeName, filter::ColumnType, FieldNameEnum};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -74,4 +74,15 @@ impl FieldNameEnum for GeneFieldName {
             GeneFieldName::RecombSuppressor => "recomb_suppressor".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            GeneFieldName::SysName => ColumnType::Text,
+            GeneFieldName::DescName => ColumnType::Text,
+            GeneFieldName::Chromosome => ColumnType::Text,
+            GeneFieldName::PhysLoc => ColumnType::Int,
+            GeneFieldName::GeneticLoc => ColumnType::Real,
+            GeneFieldName::RecombSuppressor => ColumnType::Int,
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use super::FieldNameEnum;
+use super::{filter::ColumnType, FieldNameEnum};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -84,4 +84,18 @@ impl FieldNameEnum for PhenotypeFieldName {
             PhenotypeFieldName::MaturationDays => "maturation_days".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            PhenotypeFieldName::Name => ColumnType::Text,
+            PhenotypeFieldName::Wild => ColumnType::Bool,
+            PhenotypeFieldName::ShortName => ColumnType::Text,
+            PhenotypeFieldName::Description => ColumnType::Text,
+            PhenotypeFieldName::MaleMating => ColumnType::Int,
+            PhenotypeFieldName::Lethal => ColumnType::Bool,
+            PhenotypeFieldName::FemaleSterile => ColumnType::Bool,
+            PhenotypeFieldName::Arrested => ColumnType::Bool,
+            PhenotypeFieldName::MaturationDays => ColumnType::Real,
+        }
+    }
 }
@@ -1,4 +1,8 @@
-use super::FieldNameEnum;
+use super::{
+    allele::AlleleFieldName, allele_expr::AlleleExpressionFieldName, filter::ColumnType,
+    gene::GeneFieldName, strain_allele::StrainAlleleFieldName, variation::VariationFieldName,
+    FieldNameEnum,
+};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -27,4 +31,77 @@ impl FieldNameEnum for StrainFieldName {
             StrainFieldName::Description => "description".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            StrainFieldName::Name => ColumnType::Text,
+            StrainFieldName::Genotype => ColumnType::Text,
+            StrainFieldName::Description => ColumnType::Text,
+        }
+    }
+}
+
+/// A column of a strain, or of one of its alleles along with the allele's gene, variation
+/// and expressions, for searching strains across all of them
+#[derive(Serialize, Deserialize, Debug, Hash, PartialEq, Eq, TS)]
+#[ts(
+    export,
+    export_to = "../src/models/db/filter/db_StrainJoinFieldName.ts"
+)]
+pub enum StrainJoinFieldName {
+    Strain(StrainFieldName),
+    StrainAllele(StrainAlleleFieldName),
+    Allele(AlleleFieldName),
+    Gene(GeneFieldName),
+    Variation(VariationFieldName),
+    AlleleExpr(AlleleExpressionFieldName),
+}
+
+impl FieldNameEnum for StrainJoinFieldName {
+    fn get_col_name(&self) -> String {
+        match self {
+            StrainJoinFieldName::Strain(field) => format!("strains.{}", field.get_col_name()),
+            StrainJoinFieldName::StrainAllele(field) => {
+                format!("strain_alleles.{}", field.get_col_name())
+            }
+            StrainJoinFieldName::Allele(field) => format!("alleles.{}", field.get_col_name()),
+            StrainJoinFieldName::Gene(field) => format!("genes.{}", field.get_col_name()),
+            StrainJoinFieldName::Variation(field) => {
+                format!("variations.{}", field.get_col_name())
+            }
+            StrainJoinFieldName::AlleleExpr(field) => {
+                format!("allele_exprs.{}", field.get_col_name())
+            }
+        }
+    }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            StrainJoinFieldName::Strain(field) => field.get_col_type(),
+            StrainJoinFieldName::StrainAllele(field) => field.get_col_type(),
+            StrainJoinFieldName::Allele(field) => field.get_col_type(),
+            StrainJoinFieldName::Gene(field) => field.get_col_type(),
+            StrainJoinFieldName::Variation(field) => field.get_col_type(),
+            StrainJoinFieldName::AlleleExpr(field) => field.get_col_type(),
+        }
+    }
+}
+
+/// A strain found by a search, along with the alleles of it that matched
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_StrainSearchHit.ts")]
+#[serde(rename = "db_StrainSearchHit")]
+pub struct StrainSearchHit {
+    pub strain: Strain,
+    #[serde(rename = "matchingAlleles")]
+    pub matching_alleles: Vec<String>,
+}
+
+/// One page of a strain search, and how many strains matched in all
+#[derive(Serialize, Deserialize, Debug, PartialEq, TS)]
+#[ts(export, export_to = "../src/models/db/db_StrainSearchResults.ts")]
+#[serde(rename = "db_StrainSearchResults")]
+pub struct StrainSearchResults {
+    pub hits: Vec<StrainSearchHit>,
+    pub total: u32,
 }
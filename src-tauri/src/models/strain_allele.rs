@@ -1,4 +1,4 @@
-use super::FieldNameEnum;
+use super::{filter::ColumnType, FieldNameEnum};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -60,4 +60,13 @@ impl FieldNameEnum for StrainAlleleFieldName {
             StrainAlleleFieldName::IsOnBot => "is_on_bot".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            StrainAlleleFieldName::StrainName => ColumnType::Text,
+            StrainAlleleFieldName::AlleleName => ColumnType::Text,
+            StrainAlleleFieldName::IsOnTop => ColumnType::Bool,
+            StrainAlleleFieldName::IsOnBot => ColumnType::Bool,
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use super::FieldNameEnum;
+use super::{filter::ColumnType, FieldNameEnum};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -106,4 +106,19 @@ impl FieldNameEnum for TaskFieldName {
             TaskFieldName::ChildTaskId => "child_task_id".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            TaskFieldName::Id => ColumnType::Text,
+            TaskFieldName::DueDate => ColumnType::Date,
+            TaskFieldName::Action => ColumnType::Int,
+            TaskFieldName::HermStrain => ColumnType::Text,
+            TaskFieldName::MaleStrain => ColumnType::Text,
+            TaskFieldName::ResultStrain => ColumnType::Text,
+            TaskFieldName::Notes => ColumnType::Text,
+            TaskFieldName::Completed => ColumnType::Bool,
+            TaskFieldName::CrossDesignId => ColumnType::Text,
+            TaskFieldName::ChildTaskId => ColumnType::Text,
+        }
+    }
 }
@@ -0,0 +1,40 @@
+use super::{filter::ColumnType, FieldNameEnum};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// A condition (e.g. "25C") a task has to be carried out under
+#[derive(Serialize, Deserialize, Debug, sqlx::FromRow, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/task/db_TaskCondition.ts")]
+#[serde(rename = "db_TaskCondition")]
+pub struct TaskCondition {
+    #[serde(rename = "parentId")]
+    pub task_id: String,
+    #[serde(rename = "name")]
+    pub condition_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Hash, PartialEq, Eq, TS)]
+#[ts(
+    export,
+    export_to = "../src/models/db/filter/db_TaskConditionFieldName.ts"
+)]
+pub enum TaskConditionFieldName {
+    Id,
+    Name,
+}
+
+impl FieldNameEnum for TaskConditionFieldName {
+    fn get_col_name(&self) -> String {
+        match self {
+            TaskConditionFieldName::Id => "task_id".to_owned(),
+            TaskConditionFieldName::Name => "condition_name".to_owned(),
+        }
+    }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            TaskConditionFieldName::Id => ColumnType::Text,
+            TaskConditionFieldName::Name => ColumnType::Text,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use super::{filter::ColumnType, FieldNameEnum};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Links a task to a task that has to be completed before it can start.
+/// A task may depend on any number of parents (e.g. a cross that waits on two
+/// parent crosses).
+#[derive(Serialize, Deserialize, Debug, sqlx::FromRow, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../src/models/db/task/db_TaskDependency.ts")]
+#[serde(rename = "db_TaskDependency")]
+pub struct TaskDependency {
+    #[serde(rename = "parentId")]
+    pub parent_id: String,
+    #[serde(rename = "childId")]
+    pub child_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Hash, PartialEq, Eq, TS)]
+#[ts(
+    export,
+    export_to = "../src/models/db/filter/db_TaskDependencyFieldName.ts"
+)]
+pub enum TaskDependencyFieldName {
+    ParentId,
+    ChildId,
+}
+
+impl FieldNameEnum for TaskDependencyFieldName {
+    fn get_col_name(&self) -> String {
+        match self {
+            TaskDependencyFieldName::ParentId => "parent_id".to_owned(),
+            TaskDependencyFieldName::ChildId => "child_id".to_owned(),
+        }
+    }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            TaskDependencyFieldName::ParentId => ColumnType::Text,
+            TaskDependencyFieldName::ChildId => ColumnType::Text,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{chromosome_name::ChromosomeName, FieldNameEnum};
+use super::{chromosome_name::ChromosomeName, filter::ColumnType, FieldNameEnum};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -66,4 +66,14 @@ impl FieldNameEnum for VariationFieldName {
             VariationFieldName::RecombSuppressor => "recomb_suppressor".to_owned(),
         }
     }
+
+    fn get_col_type(&self) -> ColumnType {
+        match self {
+            VariationFieldName::AlleleName => ColumnType::Text,
+            VariationFieldName::Chromosome => ColumnType::Text,
+            VariationFieldName::PhysLoc => ColumnType::Int,
+            VariationFieldName::GenLoc => ColumnType::Real,
+            VariationFieldName::RecombSuppressor => ColumnType::Int,
+        }
+    }
 }
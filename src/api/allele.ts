@@ -1,7 +1,20 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
 import { type db_Allele } from 'models/db/db_Allele';
 import { type db_Gene } from 'models/db/db_Gene';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
+import { type Conjunction } from 'models/db/filter/Conjunction';
 import { type AlleleFieldName } from 'models/db/filter/db_AlleleFieldName';
+import { type AlleleJoinFieldName } from 'models/db/filter/db_AlleleJoinFieldName';
 import { type GeneFieldName } from 'models/db/filter/db_GeneFieldName';
 import {
   type FilterGroup,
@@ -14,27 +27,99 @@ export const getAlleles = async (): Promise<db_Allele[]> => {
 };
 
 export const getFilteredAlleles = async (
-  filter: FilterGroup<AlleleFieldName>
+  filter: AnyFilter<AlleleFieldName>
 ): Promise<db_Allele[]> => {
   return await invoke('get_filtered_alleles', { filter });
 };
 
+export const getFilteredAllelesPage = async (
+  filter: AnyFilter<AlleleFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_Allele>> => {
+  return await invoke('get_filtered_alleles_page', { filter, page });
+};
+
+export const queryAlleles = async (
+  filter: AnyFilter<AlleleFieldName>,
+  facets: AlleleFieldName[] = []
+): Promise<db_QueryResult<db_Allele, AlleleFieldName>> => {
+  return await invoke('query_alleles', { filter, facets });
+};
+
+export const queryJoinedAlleles = async (
+  filter: AnyFilter<AlleleJoinFieldName>,
+  facets: AlleleJoinFieldName[] = []
+): Promise<db_QueryResult<db_Allele, AlleleJoinFieldName>> => {
+  return await invoke('query_joined_alleles', { filter, facets });
+};
+
+export const aggregateAlleles = async (
+  filter: AnyFilter<AlleleFieldName>,
+  groupBy: AlleleFieldName,
+  metrics: Array<db_Metric<AlleleFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_alleles', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredAlleles = async (
-  filter: FilterGroup<AlleleFieldName>
+  filter: AnyFilter<AlleleFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_alleles', { filter });
 };
 
 export const getFilteredAllelesWithGeneFilter = async (
-  alleleFilter: FilterGroup<AlleleFieldName>,
-  geneFilter: FilterGroup<GeneFieldName>
+  alleleFilter: AnyFilter<AlleleFieldName>,
+  geneFilter: AnyFilter<GeneFieldName>,
+  conjunction: Conjunction = 'Or'
 ): Promise<Array<[db_Allele, db_Gene]>> => {
   return await invoke('get_filtered_alleles_with_gene_filter', {
     alleleFilter,
     geneFilter,
+    conjunction,
   });
 };
 
+export const getFilteredAllelesWithGeneFilterPage = async (
+  alleleFilter: AnyFilter<AlleleFieldName>,
+  geneFilter: AnyFilter<GeneFieldName>,
+  page: db_PageRequest,
+  conjunction: Conjunction = 'Or'
+): Promise<db_Page<[db_Allele, db_Gene]>> => {
+  return await invoke('get_filtered_alleles_with_gene_filter_page', {
+    alleleFilter,
+    geneFilter,
+    conjunction,
+    page,
+  });
+};
+
+export const getFilteredJoinedAlleles = async (
+  filter: AnyFilter<AlleleJoinFieldName>
+): Promise<db_Allele[]> => {
+  return await invoke('get_filtered_joined_alleles', { filter });
+};
+
+export const getFilteredJoinedAllelesPage = async (
+  filter: AnyFilter<AlleleJoinFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_Allele>> => {
+  return await invoke('get_filtered_joined_alleles_page', { filter, page });
+};
+
+export const getCountFilteredJoinedAlleles = async (
+  filter: AnyFilter<AlleleJoinFieldName>
+): Promise<number> => {
+  return await invoke('get_count_filtered_joined_alleles', { filter });
+};
+
+export const aggregateJoinedAlleles = async (
+  filter: AnyFilter<AlleleJoinFieldName>,
+  groupBy: AlleleJoinFieldName,
+  metrics: Array<db_Metric<AlleleJoinFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_joined_alleles', { filter, groupBy, metrics });
+};
+
 export const getAllele = async (name: string): Promise<db_Allele> => {
   const filter: FilterGroup<AlleleFieldName> = {
     filters: [[['Name', { Equal: name }]]],
@@ -62,14 +147,33 @@ export const insertDbAllele = async (record: db_Allele): Promise<void> => {
   await invoke('insert_allele', { allele: record });
 };
 
-export const insertAllelesFromFile = async (path: string): Promise<void> => {
-  await invoke('insert_alleles_from_file', { path });
+export const insertAllelesFromFile = async (
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_alleles_from_file', { path, policy, dryRun });
+};
+
+export const exportFilteredAlleles = async (
+  filter: AnyFilter<AlleleFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_alleles', { filter, path, format });
+};
+
+export const previewDeleteAlleles = async (
+  filter: AnyFilter<AlleleFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_alleles', { filter });
 };
 
 export const deleteFilteredAlleles = async (
-  filter: FilterGroup<AlleleFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_alleles', { filter });
+  filter: AnyFilter<AlleleFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_alleles', { filter, mode });
 };
 
 export const deleteAllele = async (allele: db_Allele): Promise<void> => {
@@ -1,5 +1,16 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
 import { type db_AlleleExpression } from 'models/db/db_AlleleExpression';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type AlleleExpressionFieldName } from 'models/db/filter/db_AlleleExpressionFieldName';
 import {
   type FilterGroup,
@@ -15,13 +26,35 @@ export const getAlleleExpressions = async (): Promise<
 };
 
 export const getFilteredAlleleExpressions = async (
-  filter: FilterGroup<AlleleExpressionFieldName>
+  filter: AnyFilter<AlleleExpressionFieldName>
 ): Promise<db_AlleleExpression[]> => {
   return await invoke('get_filtered_allele_exprs', { filter });
 };
 
+export const getFilteredAlleleExpressionsPage = async (
+  filter: AnyFilter<AlleleExpressionFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_AlleleExpression>> => {
+  return await invoke('get_filtered_allele_exprs_page', { filter, page });
+};
+
+export const queryAlleleExpressions = async (
+  filter: AnyFilter<AlleleExpressionFieldName>,
+  facets: AlleleExpressionFieldName[] = []
+): Promise<db_QueryResult<db_AlleleExpression, AlleleExpressionFieldName>> => {
+  return await invoke('query_allele_exprs', { filter, facets });
+};
+
+export const aggregateAlleleExpressions = async (
+  filter: AnyFilter<AlleleExpressionFieldName>,
+  groupBy: AlleleExpressionFieldName,
+  metrics: Array<db_Metric<AlleleExpressionFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_allele_exprs', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredAlleleExpressions = async (
-  filter: FilterGroup<AlleleExpressionFieldName>
+  filter: AnyFilter<AlleleExpressionFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_allele_exprs', { filter });
 };
@@ -57,15 +90,36 @@ export const insertDbAlleleExpression = async (
 };
 
 export const insertAlleleExpressionsFromFile = async (
-  path: string
-): Promise<void> => {
-  await invoke('insert_allele_exprs_from_file', { path });
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_allele_exprs_from_file', {
+    path,
+    policy,
+    dryRun,
+  });
+};
+
+export const exportFilteredAlleleExpressions = async (
+  filter: AnyFilter<AlleleExpressionFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_allele_exprs', { filter, path, format });
+};
+
+export const previewDeleteAlleleExpressions = async (
+  filter: AnyFilter<AlleleExpressionFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_allele_exprs', { filter });
 };
 
 export const deleteFilteredAlleleExpressions = async (
-  filter: FilterGroup<AlleleExpressionFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_allele_exprs', { filter });
+  filter: AnyFilter<AlleleExpressionFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_allele_exprs', { filter, mode });
 };
 
 export const deleteAlleleExpression = async (
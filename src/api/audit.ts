@@ -0,0 +1,9 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AuditReport } from 'models/db/db_AuditReport';
+import { type db_RepairMode } from 'models/db/db_RepairMode';
+
+export const auditDatabase = async (
+  repair: db_RepairMode | null = null
+): Promise<db_AuditReport> => {
+  return await invoke('audit_database', { repair });
+};
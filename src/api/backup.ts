@@ -0,0 +1,18 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_Snapshot } from 'models/db/db_Snapshot';
+
+export const backupDatabase = async (path: string): Promise<void> => {
+  await invoke('backup_database', { path });
+};
+
+export const createSnapshot = async (): Promise<db_Snapshot> => {
+  return await invoke('create_snapshot');
+};
+
+export const listSnapshots = async (): Promise<db_Snapshot[]> => {
+  return await invoke('list_snapshots');
+};
+
+export const restoreSnapshot = async (name: string): Promise<void> => {
+  await invoke('restore_snapshot', { name });
+};
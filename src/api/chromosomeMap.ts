@@ -0,0 +1,10 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_ChromosomeMap } from 'models/db/db_ChromosomeMap';
+import { type ChromosomeName } from 'models/db/filter/db_ChromosomeName';
+
+export const getChromosomeMap = async (
+  chromosome: ChromosomeName,
+  range: [number, number] | null = null
+): Promise<db_ChromosomeMap> => {
+  return await invoke('get_chromosome_map', { chromosome, range });
+};
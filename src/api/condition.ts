@@ -1,5 +1,16 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
 import { type db_Condition } from 'models/db/db_Condition';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type ConditionFieldName } from 'models/db/filter/db_ConditionFieldName';
 import { type ExpressionRelationFieldName } from 'models/db/filter/db_ExpressionRelationFieldName';
 import {
@@ -14,15 +25,37 @@ export const getConditions = async (): Promise<db_Condition[]> => {
 };
 
 export const getFilteredConditions = async (
-  filter: FilterGroup<ConditionFieldName>
+  filter: AnyFilter<ConditionFieldName>
 ): Promise<db_Condition[]> => {
   return await invoke('get_filtered_conditions', {
     filter,
   });
 };
 
+export const getFilteredConditionsPage = async (
+  filter: AnyFilter<ConditionFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_Condition>> => {
+  return await invoke('get_filtered_conditions_page', { filter, page });
+};
+
+export const queryConditions = async (
+  filter: AnyFilter<ConditionFieldName>,
+  facets: ConditionFieldName[] = []
+): Promise<db_QueryResult<db_Condition, ConditionFieldName>> => {
+  return await invoke('query_conditions', { filter, facets });
+};
+
+export const aggregateConditions = async (
+  filter: AnyFilter<ConditionFieldName>,
+  groupBy: ConditionFieldName,
+  metrics: Array<db_Metric<ConditionFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_conditions', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredConditions = async (
-  filter: FilterGroup<ConditionFieldName>
+  filter: AnyFilter<ConditionFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_conditions', {
     filter,
@@ -80,14 +113,33 @@ export const insertDbCondition = async (
   await invoke('insert_condition', { condition: record });
 };
 
-export const insertConditionsFromFile = async (path: string): Promise<void> => {
-  await invoke('insert_conditions_from_file', { path });
+export const insertConditionsFromFile = async (
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_conditions_from_file', { path, policy, dryRun });
+};
+
+export const exportFilteredConditions = async (
+  filter: AnyFilter<ConditionFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_conditions', { filter, path, format });
+};
+
+export const previewDeleteConditions = async (
+  filter: AnyFilter<ConditionFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_conditions', { filter });
 };
 
 export const deleteFilteredConditions = async (
-  filter: FilterGroup<ConditionFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_conditions', { filter });
+  filter: AnyFilter<ConditionFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_conditions', { filter, mode });
 };
 
 export const deleteCondition = async (
@@ -1,5 +1,11 @@
 import { invoke } from '@tauri-apps/api';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
 import { type db_CrossDesign } from 'models/db/db_CrossDesign';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import {
   type FilterGroup,
   getSingleRecordOrThrow,
@@ -11,13 +17,35 @@ export const getCrossDesigns = async (): Promise<db_CrossDesign[]> => {
 };
 
 export const getFilteredCrossDesigns = async (
-  filter: FilterGroup<CrossDesignFieldName>
+  filter: AnyFilter<CrossDesignFieldName>
 ): Promise<db_CrossDesign[]> => {
   return await invoke('get_filtered_cross_designs', {
     filter,
   });
 };
 
+export const getFilteredCrossDesignsPage = async (
+  filter: AnyFilter<CrossDesignFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_CrossDesign>> => {
+  return await invoke('get_filtered_cross_designs_page', { filter, page });
+};
+
+export const queryCrossDesigns = async (
+  filter: AnyFilter<CrossDesignFieldName>,
+  facets: CrossDesignFieldName[] = []
+): Promise<db_QueryResult<db_CrossDesign, CrossDesignFieldName>> => {
+  return await invoke('query_cross_designs', { filter, facets });
+};
+
+export const aggregateCrossDesigns = async (
+  filter: AnyFilter<CrossDesignFieldName>,
+  groupBy: CrossDesignFieldName,
+  metrics: Array<db_Metric<CrossDesignFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_cross_designs', { filter, groupBy, metrics });
+};
+
 export const getCrossDesign = async (id: string): Promise<db_CrossDesign> => {
   const filter: FilterGroup<CrossDesignFieldName> = {
     filters: [[['Id', { Equal: id }]]],
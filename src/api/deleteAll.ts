@@ -0,0 +1,19 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_DeletableTable } from 'models/db/db_DeletableTable';
+import { type db_DeleteAllPreview } from 'models/db/db_DeleteAllPreview';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+
+export const previewDeleteAll = async (
+  table: db_DeletableTable
+): Promise<db_DeleteAllPreview> => {
+  return await invoke('preview_delete_all', { table });
+};
+
+export const deleteAll = async (
+  table: db_DeletableTable,
+  confirmation: string,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_all', { table, mode, confirmation });
+};
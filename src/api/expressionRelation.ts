@@ -1,5 +1,16 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
 import { type db_ExpressionRelation } from 'models/db/db_ExpressionRelation';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type ExpressionRelationFieldName } from 'models/db/filter/db_ExpressionRelationFieldName';
 import { type FilterGroup, getDbBoolean } from 'models/db/filter/FilterGroup';
 
@@ -10,15 +21,39 @@ export const getExpressionRelations = async (): Promise<
 };
 
 export const getFilteredExpressionRelations = async (
-  filter: FilterGroup<ExpressionRelationFieldName>
+  filter: AnyFilter<ExpressionRelationFieldName>
 ): Promise<db_ExpressionRelation[]> => {
   return await invoke('get_filtered_expr_relations', {
     filter,
   });
 };
 
+export const getFilteredExpressionRelationsPage = async (
+  filter: AnyFilter<ExpressionRelationFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_ExpressionRelation>> => {
+  return await invoke('get_filtered_expr_relations_page', { filter, page });
+};
+
+export const queryExpressionRelations = async (
+  filter: AnyFilter<ExpressionRelationFieldName>,
+  facets: ExpressionRelationFieldName[] = []
+): Promise<
+  db_QueryResult<db_ExpressionRelation, ExpressionRelationFieldName>
+> => {
+  return await invoke('query_expr_relations', { filter, facets });
+};
+
+export const aggregateExpressionRelations = async (
+  filter: AnyFilter<ExpressionRelationFieldName>,
+  groupBy: ExpressionRelationFieldName,
+  metrics: Array<db_Metric<ExpressionRelationFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_expr_relations', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredExpressionRelations = async (
-  filter: FilterGroup<ExpressionRelationFieldName>
+  filter: AnyFilter<ExpressionRelationFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_expr_relations', {
     filter,
@@ -34,15 +69,40 @@ export const insertDbExpressionRelation = async (
 };
 
 export const insertExpressionRelationsFromFile = async (
-  path: string
-): Promise<void> => {
-  await invoke('insert_expr_relations_from_file', { path });
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_expr_relations_from_file', {
+    path,
+    policy,
+    dryRun,
+  });
+};
+
+export const exportFilteredExpressionRelations = async (
+  filter: AnyFilter<ExpressionRelationFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_expr_relations', {
+    filter,
+    path,
+    format,
+  });
+};
+
+export const previewDeleteExpressionRelations = async (
+  filter: AnyFilter<ExpressionRelationFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_expr_relations', { filter });
 };
 
 export const deleteFilteredExpressionRelations = async (
-  filter: FilterGroup<ExpressionRelationFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_expr_relations', { filter });
+  filter: AnyFilter<ExpressionRelationFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_expr_relations', { filter, mode });
 };
 
 export const deleteExpressionRelation = async (
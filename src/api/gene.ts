@@ -1,5 +1,17 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
 import { type db_Gene } from 'models/db/db_Gene';
+import { type db_LocatedGene } from 'models/db/db_LocatedGene';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type GeneFieldName } from 'models/db/filter/db_GeneFieldName';
 import {
   type FilterGroup,
@@ -12,15 +24,43 @@ export const getGenes = async (): Promise<db_Gene[]> => {
 };
 
 export const getFilteredGenes = async (
-  filter: FilterGroup<GeneFieldName>
+  filter: AnyFilter<GeneFieldName>
 ): Promise<db_Gene[]> => {
   return await invoke('get_filtered_genes', {
     filter,
   });
 };
 
+export const getFilteredGenesPage = async (
+  filter: AnyFilter<GeneFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_Gene>> => {
+  return await invoke('get_filtered_genes_page', { filter, page });
+};
+
+export const getFilteredLocatedGenes = async (
+  filter: AnyFilter<GeneFieldName>
+): Promise<db_LocatedGene[]> => {
+  return await invoke('get_filtered_located_genes', { filter });
+};
+
+export const queryGenes = async (
+  filter: AnyFilter<GeneFieldName>,
+  facets: GeneFieldName[] = []
+): Promise<db_QueryResult<db_Gene, GeneFieldName>> => {
+  return await invoke('query_genes', { filter, facets });
+};
+
+export const aggregateGenes = async (
+  filter: AnyFilter<GeneFieldName>,
+  groupBy: GeneFieldName,
+  metrics: Array<db_Metric<GeneFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_genes', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredGenes = async (
-  filter: FilterGroup<GeneFieldName>
+  filter: AnyFilter<GeneFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_genes', {
     filter,
@@ -47,14 +87,33 @@ export const insertDbGene = async (record: db_Gene): Promise<void> => {
   await invoke('insert_gene', { gene: record });
 };
 
-export const insertGenesFromFile = async (path: string): Promise<void> => {
-  await invoke('insert_genes_from_file', { path });
+export const insertGenesFromFile = async (
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_genes_from_file', { path, policy, dryRun });
+};
+
+export const exportFilteredGenes = async (
+  filter: AnyFilter<GeneFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_genes', { filter, path, format });
+};
+
+export const previewDeleteGenes = async (
+  filter: AnyFilter<GeneFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_genes', { filter });
 };
 
 export const deleteFilteredGenes = async (
-  filter: FilterGroup<GeneFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_genes', { filter });
+  filter: AnyFilter<GeneFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_genes', { filter, mode });
 };
 
 export const deleteGene = async (gene: db_Gene): Promise<void> => {
@@ -0,0 +1,15 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_Offspring } from 'models/db/db_Offspring';
+
+export const crossStrains = async (
+  herm: string,
+  male: string
+): Promise<db_Offspring[]> => {
+  return await invoke('cross_strains', { herm, male });
+};
+
+export const selfCrossStrain = async (
+  herm: string
+): Promise<db_Offspring[]> => {
+  return await invoke('self_cross_strain', { herm });
+};
@@ -0,0 +1,14 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_Change } from 'models/db/db_Change';
+
+export const listRecentChanges = async (limit = 50): Promise<db_Change[]> => {
+  return await invoke('list_recent_changes', { limit });
+};
+
+export const undoLastChange = async (): Promise<db_Change | null> => {
+  return await invoke('undo_last_change');
+};
+
+export const redoChange = async (): Promise<db_Change | null> => {
+  return await invoke('redo_change');
+};
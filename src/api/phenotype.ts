@@ -1,5 +1,16 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
 import { type db_Phenotype } from 'models/db/db_Phenotype';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type ExpressionRelationFieldName } from 'models/db/filter/db_ExpressionRelationFieldName';
 import { type PhenotypeFieldName } from 'models/db/filter/db_PhenotypeFieldName';
 import {
@@ -14,15 +25,37 @@ export const getPhenotypes = async (): Promise<db_Phenotype[]> => {
 };
 
 export const getFilteredPhenotypes = async (
-  filter: FilterGroup<PhenotypeFieldName>
+  filter: AnyFilter<PhenotypeFieldName>
 ): Promise<db_Phenotype[]> => {
   return await invoke('get_filtered_phenotypes', {
     filter,
   });
 };
 
+export const getFilteredPhenotypesPage = async (
+  filter: AnyFilter<PhenotypeFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_Phenotype>> => {
+  return await invoke('get_filtered_phenotypes_page', { filter, page });
+};
+
+export const queryPhenotypes = async (
+  filter: AnyFilter<PhenotypeFieldName>,
+  facets: PhenotypeFieldName[] = []
+): Promise<db_QueryResult<db_Phenotype, PhenotypeFieldName>> => {
+  return await invoke('query_phenotypes', { filter, facets });
+};
+
+export const aggregatePhenotypes = async (
+  filter: AnyFilter<PhenotypeFieldName>,
+  groupBy: PhenotypeFieldName,
+  metrics: Array<db_Metric<PhenotypeFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_phenotypes', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredPhenotypes = async (
-  filter: FilterGroup<PhenotypeFieldName>
+  filter: AnyFilter<PhenotypeFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_phenotypes', {
     filter,
@@ -80,14 +113,33 @@ export const insertDbPhenotype = async (
   await invoke('insert_phenotype', { phenotype: record });
 };
 
-export const insertPhenotypesFromFile = async (path: string): Promise<void> => {
-  await invoke('insert_phenotypes_from_file', { path });
+export const insertPhenotypesFromFile = async (
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_phenotypes_from_file', { path, policy, dryRun });
+};
+
+export const exportFilteredPhenotypes = async (
+  filter: AnyFilter<PhenotypeFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_phenotypes', { filter, path, format });
+};
+
+export const previewDeletePhenotypes = async (
+  filter: AnyFilter<PhenotypeFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_phenotypes', { filter });
 };
 
 export const deleteFilteredPhenotypes = async (
-  filter: FilterGroup<PhenotypeFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_phenotypes', { filter });
+  filter: AnyFilter<PhenotypeFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_phenotypes', { filter, mode });
 };
 
 export const deletePhenotype = async (
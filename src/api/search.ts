@@ -0,0 +1,9 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_SearchHit } from 'models/db/db_SearchHit';
+
+export const globalSearch = async (
+  query: string,
+  limit = 20
+): Promise<db_SearchHit[]> => {
+  return await invoke('global_search', { query, limit });
+};
@@ -0,0 +1,6 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_SeedFileReport } from 'models/db/db_SeedFileReport';
+
+export const seedReferenceData = async (): Promise<db_SeedFileReport[]> => {
+  return await invoke('seed_reference_data');
+};
@@ -1,6 +1,20 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
+import { type db_GenotypeMismatch } from 'models/db/db_GenotypeMismatch';
 import { type db_Strain } from 'models/db/db_Strain';
+import { type db_StrainSearchResults } from 'models/db/db_StrainSearchResults';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type StrainFieldName } from 'models/db/filter/db_StrainFieldName';
+import { type StrainJoinFieldName } from 'models/db/filter/db_StrainJoinFieldName';
 import {
   type FilterGroup,
   getSingleRecordOrThrow,
@@ -12,21 +26,57 @@ export const getStrains = async (): Promise<db_Strain[]> => {
 };
 
 export const getFilteredStrains = async (
-  filter: FilterGroup<StrainFieldName>
+  filter: AnyFilter<StrainFieldName>
 ): Promise<db_Strain[]> => {
   return await invoke('get_filtered_strains', {
     filter,
   });
 };
 
+export const getFilteredStrainsPage = async (
+  filter: AnyFilter<StrainFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_Strain>> => {
+  return await invoke('get_filtered_strains_page', { filter, page });
+};
+
+export const queryStrains = async (
+  filter: AnyFilter<StrainFieldName>,
+  facets: StrainFieldName[] = []
+): Promise<db_QueryResult<db_Strain, StrainFieldName>> => {
+  return await invoke('query_strains', { filter, facets });
+};
+
+export const aggregateStrains = async (
+  filter: AnyFilter<StrainFieldName>,
+  groupBy: StrainFieldName,
+  metrics: Array<db_Metric<StrainFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_strains', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredStrains = async (
-  filter: FilterGroup<StrainFieldName>
+  filter: AnyFilter<StrainFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_strains', {
     filter,
   });
 };
 
+export const searchStrains = async (
+  filter: AnyFilter<StrainJoinFieldName>
+): Promise<db_StrainSearchResults> => {
+  return await invoke('search_strains', { filter });
+};
+
+export const aggregateJoinedStrains = async (
+  filter: AnyFilter<StrainJoinFieldName>,
+  groupBy: StrainJoinFieldName,
+  metrics: Array<db_Metric<StrainJoinFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_joined_strains', { filter, groupBy, metrics });
+};
+
 export const getStrain = async (name: string): Promise<db_Strain> => {
   const filter: FilterGroup<StrainFieldName> = {
     filters: [[['Name', { Equal: name }]]],
@@ -44,8 +94,20 @@ export const insertDbStrain = async (record: db_Strain): Promise<void> => {
   await invoke('insert_strain', { strain: record });
 };
 
-export const insertStrainsFromFile = async (path: string): Promise<void> => {
-  await invoke('insert_strains_from_file', { path });
+export const insertStrainsFromFile = async (
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_strains_from_file', { path, policy, dryRun });
+};
+
+export const exportFilteredStrains = async (
+  filter: AnyFilter<StrainFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_strains', { filter, path, format });
 };
 
 export const updateStrain = async (
@@ -55,10 +117,17 @@ export const updateStrain = async (
   await invoke('update_strain', { name, newStrain });
 };
 
+export const previewDeleteStrains = async (
+  filter: AnyFilter<StrainFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_strains', { filter });
+};
+
 export const deleteFilteredStrains = async (
-  filter: FilterGroup<StrainFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_strains', { filter });
+  filter: AnyFilter<StrainFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_strains', { filter, mode });
 };
 
 export const deleteStrain = async (strain: db_Strain): Promise<void> => {
@@ -69,3 +138,7 @@ export const deleteStrain = async (strain: db_Strain): Promise<void> => {
 
   await deleteFilteredStrains(filter);
 };
+
+export const validateStrains = async (): Promise<db_GenotypeMismatch[]> => {
+  return await invoke('validate_strains');
+};
@@ -1,5 +1,16 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
 import { type db_StrainAllele } from 'models/db/db_StrainAllele';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type StrainAlleleFieldName } from 'models/db/filter/db_StrainAlleleFieldName';
 import { type FilterGroup } from 'models/db/filter/FilterGroup';
 
@@ -8,15 +19,37 @@ export const getStrainAlleles = async (): Promise<db_StrainAllele[]> => {
 };
 
 export const getFilteredStrainAlleles = async (
-  filter: FilterGroup<StrainAlleleFieldName>
+  filter: AnyFilter<StrainAlleleFieldName>
 ): Promise<db_StrainAllele[]> => {
   return await invoke('get_filtered_strain_alleles', {
     filter,
   });
 };
 
+export const getFilteredStrainAllelesPage = async (
+  filter: AnyFilter<StrainAlleleFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_StrainAllele>> => {
+  return await invoke('get_filtered_strain_alleles_page', { filter, page });
+};
+
+export const queryStrainAlleles = async (
+  filter: AnyFilter<StrainAlleleFieldName>,
+  facets: StrainAlleleFieldName[] = []
+): Promise<db_QueryResult<db_StrainAllele, StrainAlleleFieldName>> => {
+  return await invoke('query_strain_alleles', { filter, facets });
+};
+
+export const aggregateStrainAlleles = async (
+  filter: AnyFilter<StrainAlleleFieldName>,
+  groupBy: StrainAlleleFieldName,
+  metrics: Array<db_Metric<StrainAlleleFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_strain_alleles', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredStrainAlleles = async (
-  filter: FilterGroup<StrainAlleleFieldName>
+  filter: AnyFilter<StrainAlleleFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_strain_alleles', {
     filter,
@@ -30,15 +63,40 @@ export const insertDbStrainAllele = async (
 };
 
 export const insertStrainAllelesFromFile = async (
-  path: string
-): Promise<void> => {
-  await invoke('insert_strain_alleles_from_file', { path });
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_strain_alleles_from_file', {
+    path,
+    policy,
+    dryRun,
+  });
+};
+
+export const exportFilteredStrainAlleles = async (
+  filter: AnyFilter<StrainAlleleFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_strain_alleles', {
+    filter,
+    path,
+    format,
+  });
+};
+
+export const previewDeleteStrainAlleles = async (
+  filter: AnyFilter<StrainAlleleFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_strain_alleles', { filter });
 };
 
 export const deleteFilteredStrainAlleles = async (
-  filter: FilterGroup<StrainAlleleFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_strain_alleles', { filter });
+  filter: AnyFilter<StrainAlleleFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_strain_alleles', { filter, mode });
 };
 
 export const deleteStrainAllele = async (
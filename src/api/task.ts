@@ -1,6 +1,11 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type TaskFieldName } from 'models/db/filter/db_TaskFieldName';
-import { type FilterGroup } from 'models/db/filter/FilterGroup';
 import { type db_Task } from 'models/db/task/db_Task';
 
 export const getTasks = async (): Promise<db_Task[]> => {
@@ -8,11 +13,33 @@ export const getTasks = async (): Promise<db_Task[]> => {
 };
 
 export const getFilteredTasks = async (
-  filter: FilterGroup<TaskFieldName>
+  filter: AnyFilter<TaskFieldName>
 ): Promise<db_Task[]> => {
   return await invoke('get_filtered_tasks', { filter });
 };
 
+export const getFilteredTasksPage = async (
+  filter: AnyFilter<TaskFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_Task>> => {
+  return await invoke('get_filtered_tasks_page', { filter, page });
+};
+
+export const queryTasks = async (
+  filter: AnyFilter<TaskFieldName>,
+  facets: TaskFieldName[] = []
+): Promise<db_QueryResult<db_Task, TaskFieldName>> => {
+  return await invoke('query_tasks', { filter, facets });
+};
+
+export const aggregateTasks = async (
+  filter: AnyFilter<TaskFieldName>,
+  groupBy: TaskFieldName,
+  metrics: Array<db_Metric<TaskFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_tasks', { filter, groupBy, metrics });
+};
+
 export const insertTasks = async (records: db_Task[]): Promise<void> => {
   await Promise.all(
     records.map(async (record) => {
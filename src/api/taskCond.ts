@@ -1,5 +1,15 @@
 import { invoke } from '@tauri-apps/api/tauri';
-import { type FilterGroup } from 'models/db/filter/FilterGroup';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type TaskConditionFieldName } from 'models/db/filter/db_TaskConditionFieldName';
 import { type iTaskCondition } from 'models/frontend/Task/TaskCondition';
 
@@ -8,13 +18,76 @@ export const getTaskConditions = async (): Promise<iTaskCondition[]> => {
 };
 
 export const getFilteredTaskConditions = async (
-  filter: FilterGroup<TaskConditionFieldName>
+  filter: AnyFilter<TaskConditionFieldName>
 ): Promise<iTaskCondition[]> => {
   return await invoke('get_filtered_task_conditions', { filter });
 };
 
+export const getFilteredTaskConditionsPage = async (
+  filter: AnyFilter<TaskConditionFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<iTaskCondition>> => {
+  return await invoke('get_filtered_task_conditions_page', { filter, page });
+};
+
+export const queryTaskConditions = async (
+  filter: AnyFilter<TaskConditionFieldName>,
+  facets: TaskConditionFieldName[] = []
+): Promise<db_QueryResult<iTaskCondition, TaskConditionFieldName>> => {
+  return await invoke('query_task_conditions', { filter, facets });
+};
+
+export const aggregateTaskConditions = async (
+  filter: AnyFilter<TaskConditionFieldName>,
+  groupBy: TaskConditionFieldName,
+  metrics: Array<db_Metric<TaskConditionFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_task_conditions', {
+    filter,
+    groupBy,
+    metrics,
+  });
+};
+
 export const insertDbTaskCondition = async (
   record: iTaskCondition
 ): Promise<void> => {
   await invoke('insert_task_condition', { task: record });
 };
+
+export const insertTaskConditionsFromFile = async (
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_task_conditions_from_file', {
+    path,
+    policy,
+    dryRun,
+  });
+};
+
+export const exportFilteredTaskConditions = async (
+  filter: AnyFilter<TaskConditionFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_task_conditions', {
+    filter,
+    path,
+    format,
+  });
+};
+
+export const previewDeleteTaskConditions = async (
+  filter: AnyFilter<TaskConditionFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_task_conditions', { filter });
+};
+
+export const deleteFilteredTaskConditions = async (
+  filter: AnyFilter<TaskConditionFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_task_conditions', { filter, mode });
+};
@@ -1,5 +1,10 @@
 import { invoke } from '@tauri-apps/api/tauri';
-import { type FilterGroup } from 'models/db/filter/FilterGroup';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type TaskDependencyFieldName } from 'models/db/filter/db_TaskDependencyFieldName';
 import { type iTaskDependency } from 'models/frontend/Task/TaskDependency';
 
@@ -8,13 +13,45 @@ export const getTaskDependencies = async (): Promise<iTaskDependency[]> => {
 };
 
 export const getFilteredTaskDependencies = async (
-  filter: FilterGroup<TaskDependencyFieldName>
+  filter: AnyFilter<TaskDependencyFieldName>
 ): Promise<iTaskDependency[]> => {
   return await invoke('get_filtered_task_dependency', { filter });
 };
 
+export const getFilteredTaskDependenciesPage = async (
+  filter: AnyFilter<TaskDependencyFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<iTaskDependency>> => {
+  return await invoke('get_filtered_task_dependencies_page', { filter, page });
+};
+
+export const queryTaskDependencies = async (
+  filter: AnyFilter<TaskDependencyFieldName>,
+  facets: TaskDependencyFieldName[] = []
+): Promise<db_QueryResult<iTaskDependency, TaskDependencyFieldName>> => {
+  return await invoke('query_task_dependencies', { filter, facets });
+};
+
+export const aggregateTaskDependencies = async (
+  filter: AnyFilter<TaskDependencyFieldName>,
+  groupBy: TaskDependencyFieldName,
+  metrics: Array<db_Metric<TaskDependencyFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_task_dependencies', {
+    filter,
+    groupBy,
+    metrics,
+  });
+};
+
 export const insertDbTaskDependency = async (
   record: iTaskDependency
 ): Promise<void> => {
   await invoke('insert_task_dependency', { task: record });
 };
+
+export const deleteDbTaskDependency = async (
+  record: iTaskDependency
+): Promise<void> => {
+  await invoke('delete_task_dependency', { task: record });
+};
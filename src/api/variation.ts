@@ -1,5 +1,16 @@
 import { invoke } from '@tauri-apps/api/tauri';
+import { type db_AggregateRow } from 'models/db/db_AggregateRow';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
+import { type db_ConflictPolicy } from 'models/db/db_ConflictPolicy';
+import { type db_DeleteMode } from 'models/db/db_DeleteMode';
+import { type db_DeleteReport } from 'models/db/db_DeleteReport';
+import { type db_ExportFormat } from 'models/db/db_ExportFormat';
 import { type db_Variation } from 'models/db/db_Variation';
+import { type db_Metric } from 'models/db/db_Metric';
+import { type db_Page } from 'models/db/db_Page';
+import { type db_PageRequest } from 'models/db/db_PageRequest';
+import { type db_QueryResult } from 'models/db/db_QueryResult';
+import { type AnyFilter } from 'models/db/filter/AnyFilter';
 import { type VariationFieldName } from 'models/db/filter/db_VariationFieldName';
 import {
   type FilterGroup,
@@ -12,15 +23,37 @@ export const getVariations = async (): Promise<db_Variation[]> => {
 };
 
 export const getFilteredVariations = async (
-  filter: FilterGroup<VariationFieldName>
+  filter: AnyFilter<VariationFieldName>
 ): Promise<db_Variation[]> => {
   return await invoke('get_filtered_variations', {
     filter,
   });
 };
 
+export const getFilteredVariationsPage = async (
+  filter: AnyFilter<VariationFieldName>,
+  page: db_PageRequest
+): Promise<db_Page<db_Variation>> => {
+  return await invoke('get_filtered_variations_page', { filter, page });
+};
+
+export const queryVariations = async (
+  filter: AnyFilter<VariationFieldName>,
+  facets: VariationFieldName[] = []
+): Promise<db_QueryResult<db_Variation, VariationFieldName>> => {
+  return await invoke('query_variations', { filter, facets });
+};
+
+export const aggregateVariations = async (
+  filter: AnyFilter<VariationFieldName>,
+  groupBy: VariationFieldName,
+  metrics: Array<db_Metric<VariationFieldName>> = []
+): Promise<db_AggregateRow[]> => {
+  return await invoke('aggregate_variations', { filter, groupBy, metrics });
+};
+
 export const getCountFilteredVariations = async (
-  filter: FilterGroup<VariationFieldName>
+  filter: AnyFilter<VariationFieldName>
 ): Promise<number> => {
   return await invoke('get_count_filtered_variations', {
     filter,
@@ -48,14 +81,33 @@ export const insertDbVariation = async (
   await invoke('insert_variation', { variation: record });
 };
 
-export const insertVariationsFromFile = async (path: string): Promise<void> => {
-  await invoke('insert_variations_from_file', { path });
+export const insertVariationsFromFile = async (
+  path: string,
+  policy: db_ConflictPolicy = 'Ignore',
+  dryRun = false
+): Promise<db_BulkReport> => {
+  return await invoke('insert_variations_from_file', { path, policy, dryRun });
+};
+
+export const exportFilteredVariations = async (
+  filter: AnyFilter<VariationFieldName>,
+  path: string,
+  format: db_ExportFormat = 'Csv'
+): Promise<number> => {
+  return await invoke('export_filtered_variations', { filter, path, format });
+};
+
+export const previewDeleteVariations = async (
+  filter: AnyFilter<VariationFieldName>
+): Promise<db_DeleteReport> => {
+  return await invoke('preview_delete_variations', { filter });
 };
 
 export const deleteFilteredVariations = async (
-  filter: FilterGroup<VariationFieldName>
-): Promise<void> => {
-  await invoke('delete_filtered_variations', { filter });
+  filter: AnyFilter<VariationFieldName>,
+  mode: db_DeleteMode = 'Restrict'
+): Promise<db_DeleteReport> => {
+  return await invoke('delete_filtered_variations', { filter, mode });
 };
 
 export const deleteVariation = async (
@@ -0,0 +1,18 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { type db_Workspace } from 'models/db/db_Workspace';
+
+export const createWorkspace = async (path: string): Promise<db_Workspace> => {
+  return await invoke('create_workspace', { path });
+};
+
+export const openWorkspace = async (path: string): Promise<db_Workspace> => {
+  return await invoke('open_workspace', { path });
+};
+
+export const listWorkspaces = async (): Promise<db_Workspace[]> => {
+  return await invoke('list_workspaces');
+};
+
+export const closeWorkspace = async (path: string): Promise<void> => {
+  await invoke('close_workspace', { path });
+};
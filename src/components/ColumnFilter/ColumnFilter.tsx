@@ -196,7 +196,8 @@ export const ColumnFilter = <T,>(
       ...props.columnFilters,
       createNewFilter(
         firstFilterType,
-        Array<FormValueType>(filterValuesLen).fill('')
+        Array<FormValueType>(filterValuesLen).fill(''),
+        props.field.type === 'number'
       ),
     ]);
   };
@@ -272,7 +273,11 @@ const FilterEntry = <T,>(props: FilterEntryProps<T>): React.JSX.Element => {
     values: FormValueType[]
   ): void => {
     const name = getFilterType(filterType);
-    const newFilterType = createNewFilter(name, values);
+    const newFilterType = createNewFilter(
+      name,
+      values,
+      props.field.type === 'number'
+    );
     setLocalFilterValues(values);
     props.setFilter(newFilterType);
   };
@@ -285,7 +290,8 @@ const FilterEntry = <T,>(props: FilterEntryProps<T>): React.JSX.Element => {
     }
     const newFilterType = createNewFilter(
       e.target.value as FilterType,
-      newFilterValues
+      newFilterValues,
+      props.field.type === 'number'
     );
     setLocalFilterValues(newFilterValues);
     props.setFilter(newFilterType);
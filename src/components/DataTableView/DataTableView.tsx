@@ -2,6 +2,7 @@ import { open } from '@tauri-apps/api/dialog';
 import { type Field } from 'components/ColumnFilter/ColumnFilter';
 import DataImportForm from 'components/DataInputForm/DataInputForm';
 import { Table, type ColumnDefinitionType } from 'components/Table/Table';
+import { type db_BulkReport } from 'models/db/db_BulkReport';
 import { type FilterGroup } from 'models/db/filter/FilterGroup';
 import { useEffect, useState } from 'react';
 import { toast } from 'react-toastify';
@@ -15,7 +16,7 @@ interface DataTableProps<T, K> {
   insertRecord: (record: T) => Promise<void>;
   getFilteredRecords: (filterObj: FilterGroup<K>) => Promise<T[]>;
   getCountFilteredRecords: (filterObj: FilterGroup<K>) => Promise<number>;
-  insertRecordsFromFile: (path: string) => Promise<void>;
+  insertRecordsFromFile: (path: string) => Promise<db_BulkReport>;
   deleteRecord: (row: T) => Promise<void>;
   updateRecord?: (
     row: T,
@@ -98,9 +99,25 @@ const DataTableView = <T, K>(
         ],
       })) as string | null;
       if (filepath === null) return;
-      await props.insertRecordsFromFile(filepath);
+      const report = await props.insertRecordsFromFile(filepath);
+      if (!report.committed) {
+        const errors = report.rejected
+          .slice(0, 5)
+          .map(
+            (row) =>
+              `line ${row.line}${
+                row.column !== null ? ` (${row.column})` : ''
+              }: ${row.reason}`
+          );
+        toast.error(
+          `Nothing was imported, ${report.rejected.length} rows were rejected: ${errors.join('; ')}`
+        );
+        return;
+      }
       refresh();
-      toast.success('Successfully imported data');
+      toast.success(
+        `Imported ${report.inserted.length} rows, updated ${report.updated.length}, skipped ${report.skipped.length} existing rows`
+      );
     } catch (e) {
       toast.error(
         'An error has occured when importing data: ' + JSON.stringify(e)
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_AuditProblemKind } from './db_AuditProblemKind';
+
+export interface db_AuditProblem {
+  table: string;
+  key: string;
+  kind: db_AuditProblemKind;
+  message: string;
+}
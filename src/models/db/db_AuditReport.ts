@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_AuditProblem } from './db_AuditProblem';
+import type { db_AuditRow } from './db_AuditRow';
+
+export interface db_AuditReport {
+  problems: db_AuditProblem[];
+  deleted: db_AuditRow[];
+  created: db_AuditRow[];
+}
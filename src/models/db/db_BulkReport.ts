@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_RejectedRow } from './db_RejectedRow';
+import type { db_RowDiff } from './db_RowDiff';
+
+export interface db_BulkReport {
+  inserted: number[];
+  updated: db_RowDiff[];
+  skipped: number[];
+  rejected: db_RejectedRow[];
+  committed: boolean;
+}
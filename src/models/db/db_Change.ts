@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface db_Change {
+  id: number;
+  description: string;
+  createdAt: string;
+  undone: boolean;
+  rows: number;
+}
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChromosomeName } from './filter/db_ChromosomeName';
+import type { db_MapLocus } from './db_MapLocus';
+import type { db_SuppressedRegion } from './db_SuppressedRegion';
+
+export interface db_ChromosomeMap {
+  chromosome: ChromosomeName;
+  loci: db_MapLocus[];
+  suppressedRegions: db_SuppressedRegion[];
+}
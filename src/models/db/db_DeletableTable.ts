@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type db_DeletableTable =
+  | 'Alleles'
+  | 'AlleleExprs'
+  | 'Conditions'
+  | 'ExprRelations'
+  | 'Genes'
+  | 'Phenotypes'
+  | 'Strains'
+  | 'StrainAlleles'
+  | 'TaskConditions'
+  | 'TaskDependencies'
+  | 'Variations';
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_DeleteReport } from './db_DeleteReport';
+
+export interface db_DeleteAllPreview {
+  report: db_DeleteReport;
+  confirmation: string;
+}
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_DependentRows } from './db_DependentRows';
+
+export interface db_DeleteReport {
+  deleted: number;
+  dependents: db_DependentRows[];
+}
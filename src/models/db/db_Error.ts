@@ -5,4 +5,12 @@ export type db_Error =
   | { Insert: string }
   | { Update: string }
   | { Delete: string }
-  | { BulkInsert: string };
+  | { BulkInsert: string }
+  | { Genetics: string }
+  | { Export: string }
+  | { Backup: string }
+  | { Workspace: string }
+  | { Audit: string }
+  | { Journal: string }
+  | { UnfilteredDelete: string }
+  | { InvalidFilter: string };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_FacetCount } from './db_FacetCount';
+
+export interface db_Facet<F> {
+  field: F;
+  counts: db_FacetCount[];
+}
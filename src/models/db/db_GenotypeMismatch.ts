@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_StrainAllele } from './db_StrainAllele';
+
+export interface db_GenotypeMismatch {
+  strainName: string;
+  genotype: string;
+  parsedAlleles: db_StrainAllele[];
+  storedAlleles: db_StrainAllele[];
+  parseError: string | null;
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_Gene } from './db_Gene';
+
+export interface db_LocatedGene {
+  gene: db_Gene;
+  geneticLoc: number | null;
+  estimated: boolean;
+}
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_LocusKey } from './db_LocusKey';
+
+export interface db_MapLocus {
+  key: db_LocusKey;
+  name: string;
+  physLoc: number | null;
+  geneticLoc: number;
+  estimated: boolean;
+  distance: number | null;
+  alleles: string[];
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_OffspringAllele } from './db_OffspringAllele';
+
+export interface db_Offspring {
+  genotype: string;
+  alleles: db_OffspringAllele[];
+  probability: number;
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_RowCount } from './db_RowCount';
+
+export interface db_Page<R> {
+  rows: R[];
+  nextCursor: string | null;
+  total: db_RowCount | null;
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_Facet } from './db_Facet';
+
+export interface db_QueryResult<R, F> {
+  rows: R[];
+  total: number;
+  facets: Array<db_Facet<F>>;
+}
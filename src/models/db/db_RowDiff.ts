@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_FieldChange } from './db_FieldChange';
+
+export interface db_RowDiff {
+  line: number;
+  changes: db_FieldChange[];
+}
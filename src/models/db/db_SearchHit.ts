@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_SearchKey } from './db_SearchKey';
+
+export interface db_SearchHit {
+  key: db_SearchKey;
+  snippet: string;
+}
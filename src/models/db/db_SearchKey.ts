@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type db_SearchKey =
+  | { Gene: string }
+  | { Allele: string }
+  | { Strain: string }
+  | { Phenotype: [string, boolean] }
+  | { CrossDesign: string };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_BulkReport } from './db_BulkReport';
+
+export interface db_SeedFileReport {
+  file: string;
+  report: db_BulkReport;
+}
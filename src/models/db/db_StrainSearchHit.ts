@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_Strain } from './db_Strain';
+
+export interface db_StrainSearchHit {
+  strain: db_Strain;
+  matchingAlleles: string[];
+}
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_StrainSearchHit } from './db_StrainSearchHit';
+
+export interface db_StrainSearchResults {
+  hits: db_StrainSearchHit[];
+  total: number;
+}
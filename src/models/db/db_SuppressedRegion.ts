@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { db_LocusKey } from './db_LocusKey';
+
+export interface db_SuppressedRegion {
+  key: db_LocusKey;
+  name: string;
+  physStart: number;
+  physEnd: number;
+  geneticSpan: [number, number] | null;
+}
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FilterGroup } from './FilterGroup';
+import type { FilterTree } from './FilterTree';
+
+export type AnyFilter<T> = FilterGroup<T> | FilterTree<T>;
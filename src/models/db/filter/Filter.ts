@@ -1,13 +1,22 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FilterValue } from './FilterValue';
 
 export type Filter =
-  | { Range: [string, boolean, string, boolean] }
-  | { LessThan: [string, boolean] }
-  | { GreaterThan: [string, boolean] }
-  | { Equal: string }
-  | { NotEqual: string }
+  | { Range: [FilterValue, boolean, FilterValue, boolean] }
+  | { LessThan: [FilterValue, boolean] }
+  | { GreaterThan: [FilterValue, boolean] }
+  | { Equal: FilterValue }
+  | { NotEqual: FilterValue }
+  | { In: FilterValue[] }
+  | { NotIn: FilterValue[] }
   | { Like: string }
+  | { NotLike: string }
+  | { StartsWith: string }
+  | { EndsWith: string }
+  | { Glob: string }
+  | { Regex: string }
   | 'Null'
   | 'NotNull'
   | 'True'
-  | 'False';
+  | 'False'
+  | { CaseSensitive: [boolean, Filter] };
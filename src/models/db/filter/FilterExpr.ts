@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Filter } from './Filter';
+
+export type FilterExpr<T> =
+  | { And: Array<FilterExpr<T>> }
+  | { Or: Array<FilterExpr<T>> }
+  | { Not: FilterExpr<T> }
+  | { Leaf: [T, Filter] };
@@ -1,4 +1,5 @@
 import { type Filter } from 'models/db/filter/Filter';
+import { type FilterValue } from 'models/db/filter/FilterValue';
 import { type Order } from 'models/db/filter/Order';
 
 export type FilterTuple<T> = [T, Filter];
@@ -103,7 +104,7 @@ export const getValuesForFilterType = (filter: Filter): FormValueType[] => {
     case 'Equal':
     case 'NotEqual':
     case 'Like':
-      return [Object.values(filter)[0] as string];
+      return [Object.values(filter)[0] as FormValueType];
   }
   return [];
 };
@@ -176,33 +177,54 @@ export const getFilterType = (filter: Filter): FilterType => {
 
 export type FormValueType = string | number | boolean;
 
+/**
+ * @param value The value from a form input
+ * @param numeric Whether the column holds numbers, which the backend won't compare to text
+ * @returns The value to filter on
+ */
+const toFilterValue = (
+  value: FormValueType,
+  numeric: boolean
+): FilterValue => {
+  if (numeric && value !== '' && !isNaN(Number(value))) {
+    return Number(value);
+  }
+  return value.toString();
+};
+
 /**
  * @param type The type of filter you want to create
  * @param values The values you want to use for the filter, this will be different lengths for different filter types
+ * @param numeric Whether the filtered column holds numbers
  * @returns Filter of given type filled with the values you provide
  */
 export const createNewFilter = (
   type: FilterType,
-  values: FormValueType[]
+  values: FormValueType[],
+  numeric = false
 ): Filter => {
   switch (type) {
     case 'Range':
       return {
         Range: [
-          values[0].toString(),
+          toFilterValue(values[0], numeric),
           values[1] as boolean,
-          values[2].toString(),
+          toFilterValue(values[2], numeric),
           values[3] as boolean,
         ],
       };
     case 'LessThan':
-      return { LessThan: [values[0].toString(), values[1] as boolean] };
+      return {
+        LessThan: [toFilterValue(values[0], numeric), values[1] as boolean],
+      };
     case 'GreaterThan':
-      return { GreaterThan: [values[0].toString(), values[1] as boolean] };
+      return {
+        GreaterThan: [toFilterValue(values[0], numeric), values[1] as boolean],
+      };
     case 'Equal':
-      return { Equal: values[0].toString() };
+      return { Equal: toFilterValue(values[0], numeric) };
     case 'NotEqual':
-      return { NotEqual: values[0].toString() };
+      return { NotEqual: toFilterValue(values[0], numeric) };
     case 'Like':
       return { Like: values[0].toString() };
     case 'Null':
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FilterExpr } from './FilterExpr';
+import type { Order } from './Order';
+
+export interface FilterTree<T> {
+  filter: FilterExpr<T>;
+  orderBy: Array<[T, Order]>;
+  limit?: number;
+  offset?: number;
+}
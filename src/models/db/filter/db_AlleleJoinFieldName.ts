@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AlleleFieldName } from './db_AlleleFieldName';
+import type { GeneFieldName } from './db_GeneFieldName';
+import type { VariationFieldName } from './db_VariationFieldName';
+
+export type AlleleJoinFieldName =
+  | { Allele: AlleleFieldName }
+  | { Gene: GeneFieldName }
+  | { Variation: VariationFieldName };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AlleleExpressionFieldName } from './db_AlleleExpressionFieldName';
+import type { AlleleFieldName } from './db_AlleleFieldName';
+import type { GeneFieldName } from './db_GeneFieldName';
+import type { StrainAlleleFieldName } from './db_StrainAlleleFieldName';
+import type { StrainFieldName } from './db_StrainFieldName';
+import type { VariationFieldName } from './db_VariationFieldName';
+
+export type StrainJoinFieldName =
+  | { Strain: StrainFieldName }
+  | { StrainAllele: StrainAlleleFieldName }
+  | { Allele: AlleleFieldName }
+  | { Gene: GeneFieldName }
+  | { Variation: VariationFieldName }
+  | { AlleleExpr: AlleleExpressionFieldName };